@@ -0,0 +1,203 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use eframe::egui;
+
+use crate::hex_view::{hex_view_combo, HexView};
+
+// Only compare this many overlapping bytes per shift to keep the search responsive on large files
+const MAX_COMPARE_LEN: usize = 0x10000;
+const NUM_RESULTS: usize = 5;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Alignment {
+    /// How many bytes file B is shifted forward relative to file A
+    pub shift: isize,
+    pub score: f32,
+}
+
+/// Scores each shift by the bytes that match where the files overlap. Scores are out of the
+/// bytes an unshifted comparison would cover, so a tiny overlap at a large shift can't win.
+pub fn find_alignments(a: &[u8], b: &[u8], max_shift: usize) -> Vec<Alignment> {
+    let compare_len = a.len().min(b.len()).min(MAX_COMPARE_LEN);
+    let max_shift = max_shift as isize;
+    let mut ret: Vec<Alignment> = (-max_shift..=max_shift)
+        .filter_map(|shift| {
+            let (a, b) = match shift >= 0 {
+                true => (a, b.get(shift as usize..)?),
+                false => (a.get(shift.unsigned_abs()..)?, b),
+            };
+
+            let len = a.len().min(b.len()).min(MAX_COMPARE_LEN);
+            if len == 0 {
+                return None;
+            }
+
+            let matches = a[..len]
+                .iter()
+                .zip(b[..len].iter())
+                .filter(|(a, b)| a == b)
+                .count();
+
+            Some(Alignment {
+                shift,
+                score: matches as f32 / compare_len as f32,
+            })
+        })
+        .collect();
+
+    ret.sort_by(|a, b| b.score.total_cmp(&a.score));
+    ret.truncate(NUM_RESULTS);
+    ret
+}
+
+pub struct AlignmentTool {
+    pub show: bool,
+    file_a: Option<usize>,
+    file_b: Option<usize>,
+    max_shift: usize,
+    results: Vec<Alignment>,
+    /// Where the search running in the background puts its results
+    pending: Option<Arc<Mutex<Option<Vec<Alignment>>>>>,
+    sync_offset: isize,
+}
+
+impl Default for AlignmentTool {
+    fn default() -> Self {
+        Self {
+            show: false,
+            file_a: None,
+            file_b: None,
+            max_shift: 0x100,
+            results: Vec::new(),
+            pending: None,
+            sync_offset: 0,
+        }
+    }
+}
+
+impl AlignmentTool {
+    pub fn display(&mut self, ctx: &egui::Context, hex_views: &mut [HexView]) {
+        let mut open = self.show;
+
+        egui::Window::new("Find alignment offset")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                hex_view_combo(ui, "File A", &mut self.file_a, hex_views);
+                hex_view_combo(ui, "File B", &mut self.file_b, hex_views);
+
+                ui.horizontal(|ui| {
+                    ui.label("Max shift");
+                    ui.add(
+                        egui::DragValue::new(&mut self.max_shift)
+                            .clamp_range(0..=0x10000)
+                            .hexadecimal(1, false, true),
+                    );
+                });
+
+                let hv_a = self
+                    .file_a
+                    .and_then(|id| hex_views.iter().position(|hv| hv.id == id));
+                let hv_b = self
+                    .file_b
+                    .and_then(|id| hex_views.iter().position(|hv| hv.id == id));
+
+                let finished = self
+                    .pending
+                    .as_ref()
+                    .and_then(|pending| pending.lock().unwrap().take());
+                if let Some(results) = finished {
+                    self.pending = None;
+                    self.results = results;
+                    if let Some(best) = self.results.first() {
+                        self.sync_offset = best.shift;
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            hv_a.is_some() && hv_b.is_some() && self.pending.is_none(),
+                            egui::Button::new("Search"),
+                        )
+                        .clicked()
+                    {
+                        // Only the bytes the search can reach are copied to the search thread
+                        let len = self.max_shift + MAX_COMPARE_LEN;
+                        let a = &hex_views[hv_a.unwrap()].file.data;
+                        let b = &hex_views[hv_b.unwrap()].file.data;
+                        let a = a[..a.len().min(len)].to_vec();
+                        let b = b[..b.len().min(len)].to_vec();
+                        let max_shift = self.max_shift;
+
+                        let pending = Arc::new(Mutex::new(None));
+                        self.pending = Some(pending.clone());
+                        let ctx = ui.ctx().clone();
+                        thread::spawn(move || {
+                            *pending.lock().unwrap() = Some(find_alignments(&a, &b, max_shift));
+                            ctx.request_repaint();
+                        });
+                    }
+                    if self.pending.is_some() {
+                        ui.spinner();
+                    }
+                });
+
+                egui::Grid::new("alignment_results")
+                    .striped(true)
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        for result in self.results.iter() {
+                            if ui
+                                .selectable_label(
+                                    self.sync_offset == result.shift,
+                                    egui::RichText::new(format!("{:+}", result.shift)).monospace(),
+                                )
+                                .clicked()
+                            {
+                                self.sync_offset = result.shift;
+                            }
+                            ui.label(format!("{:.2}%", result.score * 100.0));
+                            ui.end_row();
+                        }
+                    });
+
+                ui.horizontal(|ui| {
+                    ui.label("Sync offset");
+                    ui.add(egui::DragValue::new(&mut self.sync_offset));
+
+                    if ui
+                        .add_enabled(hv_a.is_some() && hv_b.is_some(), egui::Button::new("Apply"))
+                        .on_hover_text("Scroll file B so it lines up with file A")
+                        .clicked()
+                    {
+                        let pos = hex_views[hv_a.unwrap()].cur_pos as isize + self.sync_offset;
+                        hex_views[hv_b.unwrap()].set_cur_pos(pos.max(0) as usize);
+                    }
+                });
+            });
+
+        self.show = open;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_alignments_prefers_the_real_shift_over_tiny_overlaps() {
+        let a: Vec<u8> = (0..64).map(|i| (i * 7 % 251) as u8).collect();
+        let mut b = vec![0xFF; 3];
+        b.extend_from_slice(&a);
+        b[10] ^= 1;
+        // Shifting b by its length minus one leaves a single matching byte
+        b.push(a[0]);
+
+        let results = find_alignments(&a, &b, b.len() - 1);
+        assert_eq!(results[0].shift, 3);
+        assert_eq!(results[0].score, 63.0 / 64.0);
+    }
+}
@@ -1,6 +1,7 @@
 use std::{
     path::{Path, PathBuf},
     sync::atomic::Ordering,
+    time::{Duration, Instant},
 };
 
 use anyhow::Error;
@@ -11,32 +12,119 @@ use eframe::{
 use egui_modal::Modal;
 
 use crate::{
+    alignment_tool::AlignmentTool,
     bin_file::BinFile,
-    config::{read_json_config, write_json_config, Config, FileConfig},
-    diff_state::DiffState,
-    hex_view::{HexView, HexViewSelection, HexViewSelectionSide, HexViewSelectionState},
-    settings::{read_json_settings, write_json_settings, ByteGrouping, Settings},
+    config::{
+        find_config_path, get_config_path, read_json_config, write_json_config, Config, FileConfig,
+        MIN_POLL_INTERVAL_MS,
+    },
+    data_viewer::XOR_PREVIEW_LEN,
+    diff_state::{self, DiffState, ROLLUP_BLOCK_SIZE},
+    export,
+    goto::{self, GotoVariables},
+    hex_receiver::HexReceiverDialog,
+    hex_view::{
+        HexView, HexViewAction, HexViewSelection, HexViewSelectionMode, HexViewSelectionRange,
+        HexViewSelectionSide, HexViewSelectionState, OtherView,
+    },
+    ignore_mask::IgnoreMaskPanel,
+    map_tool::find_associated_map,
+    padding_tool::PaddingTool,
+    patch::{PatchReview, StripDialog},
+    run_summary::RunSummary,
+    search::{self, SearchMatches, SearchMode, ValueWidth},
+    settings::{
+        read_json_settings, write_json_settings, ByteGrouping, Color, CopyFormat, MemorySlot,
+        NullDisplay, Settings, ENTROPY_WINDOW_RANGE, FONT_SIZE_RANGE, MAX_BYTES_PER_ROW,
+        MEMORY_SLOT_PREVIEW_LEN, NUM_MEMORY_SLOTS,
+    },
+    watch::{Watch, WatchPanel, MAX_WATCHES},
 };
 
-#[derive(Default)]
+const MEMORY_SLOT_KEYS: [egui::Key; NUM_MEMORY_SLOTS] = [
+    egui::Key::Num1,
+    egui::Key::Num2,
+    egui::Key::Num3,
+    egui::Key::Num4,
+    egui::Key::Num5,
+    egui::Key::Num6,
+    egui::Key::Num7,
+    egui::Key::Num8,
+];
+
 struct GotoModal {
     value: String,
     status: String,
+    /// Move every view instead of only the last selected one
+    all_views: bool,
+}
+
+impl Default for GotoModal {
+    fn default() -> Self {
+        Self {
+            value: String::new(),
+            status: String::new(),
+            all_views: true,
+        }
+    }
+}
+
+#[derive(Default)]
+struct SearchModal {
+    value: String,
+    mode: SearchMode,
+    width: ValueWidth,
+    status: String,
+    /// View the matches were found in
+    target: Option<usize>,
+    /// Index of the match last moved to
+    current: Option<usize>,
+}
+
+#[derive(Default)]
+enum OverwriteTarget {
+    #[default]
+    Config,
+    /// The file of the hex view with this id
+    File(usize),
 }
 
 #[derive(Default)]
 struct OverwriteModal {
     open: bool,
+    target: OverwriteTarget,
+}
+
+/// How hex views are arranged in the main window
+#[derive(Clone, Copy, Default, PartialEq)]
+enum ViewLayout {
+    /// Each view in its own window
+    #[default]
+    Floating,
+    /// One view at a time, picked from a tab bar
+    Tabbed,
+}
+
+#[derive(Clone, Copy)]
+enum TileLayout {
+    Horizontal,
+    Vertical,
 }
 
 struct Options {
     mirror_selection: bool,
+    /// Keep the selection from changing until it's cleared or unlocked
+    lock_selection: bool,
+    /// Keep going when keyboard navigation lands on a page of only zero bytes
+    skip_null_pages: bool,
 }
 
 impl Default for Options {
     fn default() -> Self {
         Self {
             mirror_selection: true,
+            lock_selection: false,
+            skip_null_pages: false,
         }
     }
 }
@@ -47,6 +135,8 @@ pub struct BdiffApp {
     hex_views: Vec<HexView>,
     diff_state: DiffState,
     goto_modal: GotoModal,
+    search_modal: SearchModal,
+    view_preset_name: String,
     overwrite_modal: OverwriteModal,
     scroll_overflow: f32,
     options: Options,
@@ -54,6 +144,29 @@ pub struct BdiffApp {
     selecting_hv: Option<usize>,
     last_selected_hv: Option<usize>,
     settings_open: bool,
+    bytes_per_row_input: usize,
+    map_file_suffixes_input: String,
+    diff_rollup_open: bool,
+    diff_regions_open: bool,
+    diff_legend_open: bool,
+    /// A short message shown over the views until the time passes
+    toast: Option<(String, Instant)>,
+    /// Where the base file was scrolled to when the other views were last lined up with it
+    aligned_base_pos: Option<usize>,
+    pending_tile_layout: Option<TileLayout>,
+    /// Dock exactly two views side by side in a shared panel
+    paired_views: bool,
+    view_layout: ViewLayout,
+    /// View shown in the tabbed layout
+    active_tab: Option<usize>,
+    alignment_tool: AlignmentTool,
+    padding_tool: PaddingTool,
+    run_summary: RunSummary,
+    ignore_mask: IgnoreMaskPanel,
+    watch_panel: WatchPanel,
+    strip_dialog: StripDialog,
+    hex_receiver: HexReceiverDialog,
+    patch_review: PatchReview,
     settings: Settings,
     config: Config,
     started_with_arguments: bool,
@@ -79,14 +192,14 @@ impl BdiffApp {
         let mut ret = Self {
             next_hv_id: 0,
             hex_views,
+            bytes_per_row_input: settings.bytes_per_row,
+            map_file_suffixes_input: settings.map_file_suffixes.join(", "),
             settings,
             started_with_arguments,
             ..Default::default()
         };
 
         log::info!("Loading project config from file");
-        let config_path = Path::new("bdiff.json");
-
         let config = if started_with_arguments {
             let file_configs = paths
                 .into_iter()
@@ -96,19 +209,67 @@ impl BdiffApp {
             Config {
                 files: file_configs,
                 changed: true,
+                ..Default::default()
             }
-        } else if config_path.exists() {
-            read_json_config(config_path).unwrap()
+        } else if let Some(config_path) = find_config_path() {
+            read_json_config(&config_path).unwrap()
         } else {
             Config::default()
         };
 
+        let byte_grouping = ret.settings.byte_grouping;
+        let mut num_watches = 0;
         for file in config.files.iter() {
             match ret.open_file(&file.path) {
                 Ok(hv) => {
                     if let Some(map) = file.map.as_ref() {
                         hv.mt.load_file(map);
                     }
+                    hv.set_virtual_base(file.virtual_base);
+                    if let Some(bytes_per_row) = file.bytes_per_row {
+                        match Settings::validate_row_layout(bytes_per_row, byte_grouping) {
+                            Ok(()) => {
+                                hv.bytes_per_row_override = Some(bytes_per_row);
+                                hv.set_bytes_per_row(bytes_per_row);
+                            }
+                            Err(e) => {
+                                log::error!(
+                                    "Ignoring bytes_per_row of {}: {}",
+                                    file.path.display(),
+                                    e
+                                );
+                            }
+                        }
+                    }
+                    hv.bookmarks = file.bookmarks.clone();
+                    // Watches with other widths can't be read, and there's a limit across all files
+                    let (watches, invalid): (Vec<Watch>, Vec<Watch>) =
+                        file.watches.iter().cloned().partition(Watch::is_valid);
+                    for watch in invalid {
+                        log::error!(
+                            "Ignoring watch at 0x{:X} with width {}",
+                            watch.offset,
+                            watch.width
+                        );
+                    }
+                    hv.watches = watches;
+                    if num_watches + hv.watches.len() > MAX_WATCHES {
+                        log::error!("Ignoring watches past the limit of {}", MAX_WATCHES);
+                        hv.watches.truncate(MAX_WATCHES - num_watches);
+                    }
+                    num_watches += hv.watches.len();
+                    hv.auto_reload = file.auto_reload.unwrap_or(true);
+                    if let Some(interval) = file.poll_interval_ms {
+                        if interval < MIN_POLL_INTERVAL_MS {
+                            log::error!(
+                                "Polling interval of {} ms is too short, using {} ms",
+                                interval,
+                                MIN_POLL_INTERVAL_MS
+                            );
+                        }
+                        let interval = interval.max(MIN_POLL_INTERVAL_MS);
+                        hv.file.watch(Some(Duration::from_millis(interval)));
+                    }
                 }
                 Err(e) => {
                     log::error!("Failed to open file: {}", e);
@@ -116,6 +277,20 @@ impl BdiffApp {
             }
         }
 
+        // Ranges edited by hand may end before they start
+        let (ranges, invalid): (Vec<_>, Vec<_>) = config
+            .diff_ignore_ranges
+            .iter()
+            .cloned()
+            .partition(|range| range.start() <= range.end());
+        for range in invalid {
+            log::error!(
+                "Ignoring diff ignore range 0x{:X} - 0x{:X} that ends before it starts",
+                range.start(),
+                range.end()
+            );
+        }
+        ret.diff_state.ignore_ranges = ranges;
         ret.config = config;
 
         ret.diff_state.recalculate(&ret.hex_views);
@@ -128,19 +303,42 @@ impl BdiffApp {
         self.config.files.push(path.into());
         self.config.changed = true;
 
-        let hv = HexView::new(file, self.next_hv_id);
+        self.settings.add_recent_file(path.into());
+        write_json_settings(&self.settings).expect("Failed to save settings!");
+
+        let mut hv = HexView::new(file, self.next_hv_id, self.settings.bytes_per_row);
+        if let Some(map) = find_associated_map(path, &self.settings.map_file_suffixes) {
+            log::info!("Loading map file {}", map.display());
+            hv.mt.load_file(&map);
+        }
         self.hex_views.push(hv);
         self.next_hv_id += 1;
 
         Ok(self.hex_views.last_mut().unwrap())
     }
 
+    /// Opens data that isn't backed by a file on disk. It isn't added to the workspace config.
+    pub fn open_bytes(&mut self, name: &str, data: Vec<u8>) -> &mut HexView {
+        let file = BinFile::from_bytes(name, data);
+
+        let mut hv = HexView::new(file, self.next_hv_id, self.settings.bytes_per_row);
+        hv.virtual_file = true;
+        // The name isn't a real path, so a comments file found under it belongs to another file
+        hv.comments.clear();
+        self.hex_views.push(hv);
+        self.next_hv_id += 1;
+
+        self.hex_views.last_mut().unwrap()
+    }
+
     fn get_hex_view_by_id(&mut self, id: usize) -> Option<&mut HexView> {
         self.hex_views.iter_mut().find(|hv| hv.id == id)
     }
 
     fn handle_hex_view_input(&mut self, ctx: &egui::Context) {
-        if ctx.input(|i| i.modifiers.shift) {
+        // Shift+Enter jumps to the previous diff instead of moving the selection
+        let move_selection = ctx.input(|i| i.modifiers.shift && !i.key_pressed(egui::Key::Enter));
+        if move_selection && !self.options.lock_selection {
             // Move selection
             if let Some(hv) = self.last_selected_hv {
                 if let Some(hv) = self.get_hex_view_by_id(hv) {
@@ -180,8 +378,22 @@ impl BdiffApp {
                 }
             }
         } else {
-            // Move view
-            for hv in self.hex_views.iter_mut() {
+            // Move view, along with the others in the group of the hovered or last selected view
+            let wrap = self.settings.wrap_scroll;
+            let scroll_group = self
+                .hex_views
+                .iter()
+                .find(|hv| hv.cursor_pos.is_some())
+                .or_else(|| {
+                    self.last_selected_hv
+                        .and_then(|id| self.hex_views.iter().find(|hv| hv.id == id))
+                })
+                .and_then(|hv| hv.scroll_group);
+            for hv in self
+                .hex_views
+                .iter_mut()
+                .filter(|hv| hv.scroll_group == scroll_group)
+            {
                 // Keys
                 if ctx.input(|i| i.key_pressed(egui::Key::Home)) {
                     hv.set_cur_pos(0);
@@ -192,45 +404,95 @@ impl BdiffApp {
                     hv.set_cur_pos(hv.file.data.len() - hv.bytes_per_screen())
                 }
                 if ctx.input(|i| i.key_pressed(egui::Key::PageUp)) {
-                    hv.adjust_cur_pos(-(hv.bytes_per_screen() as isize))
+                    hv.scroll_cur_pos(-(hv.bytes_per_screen() as isize), wrap)
                 }
                 if ctx.input(|i| i.key_pressed(egui::Key::PageDown)) {
-                    hv.adjust_cur_pos(hv.bytes_per_screen() as isize)
+                    hv.scroll_cur_pos(hv.bytes_per_screen() as isize, wrap)
+                }
+                // Ctrl+Left/Right jump between map symbols instead
+                let command = ctx.input(|i| i.modifiers.command);
+                if ctx.input(|i| i.key_pressed(egui::Key::ArrowLeft)) && !command {
+                    hv.scroll_cur_pos(-1, wrap)
                 }
-                if ctx.input(|i| i.key_pressed(egui::Key::ArrowLeft)) {
-                    hv.adjust_cur_pos(-1)
+                if ctx.input(|i| i.key_pressed(egui::Key::ArrowRight)) && !command {
+                    hv.scroll_cur_pos(1, wrap)
                 }
-                if ctx.input(|i| i.key_pressed(egui::Key::ArrowRight)) {
-                    hv.adjust_cur_pos(1)
+                if command && hv.mt.map_file.is_some() {
+                    let target = if ctx.input(|i| i.key_pressed(egui::Key::ArrowRight)) {
+                        // Past the current row, so symbols starting within it are skipped
+                        hv.mt.get_next_symbol(hv.cur_pos + hv.bytes_per_row - 1)
+                    } else if ctx.input(|i| i.key_pressed(egui::Key::ArrowLeft)) {
+                        hv.mt.get_prev_symbol(hv.cur_pos)
+                    } else {
+                        None
+                    };
+                    if let Some(target) = target {
+                        hv.set_cur_pos(target - target % hv.bytes_per_row);
+                    }
                 }
                 if ctx.input(|i| i.key_pressed(egui::Key::ArrowUp)) {
-                    hv.adjust_cur_pos(-(hv.bytes_per_row as isize))
+                    hv.scroll_cur_pos(-(hv.bytes_per_row as isize), wrap)
                 }
                 if ctx.input(|i| i.key_pressed(egui::Key::ArrowDown)) {
-                    hv.adjust_cur_pos(hv.bytes_per_row as isize)
+                    hv.scroll_cur_pos(hv.bytes_per_row as isize, wrap)
+                }
+                if self.options.skip_null_pages {
+                    if ctx.input(|i| {
+                        i.key_pressed(egui::Key::PageDown)
+                            || i.key_pressed(egui::Key::ArrowDown)
+                            || (i.key_pressed(egui::Key::ArrowRight) && !command)
+                    }) {
+                        hv.skip_null_pages(true);
+                    }
+                    if ctx.input(|i| {
+                        i.key_pressed(egui::Key::PageUp)
+                            || i.key_pressed(egui::Key::ArrowUp)
+                            || (i.key_pressed(egui::Key::ArrowLeft) && !command)
+                    }) {
+                        hv.skip_null_pages(false);
+                    }
                 }
-                if ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
+                if ctx.input(|i| i.key_pressed(egui::Key::Enter) && !i.modifiers.shift) {
                     let last_byte = hv.cur_pos + hv.bytes_per_screen();
 
                     if self.diff_state.enabled {
-                        if last_byte < hv.file.data.len() {
-                            match self.diff_state.get_next_diff(last_byte) {
-                                Some(next_diff) => {
-                                    // Move to the next diff
-                                    let new_pos = next_diff - (next_diff % hv.bytes_per_row);
-                                    hv.set_cur_pos(new_pos);
+                        match self.diff_state.for_view(hv).get_next_diff(last_byte, true) {
+                            Some(next_diff) => {
+                                if next_diff < last_byte {
+                                    self.toast =
+                                        Some(("Wrapped to first diff".to_owned(), Instant::now()));
                                 }
-                                None => {
-                                    // Move to the end of the file
-                                    if hv.file.data.len() >= hv.bytes_per_screen() {
-                                        hv.set_cur_pos(hv.file.data.len() - hv.bytes_per_screen());
-                                    }
+                                // Move to the next diff
+                                let new_pos = next_diff - (next_diff % hv.bytes_per_row);
+                                hv.set_cur_pos(new_pos);
+                            }
+                            None => {
+                                // Move to the end of the file
+                                if hv.file.data.len() >= hv.bytes_per_screen() {
+                                    hv.set_cur_pos(hv.file.data.len() - hv.bytes_per_screen());
                                 }
                             }
                         }
                     } else {
                         // Move one screen down
-                        hv.adjust_cur_pos(hv.bytes_per_screen() as isize)
+                        hv.scroll_cur_pos(hv.bytes_per_screen() as isize, wrap)
+                    }
+                }
+                if ctx.input(|i| i.key_pressed(egui::Key::Enter) && i.modifiers.shift) {
+                    if self.diff_state.enabled {
+                        let prev_diff =
+                            self.diff_state.for_view(hv).get_prev_diff(hv.cur_pos, true);
+                        if let Some(prev_diff) = prev_diff {
+                            if prev_diff >= hv.cur_pos {
+                                self.toast =
+                                    Some(("Wrapped to last diff".to_owned(), Instant::now()));
+                            }
+                            // Move to the previous diff
+                            hv.set_cur_pos(prev_diff - (prev_diff % hv.bytes_per_row));
+                        }
+                    } else {
+                        // Move one screen up
+                        hv.scroll_cur_pos(-(hv.bytes_per_screen() as isize), wrap)
                     }
                 }
 
@@ -254,7 +516,10 @@ impl BdiffApp {
                             self.scroll_overflow -= (scroll_amt * scroll_threshold) as f32;
                         }
                     }
-                    hv.adjust_cur_pos(-scroll_amt * lines_per_scroll * hv.bytes_per_row as isize)
+                    hv.scroll_cur_pos(
+                        -scroll_amt * lines_per_scroll * hv.bytes_per_row as isize,
+                        wrap,
+                    )
                 }
             }
         }
@@ -266,6 +531,8 @@ impl BdiffApp {
             .show(ctx, |ui| {
                 if ui.button("Restore defaults").clicked() {
                     self.settings = Settings::default();
+                    self.bytes_per_row_input = self.settings.bytes_per_row;
+                    self.map_file_suffixes_input = self.settings.map_file_suffixes.join(", ");
                     write_json_settings(&self.settings).expect("Failed to save settings!");
                 }
 
@@ -276,20 +543,274 @@ impl BdiffApp {
                         .selected_text(self.settings.byte_grouping.to_string())
                         .show_ui(ui, |ui| {
                             for value in ByteGrouping::get_all_options() {
+                                let valid = Settings::validate_row_layout(
+                                    self.settings.bytes_per_row,
+                                    value,
+                                );
+                                let option = egui::SelectableLabel::new(
+                                    self.settings.byte_grouping == value,
+                                    value.to_string(),
+                                );
+                                let res = ui.add_enabled(valid.is_ok(), option);
+                                if let Err(e) = valid {
+                                    res.on_disabled_hover_text(e);
+                                } else if res.clicked() {
+                                    self.settings.byte_grouping = value;
+
+                                    // A setting has been changed, save changes
+                                    write_json_settings(&self.settings)
+                                        .expect("Failed to save settings!");
+                                }
+                            }
+                        });
+                });
+
+                // Bytes per row
+                ui.horizontal(|ui| {
+                    ui.label("Bytes per row");
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut self.bytes_per_row_input)
+                                .clamp_range(1..=MAX_BYTES_PER_ROW),
+                        )
+                        .changed()
+                        && Settings::validate_row_layout(
+                            self.bytes_per_row_input,
+                            self.settings.byte_grouping,
+                        )
+                        .is_ok()
+                    {
+                        self.settings.bytes_per_row = self.bytes_per_row_input;
+                        for hv in self
+                            .hex_views
+                            .iter_mut()
+                            .filter(|hv| hv.bytes_per_row_override.is_none())
+                        {
+                            hv.set_bytes_per_row(self.settings.bytes_per_row);
+                        }
+                        write_json_settings(&self.settings).expect("Failed to save settings!");
+                    }
+                });
+                if let Err(e) = Settings::validate_row_layout(
+                    self.bytes_per_row_input,
+                    self.settings.byte_grouping,
+                ) {
+                    ui.label(egui::RichText::new(e).color(egui::Color32::RED));
+                }
+
+                // Row grouping
+                ui.horizontal(|ui| {
+                    let mut group_rows = self.settings.group_rows_every.is_some();
+                    if ui.checkbox(&mut group_rows, "Group rows every").changed() {
+                        self.settings.group_rows_every = match group_rows {
+                            true => Some(4),
+                            false => None,
+                        };
+                        write_json_settings(&self.settings).expect("Failed to save settings!");
+                    }
+
+                    if let Some(group_rows_every) = self.settings.group_rows_every.as_mut() {
+                        if ui
+                            .add(egui::DragValue::new(group_rows_every).clamp_range(1..=64))
+                            .changed()
+                        {
+                            write_json_settings(&self.settings).expect("Failed to save settings!");
+                        }
+                        ui.label("rows");
+                    }
+                });
+
+                if ui
+                    .checkbox(&mut self.settings.show_column_header, "Column header")
+                    .on_hover_text("Show the index of each byte column above the hex grid")
+                    .changed()
+                {
+                    write_json_settings(&self.settings).expect("Failed to save settings!");
+                }
+
+                if ui
+                    .checkbox(&mut self.settings.compact_mode, "Compact mode")
+                    .on_hover_text("Reduce the padding between hex cells")
+                    .changed()
+                {
+                    write_json_settings(&self.settings).expect("Failed to save settings!");
+                }
+
+                if ui
+                    .checkbox(
+                        &mut self.settings.copy_normalize_whitespace,
+                        "Normalize whitespace when copying text",
+                    )
+                    .on_hover_text(
+                        "Copy each run of non-printable bytes in the ASCII column as a line break",
+                    )
+                    .changed()
+                {
+                    write_json_settings(&self.settings).expect("Failed to save settings!");
+                }
+
+                if ui
+                    .checkbox(&mut self.settings.wrap_scroll, "Wrap-around scrolling")
+                    .on_hover_text("Scrolling past the end of the file continues from the start")
+                    .changed()
+                {
+                    write_json_settings(&self.settings).expect("Failed to save settings!");
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Font size");
+                    if ui
+                        .add(egui::Slider::new(&mut self.settings.font_size, FONT_SIZE_RANGE).suffix(" pt"))
+                        .on_hover_text("Size of the text in the hex grid")
+                        .changed()
+                    {
+                        write_json_settings(&self.settings).expect("Failed to save settings!");
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Scrollbar width");
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut self.settings.scrollbar_width)
+                                .clamp_range(4..=48)
+                                .suffix(" px"),
+                        )
+                        .changed()
+                    {
+                        write_json_settings(&self.settings).expect("Failed to save settings!");
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Entropy window");
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut self.settings.entropy_window)
+                                .clamp_range(ENTROPY_WINDOW_RANGE)
+                                .suffix(" bytes"),
+                        )
+                        .on_hover_text(
+                            "How many bytes around each byte its entropy is computed over",
+                        )
+                        .changed()
+                    {
+                        write_json_settings(&self.settings).expect("Failed to save settings!");
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Map file suffixes");
+                    let res = ui
+                        .add(
+                            egui::TextEdit::singleline(&mut self.map_file_suffixes_input)
+                                .desired_width(150.0)
+                                .hint_text("None"),
+                        )
+                        .on_hover_text(
+                            "When opening a file, load a map with the same name and one of these comma separated suffixes",
+                        );
+                    if res.lost_focus() {
+                        self.settings.map_file_suffixes = self
+                            .map_file_suffixes_input
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|suffix| !suffix.is_empty())
+                            .map(str::to_owned)
+                            .collect();
+                        write_json_settings(&self.settings).expect("Failed to save settings!");
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Edit key");
+                    let res = ui.add(
+                        egui::TextEdit::singleline(&mut self.settings.edit_key).desired_width(60.0),
+                    );
+                    if egui::Key::from_name(&self.settings.edit_key).is_none() {
+                        ui.label(egui::RichText::new("Unknown key").color(egui::Color32::RED));
+                    } else if res.lost_focus() {
+                        write_json_settings(&self.settings).expect("Failed to save settings!");
+                    }
+                })
+                .response
+                .on_hover_text("Starts editing the hovered byte, as can double-clicking it");
+
+                ui.horizontal(|ui| {
+                    ui.label("Show null bytes as");
+                    egui::ComboBox::from_id_source("null_display_dropdown")
+                        .selected_text(self.settings.null_display.to_string())
+                        .show_ui(ui, |ui| {
+                            for display in NullDisplay::ALL {
                                 if ui
                                     .selectable_value(
-                                        &mut self.settings.byte_grouping,
-                                        value,
-                                        value.to_string(),
+                                        &mut self.settings.null_display,
+                                        display,
+                                        display.to_string(),
+                                    )
+                                    .clicked()
+                                {
+                                    write_json_settings(&self.settings)
+                                        .expect("Failed to save settings!");
+                                }
+                            }
+                        });
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Copy hex selections as");
+                    egui::ComboBox::from_id_source("copy_format_dropdown")
+                        .selected_text(self.settings.copy_format.to_string())
+                        .show_ui(ui, |ui| {
+                            for format in CopyFormat::ALL {
+                                if ui
+                                    .selectable_value(
+                                        &mut self.settings.copy_format,
+                                        format,
+                                        format.to_string(),
                                     )
                                     .clicked()
                                 {
-                                    // A setting has been changed, save changes
                                     write_json_settings(&self.settings)
                                         .expect("Failed to save settings!");
                                 }
                             }
                         });
+                })
+                .response
+                .on_hover_text("The format Ctrl+C uses for bytes selected in the hex column");
+
+                if ui
+                    .checkbox(
+                        &mut self.settings.base64_wrap,
+                        "Wrap Base64 copies at 76 characters",
+                    )
+                    .changed()
+                {
+                    write_json_settings(&self.settings).expect("Failed to save settings!");
+                }
+
+                egui::Frame::group(&Style::default()).show(ui, |ui| {
+                    let theme_settings = &self.settings.theme_settings;
+                    let text = |text: &str, color: &Color| {
+                        egui::RichText::new(text)
+                            .monospace()
+                            .size(self.settings.font_size)
+                            .color(egui::Color32::from(color.clone()))
+                    };
+                    ui.horizontal(|ui| {
+                        ui.label(text("00000000", &theme_settings.offset_text_color));
+                        ui.label(text(
+                            self.settings.null_display.text(),
+                            &theme_settings.hex_null_color,
+                        ));
+                        ui.label(text(
+                            "01 02 03 04 05 06 07 08 09 0A 0B 0C 0D 0E 0F",
+                            &theme_settings.other_hex_color,
+                        ));
+                        ui.label(text("|", &theme_settings.offset_text_color));
+                        ui.label(text("ABCDEF.xyz", &theme_settings.ascii_color));
+                    });
                 });
 
                 egui::CollapsingHeader::new("Theme settings").show(ui, |ui| {
@@ -329,12 +850,56 @@ impl BdiffApp {
                             );
                             ui.end_row();
 
+                            ui.label("Hover highlight color");
+                            ui.color_edit_button_srgba_premultiplied(
+                                self.settings
+                                    .theme_settings
+                                    .hover_highlight_color
+                                    .as_bytes_mut(),
+                            );
+                            ui.end_row();
+
+                            ui.label("Edited byte color");
+                            ui.color_edit_button_srgba_premultiplied(
+                                self.settings.theme_settings.edit_color.as_bytes_mut(),
+                            );
+                            ui.end_row();
+
+                            ui.label("Search match color");
+                            ui.color_edit_button_srgba_premultiplied(
+                                self.settings
+                                    .theme_settings
+                                    .search_match_color
+                                    .as_bytes_mut(),
+                            );
+                            ui.end_row();
+
                             ui.label("Diff color");
                             ui.color_edit_button_srgba_premultiplied(
                                 self.settings.theme_settings.diff_color.as_bytes_mut(),
                             );
                             ui.end_row();
 
+                            ui.label("Diff palette");
+                            ui.horizontal(|ui| {
+                                for color in self.settings.theme_settings.diff_palette.iter_mut() {
+                                    ui.color_edit_button_srgba_premultiplied(color.as_bytes_mut());
+                                }
+                            });
+                            ui.end_row();
+
+                            ui.label("Inserted bytes color");
+                            ui.color_edit_button_srgba_premultiplied(
+                                self.settings.theme_settings.diff_insert_color.as_bytes_mut(),
+                            );
+                            ui.end_row();
+
+                            ui.label("Deleted bytes color");
+                            ui.color_edit_button_srgba_premultiplied(
+                                self.settings.theme_settings.diff_delete_color.as_bytes_mut(),
+                            );
+                            ui.end_row();
+
                             ui.label("Null color");
                             ui.color_edit_button_srgba_premultiplied(
                                 self.settings.theme_settings.hex_null_color.as_bytes_mut(),
@@ -346,6 +911,57 @@ impl BdiffApp {
                                 self.settings.theme_settings.other_hex_color.as_bytes_mut(),
                             );
                             ui.end_row();
+
+                            ui.label("Uncovered by map color");
+                            ui.color_edit_button_srgba_premultiplied(
+                                self.settings.theme_settings.uncovered_color.as_bytes_mut(),
+                            );
+                            ui.end_row();
+
+                            ui.label("Template mismatch color");
+                            ui.color_edit_button_srgba_premultiplied(
+                                self.settings
+                                    .theme_settings
+                                    .template_mismatch_color
+                                    .as_bytes_mut(),
+                            );
+                            ui.end_row();
+
+                            ui.label("Entropy gradient");
+                            ui.horizontal(|ui| {
+                                ui.color_edit_button_srgba_premultiplied(
+                                    self.settings
+                                        .theme_settings
+                                        .entropy_low_color
+                                        .as_bytes_mut(),
+                                );
+                                ui.label("to");
+                                ui.color_edit_button_srgba_premultiplied(
+                                    self.settings
+                                        .theme_settings
+                                        .entropy_high_color
+                                        .as_bytes_mut(),
+                                );
+                            });
+                            ui.end_row();
+
+                            ui.label("Data density gradient");
+                            ui.horizontal(|ui| {
+                                ui.color_edit_button_srgba_premultiplied(
+                                    self.settings
+                                        .theme_settings
+                                        .density_near_color
+                                        .as_bytes_mut(),
+                                );
+                                ui.label("to");
+                                ui.color_edit_button_srgba_premultiplied(
+                                    self.settings
+                                        .theme_settings
+                                        .density_far_color
+                                        .as_bytes_mut(),
+                                );
+                            });
+                            ui.end_row();
                         });
                     });
 
@@ -390,6 +1006,27 @@ impl BdiffApp {
     }
 }
 
+/// Why a view can't be saved, shown on its disabled entry in the Save menus
+fn save_disabled_reason(hv: &HexView) -> &'static str {
+    match hv.virtual_file {
+        true => "Not backed by a file on disk",
+        false => "No edits to save",
+    }
+}
+
+/// Keeps printable ASCII and turns each run of other bytes into a single line break
+fn normalize_whitespace(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len());
+    for &b in bytes {
+        match b {
+            32..=126 => text.push(b as char),
+            _ if !text.is_empty() && !text.ends_with('\n') => text.push('\n'),
+            _ => {}
+        }
+    }
+    text
+}
+
 fn set_up_custom_fonts(ctx: &egui::Context) {
     // Start with the default fonts (we will be adding to them rather than replacing them).
     let mut fonts = egui::FontDefinitions::default();
@@ -475,6 +1112,13 @@ impl eframe::App for BdiffApp {
             self.show_goto_modal(&goto_modal, ui, ctx);
         });
 
+        let search_modal: Modal = Modal::new(ctx, "search_modal");
+
+        // Search modal
+        search_modal.show(|ui| {
+            self.show_search_modal(&search_modal, ui, ctx);
+        });
+
         let overwrite_modal: Modal = Modal::new(ctx, "overwrite_modal");
 
         if self.overwrite_modal.open {
@@ -482,12 +1126,93 @@ impl eframe::App for BdiffApp {
             overwrite_modal.open();
         }
 
+        // Keys go to the byte being edited instead of moving around
+        let editing = self.hex_views.iter().any(|hv| hv.editing.is_some());
+
         // Standard HexView input
-        if !(overwrite_modal.is_open() || goto_modal.is_open()) {
+        if !(editing || overwrite_modal.is_open() || goto_modal.is_open() || search_modal.is_open())
+        {
             self.handle_hex_view_input(ctx);
         }
 
-        if ctx.input(|i| i.key_pressed(egui::Key::G)) {
+        // Keep the other views lined up with the base file in aligned diff mode. Two files
+        // share a row layout with placeholder rows, more are scrolled to matching offsets.
+        if self.diff_state.enabled {
+            let layouts = std::iter::once(&mut self.diff_state.aligned)
+                .chain(
+                    self.diff_state
+                        .groups
+                        .values_mut()
+                        .map(|state| &mut state.aligned),
+                )
+                .filter_map(|aligned| aligned.as_mut()?.layout.as_mut());
+            for layout in layouts {
+                layout.follow(&mut self.hex_views);
+            }
+        }
+        if let Some(aligned) = self.diff_state.aligned.as_ref().filter(|aligned| {
+            self.diff_state.enabled
+                && aligned
+                    .layout
+                    .as_ref()
+                    .is_none_or(|layout| layout.bytes_per_row.is_none())
+        }) {
+            let base_pos = aligned
+                .base_id
+                .and_then(|id| self.hex_views.iter().find(|hv| hv.id == id))
+                .map(|hv| hv.cur_pos);
+            if base_pos != self.aligned_base_pos {
+                self.aligned_base_pos = base_pos;
+                for hv in self.hex_views.iter_mut() {
+                    if let Some(pos) = base_pos.and_then(|pos| aligned.aligned_offset(hv.id, pos)) {
+                        hv.set_cur_pos(pos);
+                    }
+                }
+            }
+        }
+
+        // Undo and redo hex edits in the last selected view
+        if !ctx.wants_keyboard_input() {
+            let undo = ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::Z));
+            let redo = ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::Y));
+            if let Some(hv) = self
+                .last_selected_hv
+                .and_then(|id| self.hex_views.iter_mut().find(|hv| hv.id == id))
+            {
+                if undo {
+                    hv.undo_edit();
+                }
+                if redo {
+                    hv.redo_edit();
+                }
+            }
+        }
+
+        if ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::F)) {
+            search_modal.open();
+        }
+        if !ctx.wants_keyboard_input() && ctx.input(|i| i.key_pressed(egui::Key::F3)) {
+            self.find_next(!ctx.input(|i| i.modifiers.shift));
+        }
+
+        if !editing && !ctx.wants_keyboard_input() && ctx.input(|i| i.key_pressed(egui::Key::L)) {
+            self.options.lock_selection = !self.options.lock_selection;
+        }
+
+        // Ctrl+1-8 saves the selection to a memory slot, 1-8 restores it
+        if !editing && !ctx.wants_keyboard_input() {
+            for (slot, key) in MEMORY_SLOT_KEYS.iter().enumerate() {
+                if ctx.input(|i| i.key_pressed(*key)) {
+                    if ctx.input(|i| i.modifiers.command) {
+                        self.save_memory_slot(slot);
+                    } else {
+                        self.restore_memory_slot(slot);
+                    }
+                }
+            }
+        }
+
+        if !editing && !search_modal.is_open() && ctx.input(|i| i.key_pressed(egui::Key::G)) {
             if goto_modal.is_open() {
                 goto_modal.close();
             } else {
@@ -510,18 +1235,21 @@ impl eframe::App for BdiffApp {
 
             for hv in self.hex_views.iter() {
                 if self.last_selected_hv.is_some() && hv.id == self.last_selected_hv.unwrap() {
-                    let selected_bytes = hv.get_selected_bytes();
-
-                    let selected_bytes: String = match hv.selection.side {
-                        HexViewSelectionSide::Hex => selected_bytes
-                            .iter()
-                            .map(|b| format!("{:02X}", b))
-                            .collect::<Vec<String>>()
-                            .join(" "),
-                        HexViewSelectionSide::Ascii => {
-                            String::from_utf8_lossy(&selected_bytes).to_string()
-                        }
-                    };
+                    // Each row of a block selection is copied on its own line
+                    let selected_bytes = hv
+                        .get_selected_rows()
+                        .iter()
+                        .map(|row| match hv.selection.side {
+                            HexViewSelectionSide::Hex => self.settings.copy_format.format(row),
+                            HexViewSelectionSide::Ascii
+                                if self.settings.copy_normalize_whitespace =>
+                            {
+                                normalize_whitespace(row)
+                            }
+                            HexViewSelectionSide::Ascii => String::from_utf8_lossy(row).to_string(),
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\n");
                     // convert selected_bytes to an ascii string
 
                     selection.push_str(&selected_bytes.to_string());
@@ -545,22 +1273,257 @@ impl eframe::App for BdiffApp {
 
                         ui.close_menu();
                     }
-                    if ui.button("Save Workspace").clicked() {
-                        if self.config.changed {
-                            if self.started_with_arguments {
-                                self.overwrite_modal.open = true;
-                            } else {
-                                write_json_config("bdiff.json", &self.config)
-                                    .expect("Failed to write config");
-                                self.config.changed = false;
-                            };
-                        }
+                    ui.add_enabled_ui(!self.settings.recent_files.is_empty(), |ui| {
+                        ui.menu_button("Recent files", |ui| {
+                            let mut opened = None;
+                            for path in self.settings.recent_files.iter() {
+                                if ui
+                                    .add_enabled(
+                                        path.exists(),
+                                        egui::Button::new(path.display().to_string()),
+                                    )
+                                    .on_disabled_hover_text("File not found")
+                                    .clicked()
+                                {
+                                    opened = Some(path.clone());
+                                    ui.close_menu();
+                                }
+                            }
+                            ui.separator();
+                            if ui.button("Clear recent files").clicked() {
+                                self.settings.recent_files.clear();
+                                write_json_settings(&self.settings)
+                                    .expect("Failed to save settings!");
+                                ui.close_menu();
+                            }
+
+                            if let Some(path) = opened {
+                                if let Err(e) = self.open_file(&path) {
+                                    log::error!("Failed to open file: {}", e);
+                                }
+                                self.diff_state.recalculate(&self.hex_views);
+                            }
+                        });
+                    });
+                    if ui.button("Listen for hex dump (TCP)...").clicked() {
+                        self.hex_receiver.show = !self.hex_receiver.show;
                         ui.close_menu();
                     }
-                    if ui.button("Quit").clicked() {
+                    ui.menu_button("Save", |ui| {
+                        for hv in self.hex_views.iter() {
+                            if ui
+                                .add_enabled(
+                                    hv.unsaved && !hv.virtual_file,
+                                    egui::Button::new(hv.file_name()),
+                                )
+                                .on_disabled_hover_text(save_disabled_reason(hv))
+                                .clicked()
+                            {
+                                self.overwrite_modal.open = true;
+                                self.overwrite_modal.target = OverwriteTarget::File(hv.id);
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.menu_button("Review and save", |ui| {
+                        for hv in self.hex_views.iter() {
+                            if ui
+                                .add_enabled(
+                                    hv.unsaved && !hv.virtual_file,
+                                    egui::Button::new(hv.file_name()),
+                                )
+                                .on_disabled_hover_text(save_disabled_reason(hv))
+                                .clicked()
+                            {
+                                self.patch_review.open(hv);
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.menu_button("Apply IPS patch...", |ui| {
+                        let mut patched = false;
+                        for hv in self.hex_views.iter_mut() {
+                            if ui.button(hv.file_name()).clicked() {
+                                ui.close_menu();
+                                if let Some(path) = rfd::FileDialog::new()
+                                    .add_filter("IPS", &["ips"])
+                                    .set_title(format!("IPS patch to apply to {}", hv.file_name()))
+                                    .pick_file()
+                                {
+                                    let result = std::fs::read(&path)
+                                        .map_err(Error::from)
+                                        .and_then(|patch| hv.apply_ips_patch(&patch));
+                                    match result {
+                                        Ok(()) => patched = true,
+                                        Err(e) => log::error!("Failed to apply IPS patch: {}", e),
+                                    }
+                                }
+                            }
+                        }
+                        if self.hex_views.iter().any(|hv| hv.pre_patch.is_some()) {
+                            ui.separator();
+                            for hv in self.hex_views.iter_mut().filter(|hv| hv.pre_patch.is_some()) {
+                                if ui.button(format!("Revert {}", hv.file_name())).clicked() {
+                                    ui.close_menu();
+                                    hv.revert_ips_patch();
+                                    patched = true;
+                                }
+                            }
+                        }
+                        if patched {
+                            self.diff_state.recalculate(&self.hex_views);
+                        }
+                    });
+                    ui.menu_button("Export selection...", |ui| {
+                        for hv in self.hex_views.iter() {
+                            if ui
+                                .add_enabled(
+                                    hv.selection.state != HexViewSelectionState::None,
+                                    egui::Button::new(hv.file_name()),
+                                )
+                                .on_disabled_hover_text("Nothing is selected")
+                                .clicked()
+                            {
+                                ui.close_menu();
+                                hv.export_selection();
+                            }
+                        }
+                    });
+                    ui.menu_button("Export as CSV", |ui| {
+                        for hv in self.hex_views.iter() {
+                            if ui.button(hv.file_name()).clicked() {
+                                if let Some(path) = rfd::FileDialog::new()
+                                    .add_filter("CSV", &["csv"])
+                                    .save_file()
+                                {
+                                    if let Err(e) =
+                                        export::write_csv(&hv.file.data, hv.bytes_per_row, &path)
+                                    {
+                                        log::error!("Failed to export CSV: {}", e);
+                                    }
+                                }
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.add_enabled_ui(self.hex_views.len() > 1, |ui| {
+                        ui.menu_button("Export patch", |ui| {
+                            let Some((base, others)) = self.hex_views.split_first() else {
+                                return;
+                            };
+                            for hv in others {
+                                let label = format!("{} → {}", base.file_name(), hv.file_name());
+                                ui.menu_button(label, |ui| {
+                                    for (format, extension) in [("IPS", "ips"), ("BPS", "bps")] {
+                                        if ui.button(format!("{}...", format)).clicked() {
+                                            if let Some(path) = rfd::FileDialog::new()
+                                                .add_filter(format, &[extension])
+                                                .save_file()
+                                            {
+                                                let base_data = &base.file.data;
+                                                let patched_data = &hv.file.data;
+                                                let result = match extension {
+                                                    "ips" => diff_state::export_ips(
+                                                        &path,
+                                                        base_data,
+                                                        patched_data,
+                                                    ),
+                                                    _ => diff_state::export_bps(
+                                                        &path,
+                                                        base_data,
+                                                        patched_data,
+                                                    ),
+                                                };
+                                                if let Err(e) = result {
+                                                    log::error!("Failed to export patch: {}", e);
+                                                }
+                                            }
+                                            ui.close_menu();
+                                        }
+                                    }
+                                });
+                            }
+                        });
+                    });
+                    if ui.button("Save Workspace").clicked() {
+                        if self.config.changed {
+                            if self.started_with_arguments {
+                                self.overwrite_modal.open = true;
+                                self.overwrite_modal.target = OverwriteTarget::Config;
+                            } else {
+                                write_json_config(get_config_path(), &self.config)
+                                    .expect("Failed to write config");
+                                self.config.changed = false;
+                            };
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("Quit").clicked() {
                         ctx.send_viewport_cmd(ViewportCommand::Close)
                     }
                 });
+                ui.menu_button("Edit", |ui| {
+                    if ui
+                        .button("Set selection from clipboard addresses")
+                        .clicked()
+                    {
+                        if let Err(e) = self.select_from_clipboard() {
+                            log::error!("Failed to set selection from clipboard: {}", e);
+                        }
+                        ui.close_menu();
+                    }
+                    ui.checkbox(&mut self.options.lock_selection, "Lock selection (L)");
+                    ui.checkbox(&mut self.options.skip_null_pages, "Skip null pages")
+                        .on_hover_text(
+                            "Keep going past pages of only zero bytes when navigating with the keyboard",
+                        );
+                    ui.menu_button("Memory slots", |ui| {
+                        let selection = self
+                            .last_selected_hv
+                            .and_then(|id| self.hex_views.iter().find(|hv| hv.id == id))
+                            .map(|hv| &hv.selection)
+                            .filter(|selection| selection.state != HexViewSelectionState::None);
+                        let can_save =
+                            selection.is_some_and(|s| s.mode == HexViewSelectionMode::Linear);
+                        for slot in 0..NUM_MEMORY_SLOTS {
+                            let text = match &self.settings.memory_slots[slot] {
+                                Some(s) => format!("{}: 0x{:X} - 0x{:X}", slot + 1, s.start, s.end),
+                                None => format!("{}: empty", slot + 1),
+                            };
+
+                            ui.horizontal(|ui| {
+                                let res = ui.label(egui::RichText::new(text).monospace());
+                                if let Some(s) = &self.settings.memory_slots[slot] {
+                                    res.on_hover_text(
+                                        s.bytes
+                                            .iter()
+                                            .map(|b| format!("{:02X}", b))
+                                            .collect::<Vec<String>>()
+                                            .join(" "),
+                                    );
+                                }
+                                if ui
+                                    .add_enabled(
+                                        self.settings.memory_slots[slot].is_some(),
+                                        egui::Button::new("Restore"),
+                                    )
+                                    .clicked()
+                                {
+                                    self.restore_memory_slot(slot);
+                                }
+                                if ui
+                                    .add_enabled(can_save, egui::Button::new("Save"))
+                                    .on_disabled_hover_text(
+                                        "Select a range of bytes first. Block selections can't be saved.",
+                                    )
+                                    .clicked()
+                                {
+                                    self.save_memory_slot(slot);
+                                }
+                            });
+                        }
+                    });
+                });
                 ui.menu_button("Options", |ui| {
                     let diff_checkbox = Checkbox::new(&mut self.diff_state.enabled, "Display diff");
                     let mirror_selection_checkbox = Checkbox::new(
@@ -576,17 +1539,242 @@ impl eframe::App for BdiffApp {
                         self.diff_state.recalculate(&self.hex_views);
                     }
 
+                    if ui
+                        .add_enabled(
+                            self.diff_state.enabled,
+                            Checkbox::new(
+                                &mut self.diff_state.ignore_whitespace,
+                                "Ignore whitespace",
+                            ),
+                        )
+                        .on_hover_text(
+                            "Treat runs of 00, 09, 0A, 0D and 20 bytes as equal regardless of length",
+                        )
+                        .clicked()
+                    {
+                        self.diff_state.recalculate(&self.hex_views);
+                    }
+
+                    ui.add_enabled_ui(self.diff_state.enabled, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Reference file");
+                            let selected_text = self
+                                .hex_views
+                                .iter()
+                                .find(|hv| Some(hv.id) == self.diff_state.diff_ref_id)
+                                .or(self.hex_views.first())
+                                .map(HexView::file_name)
+                                .unwrap_or_default();
+                            let old_ref_id = self.diff_state.diff_ref_id;
+                            egui::ComboBox::from_id_source("diff_ref_id")
+                                .selected_text(selected_text)
+                                .show_ui(ui, |ui| {
+                                    for hv in self.hex_views.iter() {
+                                        ui.selectable_value(
+                                            &mut self.diff_state.diff_ref_id,
+                                            Some(hv.id),
+                                            hv.file_name(),
+                                        );
+                                    }
+                                });
+                            if self.diff_state.diff_ref_id != old_ref_id {
+                                self.diff_state.recalculate(&self.hex_views);
+                            }
+                        })
+                        .response
+                        .on_hover_text("The file every other file is compared against");
+
+                        ui.horizontal(|ui| {
+                            ui.label("Diff word size");
+                            let old_word_size = self.diff_state.word_size;
+                            for word_size in [1, 2, 4, 8] {
+                                ui.radio_value(
+                                    &mut self.diff_state.word_size,
+                                    word_size,
+                                    word_size.to_string(),
+                                );
+                            }
+                            if self.diff_state.word_size != old_word_size {
+                                self.diff_state.recalculate(&self.hex_views);
+                            }
+                        })
+                        .response
+                        .on_hover_text("Compare aligned groups of bytes, marking the whole group if any byte differs");
+
+                        ui.horizontal(|ui| {
+                            ui.label("Diff threshold");
+                            let res = ui.add(egui::Slider::new(
+                                &mut self.diff_state.diff_threshold,
+                                0..=u8::MAX,
+                            ));
+                            // Wait for the drag to finish so large files aren't diffed every frame
+                            if res.drag_released() || (res.changed() && !res.dragged()) {
+                                self.diff_state.recalculate(&self.hex_views);
+                            }
+                        })
+                        .response
+                        .on_hover_text("Bytes only differ when they're further apart than this, for fuzzy comparisons");
+
+                        if ui
+                            .checkbox(&mut self.diff_state.aligned_mode, "Aligned diff")
+                            .on_hover_text("Line up the files to show inserted and deleted bytes, instead of comparing bytes at the same offset")
+                            .changed()
+                        {
+                            self.aligned_base_pos = None;
+                            self.diff_state.recalculate(&self.hex_views);
+                        }
+                        if let Some(error) = self
+                            .diff_state
+                            .aligned
+                            .as_ref()
+                            .and_then(|aligned| aligned.error.as_ref())
+                        {
+                            ui.colored_label(ui.visuals().error_fg_color, error);
+                        }
+                    });
+
+                    if ui
+                        .add_enabled(
+                            self.diff_state.enabled,
+                            Checkbox::new(&mut self.diff_state.diff_bytes_only, "Show diff bytes only"),
+                        )
+                        .on_hover_text("Draw bytes that match across all files as dots")
+                        .changed()
+                    {
+                        self.diff_state.sync_groups();
+                    }
+
                     ui.add_enabled(self.hex_views.len() > 1, mirror_selection_checkbox);
+                    ui.checkbox(&mut self.diff_rollup_open, "Diff rollup");
+                    ui.checkbox(&mut self.diff_regions_open, "Diff regions");
+                    ui.checkbox(&mut self.diff_legend_open, "Diff legend");
+                    ui.checkbox(&mut self.ignore_mask.show, "Diff ignore ranges");
                     if ui.button("Settings").clicked() {
                         self.settings_open = !self.settings_open;
                     }
                 });
+                ui.menu_button("View", |ui| {
+                    ui.menu_button("Layout", |ui| {
+                        ui.radio_value(&mut self.view_layout, ViewLayout::Floating, "Floating");
+                        ui.radio_value(&mut self.view_layout, ViewLayout::Tabbed, "Tabbed");
+                    });
+                    let floating = self.view_layout == ViewLayout::Floating;
+                    if ui
+                        .add_enabled(floating, egui::Button::new("Tile Horizontally"))
+                        .clicked()
+                    {
+                        self.pending_tile_layout = Some(TileLayout::Horizontal);
+                        ui.close_menu();
+                    }
+                    if ui
+                        .add_enabled(floating, egui::Button::new("Tile Vertically"))
+                        .clicked()
+                    {
+                        self.pending_tile_layout = Some(TileLayout::Vertical);
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    let pair_text = match self.paired_views {
+                        true => "Split views",
+                        false => "Pair views",
+                    };
+                    if ui
+                        .add_enabled(
+                            floating && self.hex_views.len() == 2,
+                            egui::Button::new(pair_text),
+                        )
+                        .on_disabled_hover_text("Pairing needs exactly two open files")
+                        .clicked()
+                    {
+                        self.paired_views = !self.paired_views;
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    ui.menu_button("Save view as...", |ui| {
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.view_preset_name)
+                                    .hint_text("Name")
+                                    .desired_width(120.0),
+                            );
+                            let name = self.view_preset_name.trim();
+                            if ui
+                                .add_enabled(
+                                    !name.is_empty() && !self.hex_views.is_empty(),
+                                    egui::Button::new("Save"),
+                                )
+                                .clicked()
+                            {
+                                let presets = self.hex_views.iter().map(HexView::view_preset).collect();
+                                self.settings.view_presets.insert(name.to_owned(), presets);
+                                write_json_settings(&self.settings).expect("Failed to save settings!");
+                                self.view_preset_name.clear();
+                                ui.close_menu();
+                            }
+                        });
+                    });
+                    ui.add_enabled_ui(!self.settings.view_presets.is_empty(), |ui| {
+                        ui.menu_button("Restore view", |ui| {
+                            let mut deleted = None;
+                            for (name, presets) in self.settings.view_presets.iter() {
+                                ui.horizontal(|ui| {
+                                    if ui.button(name).clicked() {
+                                        for (hv, preset) in self.hex_views.iter_mut().zip(presets) {
+                                            if let Err(e) = hv.apply_view_preset(
+                                                preset,
+                                                self.settings.byte_grouping,
+                                            ) {
+                                                log::error!("Failed to restore view {}: {}", name, e);
+                                            }
+                                        }
+                                        ui.close_menu();
+                                    }
+                                    if ui.small_button("X").on_hover_text("Delete").clicked() {
+                                        deleted = Some(name.clone());
+                                    }
+                                });
+                            }
+                            if let Some(name) = deleted {
+                                self.settings.view_presets.remove(&name);
+                                write_json_settings(&self.settings).expect("Failed to save settings!");
+                            }
+                        });
+                    });
+                });
                 ui.menu_button("Action", |ui| {
                     if ui.button("Go to address (G)").clicked() {
                         self.goto_modal.value = "0x".to_owned();
                         goto_modal.open();
                         ui.close_menu();
                     }
+                    if ui.button("Find (Ctrl+F)").clicked() {
+                        search_modal.open();
+                        ui.close_menu();
+                    }
+                    if ui.button("Watch offset...").clicked() {
+                        self.watch_panel.show = true;
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("Patch", |ui| {
+                    if ui.button("Strip range...").clicked() {
+                        self.strip_dialog.show = !self.strip_dialog.show;
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("Tools", |ui| {
+                    if ui.button("Find alignment offset").clicked() {
+                        self.alignment_tool.show = !self.alignment_tool.show;
+                        ui.close_menu();
+                    }
+                    if ui.button("Detect padding").clicked() {
+                        self.padding_tool.show = !self.padding_tool.show;
+                        ui.close_menu();
+                    }
+                    if ui.button("Byte runs").clicked() {
+                        self.run_summary.show = !self.run_summary.show;
+                        ui.close_menu();
+                    }
                 });
             })
         });
@@ -595,22 +1783,92 @@ impl eframe::App for BdiffApp {
         let mut calc_diff = false;
 
         // Main panel
-        egui::CentralPanel::default().show(ctx, |_ui| {
-            // TODO unused CentralPanel
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if let Some(layout) = self.pending_tile_layout.take() {
+                self.tile_hex_views(layout, ui.max_rect());
+            }
+
+            let shows_xor = self.hex_views.iter().any(HexView::shows_xor);
+            let views: Vec<OtherView> = self
+                .hex_views
+                .iter()
+                .map(|hv| OtherView {
+                    id: hv.id,
+                    name: hv.file_name(),
+                    selected_bytes: match shows_xor {
+                        true => hv.get_selected_prefix(XOR_PREVIEW_LEN),
+                        false => vec![],
+                    },
+                    selection_len: hv.selection_len(),
+                    data_len: hv.file.data.len(),
+                })
+                .collect();
+            let mut actions: Vec<HexViewAction> = Vec::new();
+
+            if self.hex_views.len() != 2 || self.view_layout == ViewLayout::Tabbed {
+                self.paired_views = false;
+            }
+            let pair_rects = match self.paired_views {
+                true => Some(self.show_pair_header(ui)),
+                false => None,
+            };
+            let tab_rect = match self.view_layout {
+                ViewLayout::Tabbed => Some(self.show_tab_bar(ui)),
+                ViewLayout::Floating => None,
+            };
+
+            // Highlight the offset hovered in one view in all the others
+            let hovered = self
+                .hex_views
+                .iter()
+                .find_map(|hv| hv.cursor_pos.map(|pos| (hv.id, pos)));
             for hv in self.hex_views.iter_mut() {
+                hv.hover_highlight = match hovered {
+                    Some((id, pos)) if self.options.mirror_selection && id != hv.id => Some(pos),
+                    _ => None,
+                };
+            }
+
+            for (i, hv) in self.hex_views.iter_mut().enumerate() {
                 let cur_sel = hv.selection.clone();
-                let can_selection_change = match self.selecting_hv {
-                    Some(id) => id == hv.id,
-                    None => true,
+                let other_views: Vec<OtherView> =
+                    views.iter().filter(|v| v.id != hv.id).cloned().collect();
+                let diff_state = self.diff_state.for_view(hv);
+                let can_selection_change = !self.options.lock_selection
+                    && match self.selecting_hv {
+                        Some(id) => id == hv.id,
+                        None => true,
+                    };
+                let docked_rect = match (pair_rects, tab_rect) {
+                    (Some(rects), _) => Some(rects[i]),
+                    (None, Some(rect)) => Some(rect),
+                    (None, None) => None,
                 };
-                hv.show(
-                    &mut self.config,
-                    &self.settings,
-                    &self.diff_state,
-                    ctx,
-                    cursor_state,
-                    can_selection_change,
-                );
+                match docked_rect {
+                    // Only the active tab is shown in the tabbed layout
+                    Some(_) if tab_rect.is_some() && self.active_tab != Some(hv.id) => {}
+                    Some(rect) => hv.show_docked(
+                        ui,
+                        rect,
+                        &mut self.config,
+                        &self.settings,
+                        diff_state,
+                        ctx,
+                        cursor_state,
+                        can_selection_change,
+                        &other_views,
+                    ),
+                    None => hv.show(
+                        &mut self.config,
+                        &self.settings,
+                        diff_state,
+                        ctx,
+                        cursor_state,
+                        can_selection_change,
+                        &other_views,
+                    ),
+                }
+                actions.append(&mut hv.actions);
                 if hv.selection != cur_sel {
                     match hv.selection.state {
                         HexViewSelectionState::Selecting => {
@@ -633,6 +1891,10 @@ impl eframe::App for BdiffApp {
                 }
             }
 
+            for action in actions {
+                self.apply_hex_view_action(action);
+            }
+
             if cursor_state == CursorState::Released {
                 self.selecting_hv = None;
                 if self.global_selection.state == HexViewSelectionState::Selecting {
@@ -675,7 +1937,8 @@ impl eframe::App for BdiffApp {
 
         // File reloading
         for hv in self.hex_views.iter_mut() {
-            if hv.file.modified.swap(false, Ordering::Relaxed) {
+            // Changes stay pending while auto-reload is off
+            if hv.auto_reload && hv.file.modified.swap(false, Ordering::Relaxed) {
                 match hv.reload_file() {
                     Ok(_) => {
                         log::info!("Reloaded file {}", hv.file.path.display());
@@ -687,8 +1950,7 @@ impl eframe::App for BdiffApp {
                 }
             }
 
-            if hv.mt.map_file.is_some() {
-                let map_file = hv.mt.map_file.as_mut().unwrap();
+            if let Some(map_file) = hv.mt.map_file.as_mut() {
                 if map_file.modified.swap(false, Ordering::Relaxed) {
                     match map_file.reload() {
                         Ok(_) => {
@@ -709,23 +1971,112 @@ impl eframe::App for BdiffApp {
         if self.settings_open {
             self.show_settings(ctx);
         }
+
+        if self.diff_rollup_open {
+            self.show_diff_rollup(ctx);
+        }
+
+        if self.diff_regions_open {
+            self.show_diff_regions(ctx);
+        }
+
+        if self.diff_legend_open {
+            self.show_diff_legend(ctx);
+        }
+
+        self.show_toast(ctx);
+
+        if self.alignment_tool.show {
+            self.alignment_tool.display(ctx, &mut self.hex_views);
+        }
+
+        if self.padding_tool.show {
+            self.padding_tool.display(ctx, &mut self.hex_views);
+        }
+
+        if self.run_summary.show {
+            self.run_summary.display(ctx, &mut self.hex_views);
+        }
+
+        if self.watch_panel.show {
+            self.watch_panel
+                .display(ctx, &mut self.hex_views, &mut self.config);
+        }
+
+        if self.ignore_mask.show {
+            let selection = self
+                .last_selected_hv
+                .and_then(|id| self.hex_views.iter().find(|hv| hv.id == id))
+                .filter(|hv| hv.selection.state != HexViewSelectionState::None)
+                .map(|hv| hv.selection.ranges(hv.bytes_per_row))
+                .unwrap_or_default();
+            if self
+                .ignore_mask
+                .display(ctx, &mut self.diff_state.ignore_ranges, selection)
+            {
+                self.config.diff_ignore_ranges = self.diff_state.ignore_ranges.clone();
+                self.config.changed = true;
+                self.diff_state.recalculate(&self.hex_views);
+            }
+        }
+
+        if self.strip_dialog.show && self.strip_dialog.display(ctx, &mut self.hex_views) {
+            self.diff_state.recalculate(&self.hex_views);
+        }
+
+        if self.patch_review.show && self.patch_review.display(ctx, &mut self.hex_views) {
+            self.diff_state.recalculate(&self.hex_views);
+        }
+
+        let received = self.hex_receiver.display(ctx);
+        if !received.is_empty() {
+            for dump in received {
+                self.open_bytes(&dump.file_name(), dump.data);
+            }
+            self.diff_state.recalculate(&self.hex_views);
+        }
     }
 }
 
 impl BdiffApp {
     fn overwrite_modal(&mut self, modal: &Modal) {
         modal.show(|ui| {
-            modal.title(ui, "Overwrite previous config");
-            ui.label(&format!(
-                "By saving, you are going to overwrite existing configuration file at \"{}\".",
-                "./bdiff.json"
-            ));
+            match self.overwrite_modal.target {
+                OverwriteTarget::Config => {
+                    modal.title(ui, "Overwrite previous config");
+                    ui.label(format!(
+                        "By saving, you are going to overwrite existing configuration file at \"{}\".",
+                        get_config_path().display()
+                    ));
+                }
+                OverwriteTarget::File(id) => {
+                    modal.title(ui, "Overwrite file");
+                    if let Some(hv) = self.hex_views.iter().find(|hv| hv.id == id) {
+                        ui.label(format!(
+                            "By saving, you are going to overwrite the file at \"{}\".",
+                            hv.file.path.display()
+                        ));
+                    }
+                }
+            }
             ui.label("Are you sure you want to proceed?");
 
             modal.buttons(ui, |ui| {
                 if ui.button("Overwrite").clicked() {
-                    write_json_config("bdiff.json", &self.config).unwrap();
-                    self.config.changed = false;
+                    match self.overwrite_modal.target {
+                        OverwriteTarget::Config => {
+                            write_json_config(get_config_path(), &self.config).unwrap();
+                            self.config.changed = false;
+                        }
+                        OverwriteTarget::File(id) => {
+                            if let Some(hv) = self.get_hex_view_by_id(id) {
+                                if let Err(e) = hv.save() {
+                                    log::error!("Failed to save file: {}", e);
+                                }
+                            }
+                        }
+                    }
+                    modal.close();
                     self.overwrite_modal.open = false;
                 }
                 if ui.button("Cancel").clicked() {
@@ -736,29 +2087,682 @@ impl BdiffApp {
         });
     }
 
+    fn apply_hex_view_action(&mut self, action: HexViewAction) {
+        match action {
+            HexViewAction::FollowOffset { target, offset } => {
+                if let Some(hv) = self.get_hex_view_by_id(target) {
+                    hv.set_cur_pos(offset);
+                }
+            }
+            HexViewAction::DataChanged | HexViewAction::DiffGroupChanged => {
+                self.diff_state.recalculate(&self.hex_views);
+            }
+            HexViewAction::SelectRange { target, start, end } => {
+                if let Some(hv) = self.get_hex_view_by_id(target) {
+                    hv.selection.begin(start, HexViewSelectionSide::Hex);
+                    hv.selection.finalize(end);
+                    hv.set_cur_pos(start - start % hv.bytes_per_row);
+                }
+            }
+            HexViewAction::GoToOffset(offset) => {
+                for hv in self.hex_views.iter_mut() {
+                    let offset = offset.min(hv.file.data.len().saturating_sub(1));
+                    hv.set_cur_pos(offset - offset % hv.bytes_per_row);
+                }
+            }
+        }
+    }
+
+    fn select_from_clipboard(&mut self) -> Result<(), Error> {
+        let text = arboard::Clipboard::new()?.get_text()?;
+        let range: HexViewSelectionRange = text.parse()?;
+
+        self.global_selection = HexViewSelection {
+            range,
+            state: HexViewSelectionState::Selected,
+            side: HexViewSelectionSide::Hex,
+            mode: HexViewSelectionMode::Linear,
+            bits: None,
+        };
+
+        for hv in self.hex_views.iter_mut() {
+            // Addresses on the clipboard are in the view's virtual address space
+            let (Some(first), Some(second)) = (
+                hv.file_offset(self.global_selection.range.first),
+                hv.file_offset(self.global_selection.range.second),
+            ) else {
+                continue;
+            };
+
+            if first.max(second) < hv.file.data.len() {
+                hv.selection = HexViewSelection {
+                    range: HexViewSelectionRange { first, second },
+                    ..self.global_selection.clone()
+                };
+                hv.set_cur_pos(hv.selection.start() - hv.selection.start() % hv.bytes_per_row);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn save_memory_slot(&mut self, slot: usize) {
+        let Some(hv) = self
+            .last_selected_hv
+            .and_then(|id| self.hex_views.iter().find(|hv| hv.id == id))
+        else {
+            return;
+        };
+
+        // Slots hold a start and an end, which can't describe a block
+        if hv.selection.state == HexViewSelectionState::None
+            || hv.selection.mode == HexViewSelectionMode::Block
+        {
+            return;
+        }
+
+        self.settings.memory_slots[slot] = Some(MemorySlot {
+            start: hv.virtual_addr(hv.selection.start()),
+            end: hv.virtual_addr(hv.selection.end()),
+            bytes: hv.get_selected_prefix(MEMORY_SLOT_PREVIEW_LEN),
+        });
+        write_json_settings(&self.settings).expect("Failed to save settings!");
+    }
+
+    fn restore_memory_slot(&mut self, slot: usize) {
+        let Some(memory_slot) = self.settings.memory_slots[slot].as_ref() else {
+            return;
+        };
+
+        self.global_selection = HexViewSelection {
+            range: HexViewSelectionRange {
+                first: memory_slot.start,
+                second: memory_slot.end,
+            },
+            state: HexViewSelectionState::Selected,
+            side: HexViewSelectionSide::Hex,
+            mode: HexViewSelectionMode::Linear,
+            bits: None,
+        };
+
+        for hv in self.hex_views.iter_mut() {
+            // Slots hold addresses in the view's virtual address space
+            let (Some(first), Some(second)) = (
+                hv.file_offset(memory_slot.start),
+                hv.file_offset(memory_slot.end),
+            ) else {
+                continue;
+            };
+
+            if second < hv.file.data.len() {
+                hv.selection = HexViewSelection {
+                    range: HexViewSelectionRange { first, second },
+                    ..self.global_selection.clone()
+                };
+                hv.set_cur_pos(first - first % hv.bytes_per_row);
+            }
+        }
+    }
+
+    /// Shows the tab bar of the tabbed layout and returns the rect of the active view below it
+    fn show_tab_bar(&mut self, ui: &mut egui::Ui) -> egui::Rect {
+        if !self
+            .hex_views
+            .iter()
+            .any(|hv| Some(hv.id) == self.active_tab)
+        {
+            self.active_tab = self.hex_views.first().map(|hv| hv.id);
+        }
+
+        ui.horizontal(|ui| {
+            for hv in self.hex_views.iter() {
+                let label = match hv.unsaved {
+                    true => format!("{}*", hv.file_name()),
+                    false => hv.file_name(),
+                };
+                ui.selectable_value(&mut self.active_tab, Some(hv.id), label)
+                    .on_hover_text(hv.file.path.display().to_string());
+            }
+        });
+
+        ui.available_rect_before_wrap()
+    }
+
+    /// Shows the shared header of the paired layout and returns the rects of the two halves below it
+    fn show_pair_header(&mut self, ui: &mut egui::Ui) -> [egui::Rect; 2] {
+        ui.horizontal(|ui| {
+            ui.label(
+                egui::RichText::new(format!(
+                    "{}  |  {}",
+                    self.hex_views[0].file_name(),
+                    self.hex_views[1].file_name()
+                ))
+                .monospace(),
+            );
+            if ui
+                .button("Split")
+                .on_hover_text("Restore separate windows")
+                .clicked()
+            {
+                self.paired_views = false;
+            }
+        });
+
+        let area = ui.available_rect_before_wrap();
+        let half = area.width() / 2.0;
+        [
+            egui::Rect::from_min_size(area.min, egui::vec2(half, area.height())),
+            egui::Rect::from_min_size(
+                area.min + egui::vec2(half, 0.0),
+                egui::vec2(half, area.height()),
+            ),
+        ]
+    }
+
+    fn tile_hex_views(&mut self, layout: TileLayout, area: egui::Rect) {
+        if self.hex_views.is_empty() {
+            return;
+        }
+
+        let count = self.hex_views.len() as f32;
+        let size = match layout {
+            TileLayout::Horizontal => egui::vec2(area.width() / count, area.height()),
+            TileLayout::Vertical => egui::vec2(area.width(), area.height() / count),
+        };
+
+        for (i, hv) in self.hex_views.iter_mut().enumerate() {
+            let min = match layout {
+                TileLayout::Horizontal => area.min + egui::vec2(size.x * i as f32, 0.0),
+                TileLayout::Vertical => area.min + egui::vec2(0.0, size.y * i as f32),
+            };
+            hv.tile_rect = Some(egui::Rect::from_min_size(min, size));
+        }
+    }
+
+    fn show_toast(&mut self, ctx: &egui::Context) {
+        const TOAST_DURATION: Duration = Duration::from_millis(1500);
+
+        let Some((message, shown_at)) = &self.toast else {
+            return;
+        };
+        let elapsed = shown_at.elapsed();
+        if elapsed >= TOAST_DURATION {
+            self.toast = None;
+            return;
+        }
+
+        egui::Area::new(egui::Id::new("toast"))
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 40.0))
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(message);
+                });
+            });
+        ctx.request_repaint_after(TOAST_DURATION - elapsed);
+    }
+
+    /// The diffs of the last selected view's diff group
+    fn focused_diff_state(&self) -> &DiffState {
+        match self
+            .last_selected_hv
+            .and_then(|id| self.hex_views.iter().find(|hv| hv.id == id))
+        {
+            Some(hv) => self.diff_state.for_view(hv),
+            None => &self.diff_state,
+        }
+    }
+
+    fn show_diff_rollup(&mut self, ctx: &egui::Context) {
+        let mut open = self.diff_rollup_open;
+        let mut goto_pos: Option<usize> = None;
+
+        egui::Window::new("Diff rollup")
+            .open(&mut open)
+            .default_height(300.0)
+            .show(ctx, |ui| {
+                let diff_state = self.focused_diff_state();
+                ui.label(format!(
+                    "Differing bytes per 0x{:X}-byte block",
+                    ROLLUP_BLOCK_SIZE
+                ));
+
+                let row_height = ui.text_style_height(&egui::TextStyle::Body);
+                egui::ScrollArea::vertical().show_rows(
+                    ui,
+                    row_height,
+                    diff_state.rollup.len(),
+                    |ui, row_range| {
+                        egui::Grid::new("diff_rollup_grid")
+                            .striped(true)
+                            .num_columns(4)
+                            .show(ui, |ui| {
+                                ui.label("Block");
+                                ui.label("Offset");
+                                ui.label("Diffs");
+                                ui.label("%");
+                                ui.end_row();
+
+                                for block in &diff_state.rollup[row_range] {
+                                    ui.label(
+                                        egui::RichText::new(block.index.to_string()).monospace(),
+                                    );
+                                    if ui
+                                        .selectable_label(
+                                            false,
+                                            egui::RichText::new(format!("0x{:X}", block.start))
+                                                .monospace(),
+                                        )
+                                        .on_hover_text("Go to block")
+                                        .clicked()
+                                    {
+                                        goto_pos = Some(block.start);
+                                    }
+                                    ui.label(
+                                        egui::RichText::new(block.diff_count.to_string())
+                                            .monospace(),
+                                    );
+                                    ui.label(
+                                        egui::RichText::new(format!("{:.1}", block.percentage()))
+                                            .monospace(),
+                                    );
+                                    ui.end_row();
+                                }
+                            });
+                    },
+                );
+            });
+
+        if let Some(pos) = goto_pos {
+            for hv in self.hex_views.iter_mut() {
+                hv.set_cur_pos(pos);
+            }
+        }
+
+        self.diff_rollup_open = open;
+    }
+
+    fn show_diff_legend(&mut self, ctx: &egui::Context) {
+        let mut open = self.diff_legend_open;
+
+        egui::Window::new("Diff legend")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let diff_state = self.focused_diff_state();
+                let name = |id: usize| {
+                    self.hex_views
+                        .iter()
+                        .find(|hv| hv.id == id)
+                        .map(|hv| hv.file_name())
+                        .unwrap_or_default()
+                };
+                let base_name = diff_state.base_id.map(name).unwrap_or_default();
+                let num_pairs = diff_state.pair_diffs.len();
+
+                if !diff_state.enabled || num_pairs == 0 {
+                    ui.label("Diff is disabled");
+                    return;
+                }
+
+                egui::Grid::new("diff_legend_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        for (pair, (id, _)) in diff_state.pair_diffs.iter().enumerate() {
+                            let color = match num_pairs {
+                                1 => self.settings.theme_settings.diff_color.clone().into(),
+                                _ => self.settings.theme_settings.diff_pair_color(pair),
+                            };
+                            let (rect, _) = ui
+                                .allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+                            ui.painter().rect_filled(rect, 2.0, color);
+                            ui.label(format!("{} / {}", base_name, name(*id)));
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        self.diff_legend_open = open;
+    }
+
+    fn show_diff_regions(&mut self, ctx: &egui::Context) {
+        let mut open = self.diff_regions_open;
+        let mut goto_pos: Option<usize> = None;
+
+        egui::Window::new("Diff regions")
+            .open(&mut open)
+            .default_height(300.0)
+            .show(ctx, |ui| {
+                let diff_state = self.focused_diff_state();
+                let regions = &diff_state.regions;
+                let diff_bytes: usize = regions.iter().map(|region| region.len()).sum();
+                let percentage = match diff_state.diffs.len() {
+                    0 => 0.0,
+                    len => diff_bytes as f32 * 100.0 / len as f32,
+                };
+                ui.label(format!(
+                    "{} regions, {} bytes differ ({:.2}%)",
+                    regions.len(),
+                    diff_bytes,
+                    percentage
+                ));
+                ui.separator();
+
+                // Symbols come from the first view with a map loaded
+                let map_file = self.hex_views.iter().find_map(|hv| hv.mt.map_file.as_ref());
+
+                let row_height = ui.text_style_height(&egui::TextStyle::Body);
+                egui::ScrollArea::vertical().show_rows(
+                    ui,
+                    row_height,
+                    diff_state.regions.len(),
+                    |ui, row_range| {
+                        egui::Grid::new("diff_regions_grid")
+                            .striped(true)
+                            .num_columns(4)
+                            .show(ui, |ui| {
+                                for region in &diff_state.regions[row_range] {
+                                    if ui
+                                        .selectable_label(
+                                            false,
+                                            egui::RichText::new(format!(
+                                                "0x{:X} - 0x{:X}",
+                                                region.start, region.end
+                                            ))
+                                            .monospace(),
+                                        )
+                                        .on_hover_text("Go to region")
+                                        .clicked()
+                                    {
+                                        goto_pos = Some(region.start);
+                                    }
+                                    ui.label(
+                                        egui::RichText::new(format!("len 0x{:X}", region.len()))
+                                            .monospace(),
+                                    );
+                                    ui.label(match region.kind {
+                                        Some(kind) => kind.to_string(),
+                                        None => String::new(),
+                                    });
+                                    if let Some(map_file) = map_file {
+                                        ui.label(
+                                            map_file
+                                                .get_entry(region.start, region.start + 1)
+                                                .map(|entry| entry.symbol_name.as_str())
+                                                .unwrap_or_default(),
+                                        );
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    },
+                );
+            });
+
+        if let Some(pos) = goto_pos {
+            for hv in self.hex_views.iter_mut() {
+                hv.set_cur_pos(pos);
+            }
+        }
+
+        self.diff_regions_open = open;
+    }
+
+    fn search(&mut self) -> Result<(), Error> {
+        let hv = self
+            .last_selected_hv
+            .and_then(|id| self.hex_views.iter().find(|hv| hv.id == id))
+            .or(self.hex_views.first())
+            .ok_or_else(|| Error::msg("No file is open"))?;
+
+        let (offsets, len) = match self.search_modal.mode {
+            SearchMode::Bytes => {
+                let needle = search::parse_masked_pattern(&self.search_modal.value)?;
+                (search::find_pattern(&hv.file.data, &needle), needle.len())
+            }
+            SearchMode::Text => {
+                if self.search_modal.value.is_empty() {
+                    return Err(Error::msg("Nothing to search for"));
+                }
+                let needle: Vec<Option<u8>> = self.search_modal.value.bytes().map(Some).collect();
+                (search::find_pattern(&hv.file.data, &needle), needle.len())
+            }
+            SearchMode::Value => {
+                let needle = search::encode_value(
+                    &self.search_modal.value,
+                    self.search_modal.width,
+                    hv.file.endianness,
+                )?;
+                (search::find_value(&hv.file.data, &needle), needle.len())
+            }
+        };
+        self.search_modal.status = format!("{} matches in {}", offsets.len(), hv.file_name());
+        self.search_modal.target = Some(hv.id);
+        self.search_modal.current = None;
+
+        let mut matches = Some(SearchMatches { offsets, len });
+        let target = hv.id;
+        for hv in self.hex_views.iter_mut() {
+            hv.search_matches = match hv.id == target {
+                true => matches.take().unwrap_or_default(),
+                false => SearchMatches::default(),
+            };
+        }
+        Ok(())
+    }
+
+    /// Moves to the next or previous match, wrapping around the ends of the file
+    fn find_next(&mut self, forward: bool) {
+        let Some(hv) = self
+            .search_modal
+            .target
+            .and_then(|id| self.hex_views.iter().find(|hv| hv.id == id))
+        else {
+            return;
+        };
+
+        let count = hv.search_matches.offsets.len();
+        if count == 0 {
+            self.search_modal.status = "No matches".to_owned();
+            return;
+        }
+
+        let (index, wrapped) = match (self.search_modal.current, forward) {
+            (None, true) => (0, false),
+            (None, false) => (count - 1, false),
+            (Some(i), true) if i + 1 < count => (i + 1, false),
+            (Some(_), true) => (0, true),
+            (Some(i), false) if i > 0 => (i - 1, false),
+            (Some(_), false) => (count - 1, true),
+        };
+
+        self.search_modal.status = match wrapped {
+            true => format!("Wrapped: match {} of {}", index + 1, count),
+            false => format!("Match {} of {}", index + 1, count),
+        };
+        self.go_to_match(index);
+    }
+
+    /// Selects the match and scrolls its view to it
+    fn go_to_match(&mut self, index: usize) {
+        let Some(hv) = self
+            .search_modal
+            .target
+            .and_then(|id| self.hex_views.iter_mut().find(|hv| hv.id == id))
+        else {
+            return;
+        };
+        let Some(&pos) = hv.search_matches.offsets.get(index) else {
+            return;
+        };
+
+        hv.selection = HexViewSelection {
+            range: HexViewSelectionRange {
+                first: pos,
+                second: pos + hv.search_matches.len - 1,
+            },
+            state: HexViewSelectionState::Selected,
+            side: HexViewSelectionSide::Hex,
+            mode: HexViewSelectionMode::Linear,
+            bits: None,
+        };
+        hv.set_cur_pos(pos - pos % hv.bytes_per_row);
+        self.global_selection = hv.selection.clone();
+        self.last_selected_hv = Some(hv.id);
+        self.search_modal.current = Some(index);
+    }
+
+    fn show_search_modal(&mut self, search_modal: &Modal, ui: &mut egui::Ui, ctx: &egui::Context) {
+        search_modal.title(ui, "Find");
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("search_mode")
+                .selected_text(self.search_modal.mode.to_string())
+                .show_ui(ui, |ui| {
+                    for mode in SearchMode::ALL {
+                        ui.selectable_value(&mut self.search_modal.mode, mode, mode.to_string());
+                    }
+                });
+
+            if self.search_modal.mode == SearchMode::Value {
+                egui::ComboBox::from_id_source("search_value_width")
+                    .selected_text(self.search_modal.width.to_string())
+                    .show_ui(ui, |ui| {
+                        for width in ValueWidth::ALL {
+                            ui.selectable_value(
+                                &mut self.search_modal.width,
+                                width,
+                                width.to_string(),
+                            );
+                        }
+                    });
+            }
+        });
+
+        let hint = match self.search_modal.mode {
+            SearchMode::Bytes => "DE ?? BE EF",
+            SearchMode::Text => "Text",
+            SearchMode::Value => "0x1234",
+        };
+        ui.add(egui::TextEdit::singleline(&mut self.search_modal.value).hint_text(hint))
+            .request_focus();
+
+        ui.label(&self.search_modal.status);
+
+        let mut goto_index: Option<usize> = None;
+        if let Some(hv) = self
+            .search_modal
+            .target
+            .and_then(|id| self.hex_views.iter().find(|hv| hv.id == id))
+        {
+            let offsets = &hv.search_matches.offsets;
+            if !offsets.is_empty() {
+                let row_height = ui.text_style_height(&egui::TextStyle::Body);
+                egui::ScrollArea::vertical().max_height(200.0).show_rows(
+                    ui,
+                    row_height,
+                    offsets.len(),
+                    |ui, row_range| {
+                        for i in row_range {
+                            if ui
+                                .selectable_label(
+                                    self.search_modal.current == Some(i),
+                                    egui::RichText::new(format!("0x{:X}", offsets[i])).monospace(),
+                                )
+                                .clicked()
+                            {
+                                goto_index = Some(i);
+                            }
+                        }
+                    },
+                );
+            }
+        }
+
+        if let Some(index) = goto_index {
+            self.go_to_match(index);
+        }
+
+        search_modal.buttons(ui, |ui| {
+            if ui.button("Search").clicked() || ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
+                match self.search() {
+                    Ok(()) => self.find_next(true),
+                    Err(e) => self.search_modal.status = e.to_string(),
+                }
+            }
+
+            if ui.button("Find previous (Shift+F3)").clicked() {
+                self.find_next(false);
+            }
+            if ui.button("Find next (F3)").clicked() {
+                self.find_next(true);
+            }
+
+            if search_modal.button(ui, "Close").clicked()
+                || ctx.input(|i| i.key_pressed(egui::Key::Escape))
+            {
+                search_modal.close();
+            }
+        });
+    }
+
     fn show_goto_modal(&mut self, goto_modal: &Modal, ui: &mut egui::Ui, ctx: &egui::Context) {
         goto_modal.title(ui, "Go to address");
-        ui.label("Enter a hex address to go to");
+        ui.label("Enter an address or expression to go to");
+        ui.label(
+            egui::RichText::new("Variables: filesize, cur_pos, selection_start, selection_end")
+                .small(),
+        );
 
         ui.text_edit_singleline(&mut self.goto_modal.value)
             .request_focus();
 
+        ui.checkbox(&mut self.goto_modal.all_views, "Apply to all views");
+
         ui.label(egui::RichText::new(self.goto_modal.status.clone()).color(egui::Color32::RED));
 
         goto_modal.buttons(ui, |ui| {
             if ui.button("Go").clicked() || ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
-                let pos: Option<usize> = parse_int::parse(&self.goto_modal.value).ok();
-
-                match pos {
-                    Some(pos) => {
-                        for hv in self.hex_views.iter_mut() {
+                let all_views = self.goto_modal.all_views;
+                let last_selected_hv = self.last_selected_hv;
+                let is_target = |hv: &HexView| all_views || Some(hv.id) == last_selected_hv;
+
+                // Variables differ per file, so evaluate the expression for each view
+                let positions: Result<Vec<usize>, Error> = self
+                    .hex_views
+                    .iter()
+                    .filter(|hv| is_target(hv))
+                    .map(|hv| {
+                        let vars = GotoVariables {
+                            filesize: hv.file.data.len(),
+                            cur_pos: hv.virtual_addr(hv.cur_pos),
+                            selection_start: hv.virtual_addr(hv.selection.start()),
+                            selection_end: hv.virtual_addr(hv.selection.end()),
+                        };
+                        let addr = goto::evaluate(&self.goto_modal.value, &vars)?;
+                        hv.file_offset(addr).ok_or_else(|| {
+                            Error::msg(format!(
+                                "0x{:X} is below the virtual base of {}",
+                                addr,
+                                hv.file_name()
+                            ))
+                        })
+                    })
+                    .collect();
+
+                match positions {
+                    Ok(positions) if positions.is_empty() => {
+                        self.goto_modal.status = "No view is selected".to_owned();
+                    }
+                    Ok(positions) => {
+                        let targets = self.hex_views.iter_mut().filter(|hv| is_target(hv));
+                        for (hv, pos) in targets.zip(positions) {
                             hv.set_cur_pos(pos);
                         }
+                        self.goto_modal.status = "".to_owned();
                         goto_modal.close();
                     }
-                    None => {
-                        self.goto_modal.status = "Invalid address".to_owned();
-                        self.goto_modal.value = "0x".to_owned();
+                    Err(e) => {
+                        self.goto_modal.status = format!("Invalid address: {}", e);
                     }
                 }
             }
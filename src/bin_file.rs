@@ -3,13 +3,15 @@ use std::{
     io::{BufReader, Read},
     path::PathBuf,
     sync::{atomic::AtomicBool, Arc},
+    time::Duration,
 };
 
-use anyhow::Error;
+use anyhow::{Context, Error};
 
-use crate::watcher::create_watcher;
+use crate::file_format::{self, DetectedFormat};
+use crate::watcher::{create_watcher, FileWatcher};
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub enum Endianness {
     Little,
     #[default]
@@ -21,10 +23,68 @@ pub struct BinFile {
     pub path: PathBuf,
     pub data: Vec<u8>,
     pub endianness: Endianness,
-    watcher: Option<notify::RecommendedWatcher>,
+    pub detected_format: Option<DetectedFormat>,
+    watcher: Option<FileWatcher>,
     pub modified: Arc<AtomicBool>,
 }
 
+fn read_ips_number(patch: &[u8], pos: &mut usize, len: usize) -> Result<usize, Error> {
+    let bytes = patch
+        .get(*pos..*pos + len)
+        .ok_or_else(|| Error::msg(format!("IPS patch ends early at 0x{:X}", *pos)))?;
+    *pos += len;
+    Ok(bytes.iter().fold(0, |acc, b| (acc << 8) | *b as usize))
+}
+
+/// Applies an IPS patch, growing the data when a record writes past its end.
+/// The data is left untouched if the patch is malformed.
+pub fn apply_ips_patch(data: &mut Vec<u8>, patch: &[u8]) -> Result<(), Error> {
+    let rest = patch
+        .strip_prefix(b"PATCH")
+        .ok_or_else(|| Error::msg("Not an IPS patch: missing \"PATCH\" header"))?;
+    let mut patched = data.clone();
+    let mut pos = patch.len() - rest.len();
+
+    loop {
+        if patch.get(pos..pos + 3) == Some(b"EOF") {
+            pos += 3;
+            break;
+        }
+
+        let offset = read_ips_number(patch, &mut pos, 3)?;
+        let len = read_ips_number(patch, &mut pos, 2)?;
+        let (len, record) = match len {
+            // RLE record: a run length and the byte to repeat
+            0 => {
+                let run = read_ips_number(patch, &mut pos, 2)?;
+                let value = read_ips_number(patch, &mut pos, 1)? as u8;
+                (run, vec![value; run])
+            }
+            _ => {
+                let record = patch.get(pos..pos + len).ok_or_else(|| {
+                    Error::msg(format!("IPS record at 0x{:X} is cut off", pos - 5))
+                })?;
+                pos += len;
+                (len, record.to_vec())
+            }
+        };
+
+        if patched.len() < offset + len {
+            patched.resize(offset + len, 0);
+        }
+        patched[offset..offset + len].copy_from_slice(&record);
+    }
+
+    // Some patches end with the size to truncate the file to
+    if patch.len() >= pos + 3 {
+        let size = read_ips_number(patch, &mut pos, 3)?;
+        patched.truncate(size);
+    }
+
+    *data = patched;
+    Ok(())
+}
+
 pub fn read_file_bytes<P: Into<PathBuf>>(path: P) -> Result<Vec<u8>, Error> {
     let file = match File::open(path.into()) {
         Ok(file) => file,
@@ -46,25 +106,116 @@ pub fn read_file_bytes<P: Into<PathBuf>>(path: P) -> Result<Vec<u8>, Error> {
 impl BinFile {
     pub fn from_path<P: Into<PathBuf>>(path: P) -> Result<Self, Error> {
         let path: PathBuf = path.into();
-        let data = match read_file_bytes(&path) {
-            Ok(data) => data,
-            Err(e) => return Err(e),
-        };
+        let data = read_file_bytes(&path)?;
 
         let mut ret = Self {
-            path: path.clone(),
+            path,
+            detected_format: file_format::detect(&data),
             data,
             watcher: None,
             ..Default::default()
         };
+        ret.watch(None);
 
-        match create_watcher(path, ret.modified.clone()).map_err(anyhow::Error::new) {
+        Ok(ret)
+    }
+
+    /// A file that only exists in memory, such as data received over the network.
+    /// It isn't watched since there is nothing on disk to reload from.
+    pub fn from_bytes<P: Into<PathBuf>>(path: P, data: Vec<u8>) -> Self {
+        Self {
+            path: path.into(),
+            detected_format: file_format::detect(&data),
+            data,
+            ..Default::default()
+        }
+    }
+
+    /// (Re)starts watching the file, polling at the interval if one is given
+    pub fn watch(&mut self, poll_interval: Option<Duration>) {
+        // Drop the old watcher first so the file isn't watched twice
+        self.watcher = None;
+
+        match create_watcher(&self.path, self.modified.clone(), poll_interval)
+            .map_err(anyhow::Error::new)
+        {
             Ok(watcher) => {
-                ret.watcher = Some(watcher);
+                self.watcher = Some(watcher);
             }
             Err(e) => log::error!("Failed to create watcher: {e}"),
         }
+    }
 
-        Ok(ret)
+    pub fn write_to_path<P: Into<PathBuf>>(&self, path: P) -> Result<(), Error> {
+        let path: PathBuf = path.into();
+        std::fs::write(&path, &self.data)
+            .with_context(|| format!("Failed to write file {}", path.display()))
+    }
+
+    /// Writes the bytes from `start` to `end`, inclusive, to a new file
+    pub fn write_range<P: Into<PathBuf>>(
+        &self,
+        path: P,
+        start: usize,
+        end: usize,
+    ) -> Result<(), Error> {
+        let path: PathBuf = path.into();
+        let data = self.data.get(start..=end).ok_or_else(|| {
+            Error::msg(format!("Range 0x{:X}-0x{:X} is out of bounds", start, end))
+        })?;
+        std::fs::write(&path, data)
+            .with_context(|| format!("Failed to write file {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_ips_patch_writes_records() {
+        let mut data = vec![0; 8];
+        let mut patch = b"PATCH".to_vec();
+        // A literal record, then an RLE record writing 0xAA 3 times
+        patch.extend_from_slice(&[0, 0, 1, 0, 2, 0x11, 0x22]);
+        patch.extend_from_slice(&[0, 0, 4, 0, 0, 0, 3, 0xAA]);
+        patch.extend_from_slice(b"EOF");
+
+        apply_ips_patch(&mut data, &patch).unwrap();
+        assert_eq!(data, [0, 0x11, 0x22, 0, 0xAA, 0xAA, 0xAA, 0]);
+    }
+
+    #[test]
+    fn apply_ips_patch_resizes_the_data() {
+        let mut data = vec![0; 4];
+        let mut patch = b"PATCH".to_vec();
+        patch.extend_from_slice(&[0, 0, 6, 0, 2, 1, 2]);
+        patch.extend_from_slice(b"EOF");
+        apply_ips_patch(&mut data, &patch).unwrap();
+        assert_eq!(data, [0, 0, 0, 0, 0, 0, 1, 2]);
+
+        // Truncated to the size after the EOF marker
+        let mut patch = b"PATCH".to_vec();
+        patch.extend_from_slice(b"EOF");
+        patch.extend_from_slice(&[0, 0, 3]);
+        apply_ips_patch(&mut data, &patch).unwrap();
+        assert_eq!(data, [0, 0, 0]);
+    }
+
+    #[test]
+    fn apply_ips_patch_rejects_malformed_patches() {
+        let mut data = vec![0; 4];
+        let malformed: [&[u8]; 4] = [
+            b"",
+            b"PACTH\0\0\0\0\x01\x01EOF",
+            // A record with fewer bytes than its length
+            b"PATCH\0\0\0\0\x04\x01\x02",
+            // Missing the EOF marker
+            b"PATCH\0\0\0\0\x01\x01",
+        ];
+        for patch in malformed {
+            assert!(apply_ips_patch(&mut data, patch).is_err());
+            assert_eq!(data, [0; 4]);
+        }
     }
 }
@@ -0,0 +1,70 @@
+use anyhow::Error;
+
+/// A named range of bits within a 32-bit word, inclusive on both ends
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitField {
+    pub name: String,
+    pub high: u32,
+    pub low: u32,
+}
+
+impl BitField {
+    pub fn extract(&self, value: u32) -> u32 {
+        let width = self.high - self.low + 1;
+        let mask = match width {
+            32 => u32::MAX,
+            _ => (1 << width) - 1,
+        };
+        (value >> self.low) & mask
+    }
+}
+
+fn parse_bit(s: &str) -> Result<u32, Error> {
+    let bit: u32 = s
+        .trim()
+        .parse()
+        .map_err(|_| Error::msg(format!("Invalid bit index \"{}\"", s.trim())))?;
+    if bit > 31 {
+        return Err(Error::msg(format!("Bit index {} is out of range", bit)));
+    }
+    Ok(bit)
+}
+
+/// Parses a layout such as `bits 31:24 = opcode, bits 23:16 = rs, bit 0 = flag`
+pub fn parse_layout(layout: &str) -> Result<Vec<BitField>, Error> {
+    layout
+        .split([',', ';', '\n'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|field| {
+            let (bits, name) = field
+                .split_once('=')
+                .ok_or_else(|| Error::msg(format!("Missing '=' in \"{}\"", field)))?;
+
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(Error::msg(format!("Missing field name in \"{}\"", field)));
+            }
+
+            let bits = bits.trim();
+            let bits = bits
+                .strip_prefix("bits")
+                .or_else(|| bits.strip_prefix("bit"))
+                .unwrap_or(bits);
+
+            let (high, low) = match bits.split_once(':') {
+                Some((high, low)) => (parse_bit(high)?, parse_bit(low)?),
+                None => {
+                    let bit = parse_bit(bits)?;
+                    (bit, bit)
+                }
+            };
+
+            Ok(BitField {
+                name: name.to_owned(),
+                high: high.max(low),
+                low: high.min(low),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,108 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+/// A named offset in a file
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Bookmark {
+    pub name: String,
+    pub offset: usize,
+}
+
+pub enum BookmarkAction {
+    Add(String),
+    /// Add a bookmark at the offset, named after its address
+    AddAt(usize),
+    Delete(usize),
+    GoTo(usize),
+}
+
+#[derive(Default)]
+pub struct BookmarksPanel {
+    pub show: bool,
+    new_name: String,
+}
+
+impl BookmarksPanel {
+    pub fn display(
+        &mut self,
+        ui: &mut egui::Ui,
+        hv_id: usize,
+        bookmarks: &[Bookmark],
+        virtual_base: usize,
+    ) -> Option<BookmarkAction> {
+        if !self.show {
+            return None;
+        }
+
+        let mut action = None;
+
+        ui.group(|ui| {
+            ui.with_layout(egui::Layout::top_down(eframe::emath::Align::Min), |ui| {
+                ui.add(egui::Label::new(
+                    egui::RichText::new("Bookmarks").monospace(),
+                ));
+
+                ui.horizontal(|ui| {
+                    let res = ui.add(
+                        egui::TextEdit::singleline(&mut self.new_name)
+                            .desired_width(150.0)
+                            .hint_text("Name"),
+                    );
+                    let submitted =
+                        res.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if (ui
+                        .button("Add")
+                        .on_hover_text(
+                            "Bookmark the start of the selection, or the top of the view",
+                        )
+                        .clicked()
+                        || submitted)
+                        && !self.new_name.trim().is_empty()
+                    {
+                        action = Some(BookmarkAction::Add(self.new_name.trim().to_owned()));
+                        self.new_name.clear();
+                    }
+                });
+
+                if bookmarks.is_empty() {
+                    ui.label("No bookmarks");
+                    return;
+                }
+
+                egui::Grid::new(format!("bookmarks_grid_{}", hv_id))
+                    .striped(true)
+                    .num_columns(3)
+                    .show(ui, |ui| {
+                        for (i, bookmark) in bookmarks.iter().enumerate() {
+                            let offset = ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(format!(
+                                        "0x{:X}",
+                                        virtual_base.saturating_add(bookmark.offset)
+                                    ))
+                                    .monospace(),
+                                )
+                                .sense(egui::Sense::click()),
+                            );
+                            let name = ui
+                                .add(egui::Label::new(&bookmark.name).sense(egui::Sense::click()));
+                            if offset.double_clicked() || name.double_clicked() {
+                                action = Some(BookmarkAction::GoTo(bookmark.offset));
+                            }
+
+                            if ui
+                                .button(egui_phosphor::regular::TRASH)
+                                .on_hover_text("Delete bookmark")
+                                .clicked()
+                            {
+                                action = Some(BookmarkAction::Delete(i));
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+
+        action
+    }
+}
@@ -0,0 +1,212 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use eframe::epaint::Color32;
+
+/// What the hex bytes are colored by, instead of the usual null/other colors
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ByteColoring {
+    #[default]
+    Default,
+    /// Shannon entropy of the bytes around each byte
+    Entropy,
+    /// Distance to the nearest null byte
+    Density,
+}
+
+impl ByteColoring {
+    pub const ALL: [ByteColoring; 3] = [Self::Default, Self::Entropy, Self::Density];
+}
+
+impl fmt::Display for ByteColoring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Default => "Default",
+            Self::Entropy => "Entropy",
+            Self::Density => "Data density",
+        })
+    }
+}
+
+/// Blends from `low` at 0.0 to `high` at 1.0
+pub fn gradient(low: Color32, high: Color32, t: f32) -> Color32 {
+    let t = t.clamp(0.0, 1.0);
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    Color32::from_rgba_premultiplied(
+        mix(low.r(), high.r()),
+        mix(low.g(), high.g()),
+        mix(low.b(), high.b()),
+        mix(low.a(), high.a()),
+    )
+}
+
+/// Distance at which the density gradient reaches its far color
+const MAX_ZERO_DISTANCE: u16 = 256;
+
+/// Distance in bytes from each byte to the nearest null byte, saturating at the end of the
+/// density gradient
+pub fn zero_distances(data: &[u8]) -> Vec<u16> {
+    let distance = |from: usize, to: usize| (to - from).min(MAX_ZERO_DISTANCE as usize) as u16;
+    let mut distances = vec![MAX_ZERO_DISTANCE; data.len()];
+
+    // Distance to the closest null at or before each byte, then at or after it
+    let mut last_zero = None;
+    for (i, &b) in data.iter().enumerate() {
+        if b == 0 {
+            last_zero = Some(i);
+        }
+        if let Some(zero) = last_zero {
+            distances[i] = distance(zero, i);
+        }
+    }
+    let mut next_zero = None;
+    for (i, &b) in data.iter().enumerate().rev() {
+        if b == 0 {
+            next_zero = Some(i);
+        }
+        if let Some(zero) = next_zero {
+            distances[i] = distances[i].min(distance(i, zero));
+        }
+    }
+
+    distances
+}
+
+/// Maps a distance to 0.0..=1.0 on a log scale, reaching 1.0 at `MAX_ZERO_DISTANCE` bytes
+pub fn density_level(distance: u16) -> f32 {
+    ((distance as f32).ln_1p() / (MAX_ZERO_DISTANCE as f32).ln_1p()).min(1.0)
+}
+
+/// The most entropy a window can have, as it can't hold more distinct bytes than its size
+pub fn max_entropy(window: usize) -> f32 {
+    (window.clamp(2, 256) as f32).log2()
+}
+
+/// Entropy in bits of the `window` bytes centered on each byte.
+/// The window is kept as byte counts that are updated as it slides, so this is linear in the data size.
+pub fn sliding_entropy(data: &[u8], window: usize) -> Vec<f32> {
+    let window = window.max(1);
+    // c * log2(c) for every count a window can reach
+    let c_log_c: Vec<f64> = (0..=window)
+        .map(|c| match c {
+            0 => 0.0,
+            c => c as f64 * (c as f64).log2(),
+        })
+        .collect();
+
+    let mut counts = [0usize; 256];
+    let mut sum = 0.0;
+    let (mut start, mut end) = (0, 0);
+    let mut entropy = Vec::with_capacity(data.len());
+
+    for i in 0..data.len() {
+        let new_start = i.saturating_sub(window / 2);
+        let new_end = (new_start + window).min(data.len());
+
+        // Drop bytes before adding new ones so no count goes past the window size
+        while start < new_start {
+            let c = &mut counts[data[start] as usize];
+            sum += c_log_c[*c - 1] - c_log_c[*c];
+            *c -= 1;
+            start += 1;
+        }
+        while end < new_end {
+            let c = &mut counts[data[end] as usize];
+            sum += c_log_c[*c + 1] - c_log_c[*c];
+            *c += 1;
+            end += 1;
+        }
+
+        // H = log2(n) - sum(c * log2(c)) / n
+        let n = (end - start) as f64;
+        entropy.push((n.log2() - sum / n).max(0.0) as f32);
+    }
+
+    entropy
+}
+
+/// Per-byte entropy of a file, computed on a background thread.
+/// Until the first computation finishes the map is empty and bytes keep their usual colors.
+#[derive(Default)]
+pub struct EntropyMap {
+    pub values: Arc<Mutex<Vec<f32>>>,
+    /// Bumped for every new computation, so results for outdated data are dropped
+    generation: Arc<AtomicUsize>,
+    /// Window size the current or pending values are for, None when they need recomputing
+    window: Option<usize>,
+}
+
+impl EntropyMap {
+    /// The data changed, the values are recomputed the next time they are needed
+    pub fn invalidate(&mut self) {
+        self.window = None;
+    }
+
+    /// Starts a computation if the values are out of date
+    pub fn update(&mut self, data: &[u8], window: usize) {
+        if self.window == Some(window) {
+            return;
+        }
+        self.window = Some(window);
+
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let current_generation = self.generation.clone();
+        let values = self.values.clone();
+        let data = data.to_vec();
+
+        thread::spawn(move || {
+            let entropy = sliding_entropy(&data, window);
+            if current_generation.load(Ordering::SeqCst) == generation {
+                *values.lock().unwrap() = entropy;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sliding_entropy_counts_distinct_bytes_in_the_window() {
+        assert_eq!(sliding_entropy(&[7; 32], 8), vec![0.0; 32]);
+
+        let alternating: Vec<u8> = (0..32).map(|i| i % 2).collect();
+        assert!(sliding_entropy(&alternating, 8)[..=28]
+            .iter()
+            .all(|bits| (bits - 1.0).abs() < 1e-6));
+
+        // Every window of 4 distinct bytes holds 2 bits, except where the end cuts it short
+        let distinct: Vec<u8> = (0..16).collect();
+        let entropy = sliding_entropy(&distinct, 4);
+        assert!(entropy[..13].iter().all(|bits| (bits - 2.0).abs() < 1e-6));
+        assert!((entropy[15] - 3f32.log2()).abs() < 1e-6);
+
+        assert!(sliding_entropy(&[], 16).is_empty());
+    }
+
+    #[test]
+    fn max_entropy_is_limited_by_the_window() {
+        assert_eq!(max_entropy(16), 4.0);
+        assert_eq!(max_entropy(256), 8.0);
+        assert_eq!(max_entropy(4096), 8.0);
+    }
+
+    #[test]
+    fn zero_distances_saturate_at_the_gradients_range() {
+        assert_eq!(zero_distances(&[1, 0, 1, 1, 1, 0]), vec![1, 0, 1, 2, 1, 0]);
+        assert_eq!(zero_distances(&[1, 2]), vec![MAX_ZERO_DISTANCE; 2]);
+
+        let mut data = vec![1; 300];
+        data.push(0);
+        let distances = zero_distances(&data);
+        assert_eq!(distances[0], MAX_ZERO_DISTANCE);
+        assert_eq!(distances[299], 1);
+    }
+}
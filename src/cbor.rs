@@ -0,0 +1,287 @@
+use ciborium_ll::{Decoder, Header};
+
+/// Containers nested deeper than this are reported as an error instead of overflowing the stack
+const MAX_DEPTH: usize = 128;
+/// Byte strings longer than this are shortened in their label
+const MAX_BYTES_SHOWN: usize = 32;
+/// Decoding stops after this many items, since every one of them is drawn
+const MAX_NODES: usize = 10_000;
+
+/// One decoded CBOR item. Arrays, maps and tags have their contents as children.
+#[derive(Debug)]
+pub struct CborNode {
+    pub label: String,
+    pub children: Vec<CborNode>,
+}
+
+/// The items decoded from a byte sequence, up to the first error
+#[derive(Debug, Default)]
+pub struct CborDecode {
+    pub items: Vec<CborNode>,
+    pub error: Option<String>,
+}
+
+/// Reading from a slice can only fail by running out of data
+fn describe_error<E>(err: ciborium_ll::Error<E>) -> String {
+    match err {
+        ciborium_ll::Error::Io(_) => "Unexpected end of data".to_owned(),
+        ciborium_ll::Error::Syntax(offset) => format!("Invalid CBOR at offset 0x{:X}", offset),
+    }
+}
+
+fn bytes_label(bytes: &[u8]) -> String {
+    let hex: String = bytes
+        .iter()
+        .take(MAX_BYTES_SHOWN)
+        .map(|b| format!("{:02X}", b))
+        .collect();
+    match bytes.len() > MAX_BYTES_SHOWN {
+        true => format!("h'{}…' ({} bytes)", hex, bytes.len()),
+        false => format!("h'{}'", hex),
+    }
+}
+
+fn simple_label(value: u8) -> String {
+    match value {
+        20 => "false".to_owned(),
+        21 => "true".to_owned(),
+        22 => "null".to_owned(),
+        23 => "undefined".to_owned(),
+        _ => format!("simple({})", value),
+    }
+}
+
+/// Whether a container has another item after `i` of them. Indefinite length containers end
+/// with a break, which is consumed.
+fn has_next(decoder: &mut Decoder<&[u8]>, len: Option<usize>, i: usize) -> Result<bool, String> {
+    if let Some(len) = len {
+        return Ok(i < len);
+    }
+
+    match decoder.pull().map_err(describe_error)? {
+        Header::Break => Ok(false),
+        header => {
+            decoder.push(header);
+            Ok(true)
+        }
+    }
+}
+
+/// Decodes one item and appends it to `out`, labeled with the prefix. Containers are appended
+/// before their contents are decoded, so an error leaves the items read so far in the tree.
+fn decode_item(
+    decoder: &mut Decoder<&[u8]>,
+    out: &mut Vec<CborNode>,
+    prefix: String,
+    depth: usize,
+    num_nodes: &mut usize,
+) -> Result<(), String> {
+    if depth > MAX_DEPTH {
+        return Err(format!(
+            "Nested too deeply at offset 0x{:X}",
+            decoder.offset()
+        ));
+    }
+    if *num_nodes >= MAX_NODES {
+        return Err(format!(
+            "Truncated after {} items at offset 0x{:X}",
+            MAX_NODES,
+            decoder.offset()
+        ));
+    }
+    *num_nodes += 1;
+
+    let offset = decoder.offset();
+    let mut leaf = |label: String| {
+        out.push(CborNode {
+            label: format!("{}{}", prefix, label),
+            children: Vec::new(),
+        })
+    };
+
+    match decoder.pull().map_err(describe_error)? {
+        Header::Positive(value) => leaf(value.to_string()),
+        Header::Negative(value) => leaf((-1 - value as i128).to_string()),
+        Header::Float(value) => leaf(value.to_string()),
+        Header::Simple(value) => leaf(simple_label(value)),
+        Header::Break => return Err(format!("Unexpected break at offset 0x{:X}", offset)),
+        Header::Bytes(len) => {
+            let mut bytes = Vec::new();
+            let mut buffer = [0; 4096];
+            let mut segments = decoder.bytes(len);
+            while let Some(mut segment) = segments.pull().map_err(describe_error)? {
+                while let Some(chunk) = segment.pull(&mut buffer).map_err(describe_error)? {
+                    bytes.extend_from_slice(chunk);
+                }
+            }
+            leaf(bytes_label(&bytes));
+        }
+        Header::Text(len) => {
+            let mut text = String::new();
+            let mut buffer = [0; 4096];
+            let mut segments = decoder.text(len);
+            while let Some(mut segment) = segments.pull().map_err(describe_error)? {
+                while let Some(chunk) = segment.pull(&mut buffer).map_err(describe_error)? {
+                    text.push_str(chunk);
+                }
+            }
+            leaf(format!("{:?}", text));
+        }
+        Header::Tag(tag) => {
+            leaf(format!("tag {}", tag));
+            let node = out.last_mut().unwrap();
+            decode_item(
+                decoder,
+                &mut node.children,
+                String::new(),
+                depth + 1,
+                num_nodes,
+            )?;
+        }
+        Header::Array(len) => {
+            leaf(match len {
+                Some(len) => format!("array ({})", len),
+                None => "array (indefinite)".to_owned(),
+            });
+            let children = &mut out.last_mut().unwrap().children;
+            let mut i = 0;
+            while has_next(decoder, len, i)? {
+                decode_item(decoder, children, format!("[{}] ", i), depth + 1, num_nodes)?;
+                i += 1;
+            }
+        }
+        Header::Map(len) => {
+            leaf(match len {
+                Some(len) => format!("map ({})", len),
+                None => "map (indefinite)".to_owned(),
+            });
+            let children = &mut out.last_mut().unwrap().children;
+            let mut i = 0;
+            while has_next(decoder, len, i)? {
+                let mut key = Vec::new();
+                if let Err(err) =
+                    decode_item(decoder, &mut key, String::new(), depth + 1, num_nodes)
+                {
+                    children.append(&mut key);
+                    return Err(err);
+                }
+                let key = key.remove(0);
+                if key.children.is_empty() {
+                    let prefix = format!("{}: ", key.label);
+                    decode_item(decoder, children, prefix, depth + 1, num_nodes)?;
+                } else {
+                    // A container can't be written inline as a key, so the entry holds both
+                    children.push(CborNode {
+                        label: format!("[{}] entry", i),
+                        children: vec![CborNode {
+                            label: format!("key: {}", key.label),
+                            children: key.children,
+                        }],
+                    });
+                    let entry = &mut children.last_mut().unwrap().children;
+                    let prefix = "value: ".to_owned();
+                    decode_item(decoder, entry, prefix, depth + 1, num_nodes)?;
+                }
+                i += 1;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Decodes a sequence of CBOR items, keeping whatever was decoded before an error
+pub fn decode(data: &[u8]) -> CborDecode {
+    let mut decoder = Decoder::from(data);
+    let mut decoded = CborDecode::default();
+    let mut num_nodes = 0;
+
+    while decoder.offset() < data.len() {
+        let items = &mut decoded.items;
+        if let Err(err) = decode_item(&mut decoder, items, String::new(), 0, &mut num_nodes) {
+            decoded.error = Some(err);
+            break;
+        }
+    }
+
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(nodes: &[CborNode]) -> Vec<&str> {
+        nodes.iter().map(|node| node.label.as_str()).collect()
+    }
+
+    #[test]
+    fn decode_reads_a_sequence_of_items() {
+        // 1, -10, "hi", h'0102', true, 1.5 as a half float
+        let decoded = decode(&[
+            0x01, 0x29, 0x62, b'h', b'i', 0x42, 1, 2, 0xF5, 0xF9, 0x3E, 0x00,
+        ]);
+        assert_eq!(decoded.error, None);
+        assert_eq!(
+            labels(&decoded.items),
+            vec!["1", "-10", "\"hi\"", "h'0102'", "true", "1.5"]
+        );
+    }
+
+    #[test]
+    fn decode_builds_trees_of_containers() {
+        // {"a": [1, 2], "b": tag 1(0)}, then an indefinite array [3]
+        let decoded = decode(&[
+            0xA2, 0x61, b'a', 0x82, 0x01, 0x02, 0x61, b'b', 0xC1, 0x00, 0x9F, 0x03, 0xFF,
+        ]);
+        assert_eq!(decoded.error, None);
+        assert_eq!(
+            labels(&decoded.items),
+            vec!["map (2)", "array (indefinite)"]
+        );
+
+        let map = &decoded.items[0].children;
+        assert_eq!(labels(map), vec!["\"a\": array (2)", "\"b\": tag 1"]);
+        assert_eq!(labels(&map[0].children), vec!["[0] 1", "[1] 2"]);
+        assert_eq!(labels(&map[1].children), vec!["0"]);
+        assert_eq!(labels(&decoded.items[1].children), vec!["[0] 3"]);
+    }
+
+    #[test]
+    fn decode_keeps_the_contents_of_container_keys() {
+        // {[1]: 2}
+        let decoded = decode(&[0xA1, 0x81, 0x01, 0x02]);
+        assert_eq!(decoded.error, None);
+
+        let entry = &decoded.items[0].children[0];
+        assert_eq!(entry.label, "[0] entry");
+        assert_eq!(labels(&entry.children), vec!["key: array (1)", "value: 2"]);
+        assert_eq!(labels(&entry.children[0].children), vec!["[0] 1"]);
+    }
+
+    #[test]
+    fn decode_keeps_the_items_before_an_error() {
+        // An array of 3 that ends after 2 items
+        let decoded = decode(&[0x07, 0x83, 0x01, 0x02]);
+        assert_eq!(decoded.error.as_deref(), Some("Unexpected end of data"));
+        assert_eq!(labels(&decoded.items), vec!["7", "array (3)"]);
+        assert_eq!(labels(&decoded.items[1].children), vec!["[0] 1", "[1] 2"]);
+
+        let decoded = decode(&[0x01, 0xFF]);
+        assert_eq!(
+            decoded.error.as_deref(),
+            Some("Unexpected break at offset 0x1")
+        );
+        assert_eq!(labels(&decoded.items), vec!["1"]);
+    }
+
+    #[test]
+    fn decode_limits_depth_and_item_count() {
+        let decoded = decode(&[0x81; MAX_DEPTH + 2]);
+        assert!(decoded.error.unwrap().starts_with("Nested too deeply"));
+
+        let decoded = decode(&vec![0; MAX_NODES + 10]);
+        assert!(decoded.error.unwrap().starts_with("Truncated"));
+        assert_eq!(decoded.items.len(), MAX_NODES);
+    }
+}
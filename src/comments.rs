@@ -0,0 +1,56 @@
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Error};
+
+/// Longest comment that can be attached to a byte, in characters
+pub const MAX_COMMENT_LEN: usize = 40;
+
+/// Comments on individual bytes, by offset
+pub type Comments = BTreeMap<usize, String>;
+
+/// The side-car file next to the binary that its comments are stored in
+fn comments_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".bdiff-comments.json");
+    PathBuf::from(name)
+}
+
+/// Reads the comments for a file. A file without a side-car has no comments.
+/// Comments edited by hand to be longer than the limit are cut short.
+pub fn read_comments(path: &Path) -> Result<Comments, Error> {
+    let comments_path = comments_path(path);
+    if !comments_path.exists() {
+        return Ok(Comments::new());
+    }
+
+    let reader = File::open(&comments_path)
+        .with_context(|| format!("Failed to open {}", comments_path.display()))?;
+    let mut comments: Comments = serde_json::from_reader(reader)
+        .with_context(|| format!("Failed to parse {}", comments_path.display()))?;
+    for comment in comments.values_mut() {
+        if let Some((end, _)) = comment.char_indices().nth(MAX_COMMENT_LEN) {
+            comment.truncate(end);
+        }
+    }
+    Ok(comments)
+}
+
+/// Writes the comments for a file, removing the side-car once the last one is gone
+pub fn write_comments(path: &Path, comments: &Comments) -> Result<(), Error> {
+    let comments_path = comments_path(path);
+    if comments.is_empty() {
+        if comments_path.exists() {
+            std::fs::remove_file(&comments_path)
+                .with_context(|| format!("Failed to remove {}", comments_path.display()))?;
+        }
+        return Ok(());
+    }
+
+    std::fs::write(&comments_path, serde_json::to_string_pretty(comments)?)
+        .with_context(|| format!("Failed to write {}", comments_path.display()))
+}
@@ -1,47 +1,128 @@
 use std::{
+    collections::BTreeMap,
     fs::{File, OpenOptions},
     io::Write,
+    ops::RangeInclusive,
     path::{Path, PathBuf},
 };
 
 use anyhow::{Context, Error};
 use serde::{Deserialize, Serialize};
 
+use crate::{bookmarks::Bookmark, watch::Watch};
+
+const CONFIG_FILE_NAME: &str = "bdiff.json";
+/// Shortest polling interval, so a tiny value doesn't keep rereading the file
+pub const MIN_POLL_INTERVAL_MS: u64 = 100;
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct FileConfig {
     pub path: PathBuf,
     pub map: Option<PathBuf>,
+    /// Address the start of the file is mapped to, added to all displayed offsets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub virtual_base: Option<usize>,
+    /// Poll the file for changes at this interval instead of using native notifications
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll_interval_ms: Option<u64>,
+    /// Row width for this file, overriding the one from the settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes_per_row: Option<usize>,
+    /// Position of the file's window, restored when the workspace is opened
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_pos: Option<[f32; 2]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_size: Option<[f32; 2]>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bookmarks: Vec<Bookmark>,
+    /// Offsets logged in the watch panel whenever the file is reloaded
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub watches: Vec<Watch>,
+    /// Set to false to stop reloading the file when it changes on disk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_reload: Option<bool>,
 }
 
 impl From<PathBuf> for FileConfig {
     fn from(path: PathBuf) -> Self {
-        Self { path, map: None }
+        Self {
+            path,
+            map: None,
+            virtual_base: None,
+            poll_interval_ms: None,
+            bytes_per_row: None,
+            window_pos: None,
+            window_size: None,
+            bookmarks: Vec::new(),
+            watches: Vec::new(),
+            auto_reload: None,
+        }
     }
 }
 
 impl From<&Path> for FileConfig {
     fn from(path: &Path) -> Self {
         let path: PathBuf = path.into();
-        Self { path, map: None }
+        Self {
+            path,
+            map: None,
+            virtual_base: None,
+            poll_interval_ms: None,
+            bytes_per_row: None,
+            window_pos: None,
+            window_size: None,
+            bookmarks: Vec::new(),
+            watches: Vec::new(),
+            auto_reload: None,
+        }
     }
 }
 
 #[derive(Clone, Deserialize, Serialize, Default)]
 pub struct Config {
     pub files: Vec<FileConfig>,
+    /// Named bit field layouts for the data viewer
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub bit_field_profiles: BTreeMap<String, String>,
+    /// Ranges excluded from the diff
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diff_ignore_ranges: Vec<RangeInclusive<usize>>,
     #[serde(skip)]
     pub changed: bool,
 }
 
+/// The path the workspace config is saved to.
+/// On Linux this is `$XDG_CONFIG_HOME/bdiff/bdiff.json`, elsewhere the current directory.
+pub fn get_config_path() -> PathBuf {
+    #[cfg(target_os = "linux")]
+    if let Some(mut path) = dirs::config_dir() {
+        path.push("bdiff");
+        path.push(CONFIG_FILE_NAME);
+        return path;
+    }
+
+    PathBuf::from(CONFIG_FILE_NAME)
+}
+
+/// Finds an existing workspace config, falling back to the current directory
+pub fn find_config_path() -> Option<PathBuf> {
+    [get_config_path(), PathBuf::from(CONFIG_FILE_NAME)]
+        .into_iter()
+        .find(|path| path.exists())
+}
+
 pub fn read_json_config(config_path: &Path) -> Result<Config, Error> {
     let mut reader = File::open(config_path)
         .with_context(|| format!("Failed to open config file at {}", config_path.display()))?;
     Ok(serde_json::from_reader(&mut reader)?)
 }
 
-#[allow(dead_code)]
 pub fn write_json_config<P: Into<PathBuf>>(config_path: P, config: &Config) -> Result<(), Error> {
     let path: PathBuf = config_path.into();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create config folder {}", parent.display()))?;
+    }
     let mut oo = OpenOptions::new();
     let mut writer = oo
         .create(true)
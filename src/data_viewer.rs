@@ -1,6 +1,193 @@
+use std::{
+    fmt,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
 use eframe::egui;
+use half::f16;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    bin_file::Endianness,
+    bit_field::parse_layout,
+    cbor::{self, CborDecode, CborNode},
+    config::Config,
+    hex_view::OtherView,
+};
+
+/// Number of bytes XORed with each other view's selection
+pub const XOR_PREVIEW_LEN: usize = 100;
+
+/// A custom interpretation of the selected bytes, displayed as an extra row in the data viewer.
+pub trait DataInterpreter {
+    fn name(&self) -> &str;
+    fn interpret(&self, bytes: &[u8], endianness: Endianness) -> Option<String>;
+}
+
+/// Displays the selected bytes as binary digits
+struct BinaryInterpreter;
+
+/// Bytes shown in binary before the rest is elided
+const BINARY_PREVIEW_LEN: usize = 8;
+
+impl DataInterpreter for BinaryInterpreter {
+    fn name(&self) -> &str {
+        "bin"
+    }
+
+    fn interpret(&self, bytes: &[u8], _endianness: Endianness) -> Option<String> {
+        if bytes.is_empty() {
+            return None;
+        }
+
+        let mut ret = bytes
+            .iter()
+            .take(BINARY_PREVIEW_LEN)
+            .map(|b| format!("{:08b}", b))
+            .collect::<Vec<String>>()
+            .join(" ");
+        if bytes.len() > BINARY_PREVIEW_LEN {
+            ret.push_str(&format!(
+                " … (first {} of {} bytes)",
+                BINARY_PREVIEW_LEN,
+                bytes.len()
+            ));
+        }
+
+        Some(ret)
+    }
+}
 
-use crate::bin_file::Endianness;
+/// Digests of the selected bytes
+struct SelectionHashes {
+    /// Cheap hash of the bytes the digests were computed for
+    key: u64,
+    crc32: String,
+    md5: String,
+    sha256: String,
+}
+
+impl SelectionHashes {
+    fn key(bytes: &[u8]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn new(bytes: &[u8], key: u64) -> Self {
+        let sha256: String = Sha256::digest(bytes)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        Self {
+            key,
+            crc32: format!("{:08x}", crc32fast::hash(bytes)),
+            md5: format!("{:x}", md5::compute(bytes)),
+            sha256,
+        }
+    }
+}
+
+/// A number type the selected bytes can be read as
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DataType {
+    S8,
+    U8,
+    S16,
+    U16,
+    S32,
+    U32,
+    S64,
+    U64,
+    F16,
+    F32,
+    F64,
+}
+
+impl DataType {
+    pub const ALL: [DataType; 11] = [
+        Self::S8,
+        Self::U8,
+        Self::S16,
+        Self::U16,
+        Self::S32,
+        Self::U32,
+        Self::S64,
+        Self::U64,
+        Self::F16,
+        Self::F32,
+        Self::F64,
+    ];
+
+    pub fn size(&self) -> usize {
+        match self {
+            Self::S8 | Self::U8 => 1,
+            Self::S16 | Self::U16 | Self::F16 => 2,
+            Self::S32 | Self::U32 | Self::F32 => 4,
+            Self::S64 | Self::U64 | Self::F64 => 8,
+        }
+    }
+
+    /// Formats the value in `chunk`, which should be `size()` bytes long
+    pub fn format(
+        &self,
+        chunk: &[u8],
+        endianness: Endianness,
+        float_buffer: &mut dtoa::Buffer,
+    ) -> String {
+        macro_rules! read {
+            ($t:ty) => {{
+                let bytes = chunk.try_into().unwrap_or_default();
+                match endianness {
+                    Endianness::Little => <$t>::from_le_bytes(bytes),
+                    Endianness::Big => <$t>::from_be_bytes(bytes),
+                }
+            }};
+        }
+
+        match self {
+            Self::S8 => read!(i8).to_string(),
+            Self::U8 => read!(u8).to_string(),
+            Self::S16 => read!(i16).to_string(),
+            Self::U16 => read!(u16).to_string(),
+            Self::S32 => read!(i32).to_string(),
+            Self::U32 => read!(u32).to_string(),
+            Self::S64 => read!(i64).to_string(),
+            Self::U64 => read!(u64).to_string(),
+            Self::F16 => float_buffer
+                .format(f16::from_bits(read!(u16)).to_f32())
+                .to_owned(),
+            Self::F32 => float_buffer.format(read!(f32)).to_owned(),
+            Self::F64 => float_buffer.format(read!(f64)).to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::S8 => "s8",
+            Self::U8 => "u8",
+            Self::S16 => "s16",
+            Self::U16 => "u16",
+            Self::S32 => "s32",
+            Self::U32 => "u32",
+            Self::S64 => "s64",
+            Self::U64 => "u64",
+            Self::F16 => "f16",
+            Self::F32 => "f32",
+            Self::F64 => "f64",
+        })
+    }
+}
+
+/// Element type of the array view
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum ArrayElement {
+    #[default]
+    U32,
+    F32,
+}
 
 pub struct DataViewer {
     pub show: bool,
@@ -12,13 +199,28 @@ pub struct DataViewer {
     pub u32: bool,
     pub s64: bool,
     pub u64: bool,
+    pub f16: bool,
     pub f32: bool,
     pub f64: bool,
+    /// XOR of the selected bytes, and of the selection with other views' selections
+    pub xor: bool,
+    pub bit_fields: bool,
+    /// Shown in its own window instead of the hex view
+    pub detached: bool,
+    bit_field_layout: String,
+    bit_field_profile_name: String,
+    interpreters: Vec<Box<dyn DataInterpreter>>,
+    hashes: Option<SelectionHashes>,
+    /// The selection decoded as CBOR, keyed like the hashes
+    cbor: Option<(u64, CborDecode)>,
+    array_element: ArrayElement,
+    /// Byte order of the array view, the file's when None
+    array_endianness: Option<Endianness>,
 }
 
 impl Default for DataViewer {
     fn default() -> DataViewer {
-        DataViewer {
+        let mut ret = DataViewer {
             show: false,
             s8: true,
             u8: true,
@@ -28,12 +230,88 @@ impl Default for DataViewer {
             u32: true,
             s64: false,
             u64: false,
+            f16: true,
             f32: true,
             f64: true,
+            xor: true,
+            bit_fields: false,
+            detached: false,
+            bit_field_layout: String::new(),
+            bit_field_profile_name: String::new(),
+            interpreters: Vec::new(),
+            hashes: None,
+            cbor: None,
+            array_element: ArrayElement::default(),
+            array_endianness: None,
+        };
+
+        ret.register_interpreter(Box::new(BinaryInterpreter));
+
+        ret
+    }
+}
+
+/// Shows the XOR of the selected bytes, then the selection XORed with each other view's selection
+fn display_xor(ui: &mut egui::Ui, bytes: &[u8], other_views: &[OtherView]) {
+    if bytes.is_empty() {
+        return;
+    }
+
+    let xor = bytes.iter().fold(0, |acc, b| acc ^ b);
+    ui.add(egui::Label::new(egui::RichText::new("xor").monospace()));
+    ui.text_edit_singleline(&mut format!("0x{:02X}", xor));
+    ui.end_row();
+
+    for other in other_views.iter().filter(|v| !v.selected_bytes.is_empty()) {
+        let mut text = bytes
+            .iter()
+            .zip(other.selected_bytes.iter())
+            .take(XOR_PREVIEW_LEN)
+            .map(|(a, b)| format!("{:02X}", a ^ b))
+            .collect::<Vec<String>>()
+            .join(" ");
+        let xor_len = bytes.len().min(other.selection_len);
+        if xor_len > XOR_PREVIEW_LEN {
+            text.push_str(&format!(
+                " … (first {} of {} bytes)",
+                XOR_PREVIEW_LEN, xor_len
+            ));
+        }
+        if bytes.len() != other.selection_len {
+            text.push_str(&format!(
+                " (lengths differ: {} / {})",
+                bytes.len(),
+                other.selection_len
+            ));
         }
+
+        ui.add(egui::Label::new(
+            egui::RichText::new(format!("xor {}", other.name)).monospace(),
+        ))
+        .on_hover_text(format!("XOR with the selection in {}", other.name));
+        ui.text_edit_singleline(&mut text);
+        ui.end_row();
     }
 }
 
+/// Shows a decoded CBOR item, with arrays, maps and tags as collapsible branches
+fn display_cbor_node(ui: &mut egui::Ui, node: &CborNode, id: String) {
+    let text = egui::RichText::new(&node.label).monospace();
+    if node.children.is_empty() {
+        ui.label(text);
+        return;
+    }
+
+    egui::CollapsingHeader::new(text)
+        .id_source(&id)
+        .default_open(true)
+        .show(ui, |ui| {
+            for (i, child) in node.children.iter().enumerate() {
+                display_cbor_node(ui, child, format!("{}_{}", id, i));
+            }
+        });
+}
+
 fn display_type(
     ui: &mut egui::Ui,
     bytes: &[u8],
@@ -57,206 +335,433 @@ fn display_type(
     }
 }
 
-macro_rules! create_display_type {
-    ($ui:expr, $selected_bytes:expr, $endianness:expr, $delimiter:expr, $represented_type:ty, $type_name:literal, $value:expr, $size:expr) => {
-        display_type(
-            $ui,
-            $selected_bytes,
-            $value,
-            $type_name,
-            $size,
-            |chunk| {
-                format!(
-                    "{:}",
-                    match $endianness {
-                        Endianness::Little =>
-                            <$represented_type>::from_le_bytes(chunk.try_into().unwrap_or_default()),
-                        Endianness::Big =>
-                            <$represented_type>::from_be_bytes(chunk.try_into().unwrap_or_default()),
-                    }
-                )
-            },
-            $delimiter,
-        );
-    };
-    ($ui:expr, $selected_bytes:expr, $endianness:expr, $delimiter:expr, $represented_type:ty, $type_name:literal, $value:expr, $size:expr, $float_buffer:expr) => {
-        display_type(
-            $ui,
-            $selected_bytes,
-            $value,
-            $type_name,
-            $size,
-            |chunk| {
-                $float_buffer
-                    .format(match $endianness {
-                        Endianness::Little => {
-                            f64::from_le_bytes(chunk.try_into().unwrap_or_default())
-                        }
-                        Endianness::Big => f64::from_be_bytes(chunk.try_into().unwrap_or_default()),
-                    })
-                    .to_string()
-            },
-            $delimiter,
-        );
-    };
-}
-
 impl DataViewer {
+    fn shows(&self, data_type: DataType) -> bool {
+        match data_type {
+            DataType::S8 => self.s8,
+            DataType::U8 => self.u8,
+            DataType::S16 => self.s16,
+            DataType::U16 => self.u16,
+            DataType::S32 => self.s32,
+            DataType::U32 => self.u32,
+            DataType::S64 => self.s64,
+            DataType::U64 => self.u64,
+            DataType::F16 => self.f16,
+            DataType::F32 => self.f32,
+            DataType::F64 => self.f64,
+        }
+    }
+
+    pub fn register_interpreter(&mut self, interpreter: Box<dyn DataInterpreter>) {
+        self.interpreters.push(interpreter);
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn display(
         &mut self,
         ui: &mut egui::Ui,
         hv_id: usize,
         selected_bytes: Vec<u8>,
+        selected_bits: Option<(u8, u8)>,
         endianness: Endianness,
+        other_views: &[OtherView],
+        config: &mut Config,
     ) {
         if !self.show {
             return;
         }
 
-        ui.group(|ui| {
-            ui.vertical(|ui| {
-                ui.with_layout(
-                    egui::Layout::left_to_right(eframe::emath::Align::Min),
-                    |ui| {
+        if self.detached {
+            let mut open = true;
+            egui::Window::new("Data Viewer")
+                .id(egui::Id::new(format!("data_viewer_window{}", hv_id)))
+                .open(&mut open)
+                .resizable(false)
+                .show(ui.ctx(), |ui| {
+                    self.display_contents(
+                        ui,
+                        hv_id,
+                        selected_bytes,
+                        selected_bits,
+                        endianness,
+                        other_views,
+                        config,
+                    );
+                });
+            // Closing the window puts the viewer back into the hex view
+            self.detached &= open;
+        } else {
+            ui.group(|ui| {
+                self.display_contents(
+                    ui,
+                    hv_id,
+                    selected_bytes,
+                    selected_bits,
+                    endianness,
+                    other_views,
+                    config,
+                );
+            });
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn display_contents(
+        &mut self,
+        ui: &mut egui::Ui,
+        hv_id: usize,
+        selected_bytes: Vec<u8>,
+        selected_bits: Option<(u8, u8)>,
+        endianness: Endianness,
+        other_views: &[OtherView],
+        config: &mut Config,
+    ) {
+        ui.vertical(|ui| {
+            ui.with_layout(
+                egui::Layout::left_to_right(eframe::emath::Align::Min),
+                |ui| {
+                    ui.add(egui::Label::new(
+                        egui::RichText::new("Data Viewer").monospace(),
+                    ));
+
+                    ui.menu_button("...", |ui| {
+                        ui.checkbox(&mut self.s8, "s8");
+                        ui.checkbox(&mut self.u8, "u8");
+                        ui.checkbox(&mut self.s16, "s16");
+                        ui.checkbox(&mut self.u16, "u16");
+                        ui.checkbox(&mut self.s32, "s32");
+                        ui.checkbox(&mut self.u32, "u32");
+                        ui.checkbox(&mut self.s64, "s64");
+                        ui.checkbox(&mut self.u64, "u64");
+                        ui.checkbox(&mut self.f16, "f16");
+                        ui.checkbox(&mut self.f32, "f32");
+                        ui.checkbox(&mut self.f64, "f64");
+                        ui.checkbox(&mut self.xor, "XOR");
+                        ui.checkbox(&mut self.bit_fields, "Bit fields");
+                    });
+
+                    let (text, hover_text) = match self.detached {
+                        true => ("Dock", "Move back into the hex view"),
+                        false => ("Pop out", "Open in a separate window"),
+                    };
+                    if ui.button(text).on_hover_text(hover_text).clicked() {
+                        self.detached = !self.detached;
+                    }
+                },
+            );
+
+            egui::Grid::new(format!("hex_grid_selection{}", hv_id))
+                .striped(true)
+                .num_columns(2)
+                .show(ui, |ui| {
+                    if let (Some((high, low)), [byte]) = (selected_bits, selected_bytes.as_slice())
+                    {
+                        let value = (*byte as u32 >> low) & ((1 << (high - low + 1)) - 1);
                         ui.add(egui::Label::new(
-                            egui::RichText::new("Data Viewer").monospace(),
+                            egui::RichText::new(format!("bits {}:{}", high, low)).monospace(),
                         ));
+                        ui.text_edit_singleline(&mut format!("{} (0x{:X})", value, value));
+                        ui.end_row();
+                    }
+                    self.display_data_types(ui, &selected_bytes, endianness);
+                    if self.xor {
+                        display_xor(ui, &selected_bytes, other_views);
+                    }
+                });
 
-                        ui.menu_button("...", |ui| {
-                            ui.checkbox(&mut self.s8, "s8");
-                            ui.checkbox(&mut self.u8, "u8");
-                            ui.checkbox(&mut self.s16, "s16");
-                            ui.checkbox(&mut self.u16, "u16");
-                            ui.checkbox(&mut self.s32, "s32");
-                            ui.checkbox(&mut self.u32, "u32");
-                            ui.checkbox(&mut self.s64, "s64");
-                            ui.checkbox(&mut self.u64, "u64");
-                            ui.checkbox(&mut self.f32, "f32");
-                            ui.checkbox(&mut self.f64, "f64");
-                        });
-                    },
-                );
+            if self.bit_fields {
+                self.display_bit_field_profiles(ui, hv_id, config);
+            }
+
+            if !selected_bytes.is_empty() && selected_bytes.len().is_multiple_of(4) {
+                egui::CollapsingHeader::new("Array view")
+                    .id_source(format!("data_viewer_array{}", hv_id))
+                    .show(ui, |ui| {
+                        self.display_array(ui, hv_id, &selected_bytes, endianness);
+                    });
+            }
 
-                egui::Grid::new(format!("hex_grid_selection{}", hv_id))
+            egui::CollapsingHeader::new("Hashes")
+                .id_source(format!("data_viewer_hashes{}", hv_id))
+                .show(ui, |ui| {
+                    self.display_hashes(ui, hv_id, &selected_bytes);
+                });
+
+            egui::CollapsingHeader::new("CBOR")
+                .id_source(format!("data_viewer_cbor{}", hv_id))
+                .show(ui, |ui| {
+                    self.display_cbor(ui, hv_id, &selected_bytes);
+                });
+        });
+    }
+
+    fn display_array(
+        &mut self,
+        ui: &mut egui::Ui,
+        hv_id: usize,
+        bytes: &[u8],
+        file_endianness: Endianness,
+    ) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.array_element, ArrayElement::U32, "u32");
+            ui.radio_value(&mut self.array_element, ArrayElement::F32, "f32");
+            ui.separator();
+            ui.radio_value(&mut self.array_endianness, None, "File");
+            ui.radio_value(
+                &mut self.array_endianness,
+                Some(Endianness::Little),
+                "Little",
+            );
+            ui.radio_value(&mut self.array_endianness, Some(Endianness::Big), "Big");
+        });
+
+        let endianness = self.array_endianness.unwrap_or(file_endianness);
+        let mut float_buffer = dtoa::Buffer::new();
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+
+        egui::ScrollArea::vertical()
+            .id_source(format!("data_viewer_array_scroll{}", hv_id))
+            .max_height(200.0)
+            .show_rows(ui, row_height, bytes.len() / 4, |ui, row_range| {
+                egui::Grid::new(format!("data_viewer_array_grid{}", hv_id))
                     .striped(true)
                     .num_columns(2)
                     .show(ui, |ui| {
-                        self.display_data_types(ui, selected_bytes, endianness)
+                        for i in row_range {
+                            let chunk = &bytes[i * 4..i * 4 + 4];
+                            let word = chunk.try_into().unwrap_or_default();
+                            let value = match endianness {
+                                Endianness::Little => u32::from_le_bytes(word),
+                                Endianness::Big => u32::from_be_bytes(word),
+                            };
+                            let text = match self.array_element {
+                                ArrayElement::U32 => format!("{} (0x{:08X})", value, value),
+                                ArrayElement::F32 => {
+                                    float_buffer.format(f32::from_bits(value)).to_owned()
+                                }
+                            };
+
+                            ui.label(egui::RichText::new(format!("[{}]", i)).monospace());
+                            ui.label(egui::RichText::new(text).monospace());
+                            ui.end_row();
+                        }
                     });
             });
-        });
+    }
+
+    fn display_hashes(&mut self, ui: &mut egui::Ui, hv_id: usize, bytes: &[u8]) {
+        if bytes.is_empty() {
+            ui.label("Nothing selected");
+            return;
+        }
+
+        // Only rehash when the selected bytes actually changed
+        let key = SelectionHashes::key(bytes);
+        let hashes = match &mut self.hashes {
+            Some(hashes) if hashes.key == key => hashes,
+            hashes => hashes.insert(SelectionHashes::new(bytes, key)),
+        };
+
+        egui::Grid::new(format!("data_viewer_hashes_grid{}", hv_id))
+            .striped(true)
+            .num_columns(2)
+            .show(ui, |ui| {
+                for (name, value) in [
+                    ("CRC-32 (ISO-HDLC)", &hashes.crc32),
+                    ("MD5", &hashes.md5),
+                    ("SHA-256", &hashes.sha256),
+                ] {
+                    ui.add(egui::Label::new(egui::RichText::new(name).monospace()));
+                    ui.text_edit_singleline(&mut value.as_str());
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn display_cbor(&mut self, ui: &mut egui::Ui, hv_id: usize, bytes: &[u8]) {
+        if bytes.is_empty() {
+            ui.label("Nothing selected");
+            return;
+        }
+
+        let key = SelectionHashes::key(bytes);
+        let decoded = match &mut self.cbor {
+            Some((cbor_key, decoded)) if *cbor_key == key => decoded,
+            cbor => &cbor.insert((key, cbor::decode(bytes))).1,
+        };
+
+        egui::ScrollArea::vertical()
+            .id_source(format!("data_viewer_cbor_scroll{}", hv_id))
+            .max_height(300.0)
+            .show(ui, |ui| {
+                for (i, node) in decoded.items.iter().enumerate() {
+                    display_cbor_node(ui, node, format!("data_viewer_cbor{}_{}", hv_id, i));
+                }
+            });
+
+        if let Some(error) = &decoded.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
     }
 
     fn display_data_types(
         &mut self,
         ui: &mut egui::Ui,
-        selected_bytes: Vec<u8>,
+        selected_bytes: &[u8],
         endianness: Endianness,
     ) {
         let mut float_buffer = dtoa::Buffer::new();
         let delimiter = ", ";
 
-        create_display_type!(
-            ui,
-            &selected_bytes,
-            endianness,
-            delimiter,
-            i8,
-            "s8",
-            self.s8,
-            1
-        );
-        create_display_type!(
-            ui,
-            &selected_bytes,
-            endianness,
-            delimiter,
-            u8,
-            "u8",
-            self.u8,
-            1
-        );
-        create_display_type!(
-            ui,
-            &selected_bytes,
-            endianness,
-            delimiter,
-            i16,
-            "s16",
-            self.s16,
-            2
-        );
-        create_display_type!(
-            ui,
-            &selected_bytes,
-            endianness,
-            delimiter,
-            u16,
-            "u16",
-            self.u16,
-            2
+        for data_type in DataType::ALL {
+            display_type(
+                ui,
+                selected_bytes,
+                self.shows(data_type),
+                data_type.to_string(),
+                data_type.size(),
+                |chunk| data_type.format(chunk, endianness, &mut float_buffer),
+                delimiter,
+            );
+
+            // Shader constants and texture data often pack four halves together
+            if data_type == DataType::F16 && self.f16 && selected_bytes.len() == 8 {
+                let values: Vec<String> = selected_bytes
+                    .chunks_exact(2)
+                    .map(|chunk| data_type.format(chunk, endianness, &mut float_buffer))
+                    .collect();
+                ui.add(egui::Label::new(egui::RichText::new("f16x4").monospace()));
+                ui.text_edit_singleline(&mut format!("({})", values.join(", ")));
+                ui.end_row();
+            }
+        }
+
+        for interpreter in self.interpreters.iter() {
+            if let Some(mut data) = interpreter.interpret(selected_bytes, endianness) {
+                ui.add(egui::Label::new(
+                    egui::RichText::new(interpreter.name()).monospace(),
+                ));
+                ui.text_edit_singleline(&mut data);
+                ui.end_row();
+            }
+        }
+
+        if self.bit_fields {
+            self.display_bit_fields(ui, selected_bytes, endianness);
+        }
+    }
+
+    fn display_bit_fields(&mut self, ui: &mut egui::Ui, bytes: &[u8], endianness: Endianness) {
+        ui.add(egui::Label::new(egui::RichText::new("bits").monospace()));
+        ui.add(
+            egui::TextEdit::singleline(&mut self.bit_field_layout)
+                .hint_text("bits 31:26 = op, bits 25:21 = rs"),
         );
-        create_display_type!(
-            ui,
-            &selected_bytes,
-            endianness,
-            delimiter,
-            i32,
-            "s32",
-            self.s32,
-            4
+        ui.end_row();
+
+        let fields = match parse_layout(&self.bit_field_layout) {
+            Ok(fields) => fields,
+            Err(e) => {
+                ui.label("");
+                ui.colored_label(egui::Color32::RED, e.to_string());
+                ui.end_row();
+                return;
+            }
+        };
+
+        let Ok(word) = <[u8; 4]>::try_from(bytes) else {
+            return;
+        };
+        let value = match endianness {
+            Endianness::Little => u32::from_le_bytes(word),
+            Endianness::Big => u32::from_be_bytes(word),
+        };
+
+        for field in fields.iter() {
+            let extracted = field.extract(value);
+            ui.add(egui::Label::new(
+                egui::RichText::new(&field.name).monospace(),
+            ));
+            ui.text_edit_singleline(&mut format!("{} (0x{:X})", extracted, extracted));
+            ui.end_row();
+        }
+    }
+
+    fn display_bit_field_profiles(&mut self, ui: &mut egui::Ui, hv_id: usize, config: &mut Config) {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source(format!("bit_field_profiles{}", hv_id))
+                .selected_text("Load profile")
+                .show_ui(ui, |ui| {
+                    for (name, layout) in config.bit_field_profiles.iter() {
+                        if ui.selectable_label(false, name).clicked() {
+                            self.bit_field_layout = layout.clone();
+                            self.bit_field_profile_name = name.clone();
+                        }
+                    }
+                });
+
+            ui.add(
+                egui::TextEdit::singleline(&mut self.bit_field_profile_name)
+                    .desired_width(80.0)
+                    .hint_text("Profile name"),
+            );
+
+            let name = self.bit_field_profile_name.trim();
+            if ui
+                .add_enabled(!name.is_empty(), egui::Button::new("Save"))
+                .on_hover_text("Save the layout to the workspace")
+                .clicked()
+            {
+                config
+                    .bit_field_profiles
+                    .insert(name.to_owned(), self.bit_field_layout.clone());
+                config.changed = true;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_types_read_in_the_given_byte_order() {
+        let mut float_buffer = dtoa::Buffer::new();
+        let mut format = |data_type: DataType, chunk: &[u8], endianness| {
+            data_type.format(chunk, endianness, &mut float_buffer)
+        };
+
+        assert_eq!(format(DataType::S8, &[0xFF], Endianness::Little), "-1");
+        assert_eq!(format(DataType::U8, &[0xFF], Endianness::Little), "255");
+        assert_eq!(
+            format(DataType::U16, &[0x12, 0x34], Endianness::Little),
+            "13330"
         );
-        create_display_type!(
-            ui,
-            &selected_bytes,
-            endianness,
-            delimiter,
-            u32,
-            "u32",
-            self.u32,
-            4
+        assert_eq!(
+            format(DataType::U16, &[0x12, 0x34], Endianness::Big),
+            "4660"
         );
-        create_display_type!(
-            ui,
-            &selected_bytes,
-            endianness,
-            delimiter,
-            i64,
-            "s64",
-            self.s64,
-            8
+        assert_eq!(
+            format(DataType::S32, &[0xFE, 0xFF, 0xFF, 0xFF], Endianness::Little),
+            "-2"
         );
-        create_display_type!(
-            ui,
-            &selected_bytes,
-            endianness,
-            delimiter,
-            u64,
-            "u64",
-            self.u64,
-            8
+        assert_eq!(
+            format(DataType::U64, &[0, 0, 0, 0, 0, 0, 1, 0], Endianness::Big),
+            "256"
         );
-        create_display_type!(
-            ui,
-            &selected_bytes,
-            endianness,
-            delimiter,
-            f32,
-            "f32",
-            self.f32,
-            4,
-            float_buffer
+        assert_eq!(format(DataType::F16, &[0x3C, 0x00], Endianness::Big), "1.0");
+        assert_eq!(
+            format(DataType::F32, &[0x00, 0x00, 0xC0, 0x3F], Endianness::Little),
+            "1.5"
         );
-        create_display_type!(
-            ui,
-            &selected_bytes,
-            endianness,
-            delimiter,
-            f64,
-            "f64",
-            self.f64,
-            8,
-            float_buffer
+        assert_eq!(
+            format(
+                DataType::F64,
+                &[0x40, 0x04, 0, 0, 0, 0, 0, 0],
+                Endianness::Big
+            ),
+            "2.5"
         );
     }
 }
@@ -1,10 +1,629 @@
-use crate::hex_view::HexView;
+use std::{
+    collections::HashMap,
+    fmt,
+    ops::{Range, RangeInclusive},
+    path::Path,
+};
+
+use anyhow::Error;
+
+use crate::hex_view::{HexView, ViewGroup};
+
+pub const ROLLUP_BLOCK_SIZE: usize = 0x100;
+
+#[derive(Clone, Copy, Debug)]
+pub struct DiffBlock {
+    pub index: usize,
+    pub start: usize,
+    pub len: usize,
+    pub diff_count: usize,
+}
+
+impl DiffBlock {
+    pub fn percentage(&self) -> f32 {
+        self.diff_count as f32 / self.len as f32 * 100.0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DiffRegionKind {
+    /// Printable ASCII in one file, non-ASCII in the other
+    TextChange,
+    /// The region runs past the end of one of the files
+    LengthMismatch,
+    /// The bytes only differ by having their nibbles swapped
+    NibbleSwap,
+}
+
+impl fmt::Display for DiffRegionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::TextChange => "text change",
+            Self::LengthMismatch => "length mismatch",
+            Self::NibbleSwap => "nibble swap",
+        })
+    }
+}
+
+/// A contiguous run of differing bytes
+#[derive(Clone, Copy, Debug)]
+pub struct DiffRegion {
+    pub start: usize,
+    pub end: usize,
+    pub kind: Option<DiffRegionKind>,
+}
+
+impl DiffRegion {
+    pub fn len(&self) -> usize {
+        self.end - self.start + 1
+    }
+}
+
+/// Whether two bytes are further apart than the threshold. A missing byte always differs.
+fn bytes_differ(a: Option<&u8>, b: Option<&u8>, threshold: u8) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.abs_diff(*b) > threshold,
+        (a, b) => a != b,
+    }
+}
+
+fn is_printable(byte: &u8) -> bool {
+    (32..=126).contains(byte)
+}
+
+/// Bytes treated as interchangeable separators when ignoring whitespace
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, 0x00 | 0x09 | 0x0A | 0x0D | 0x20)
+}
+
+/// Compares two files like `diff -w`, treating whitespace runs of any length as equal.
+/// Differences are marked at each file's own offsets, in that file's vector.
+fn mark_diffs_ignoring_whitespace(
+    base: &[u8],
+    other: &[u8],
+    base_diffs: &mut [bool],
+    other_diffs: &mut [bool],
+    threshold: u8,
+) {
+    let (mut i, mut j) = (0, 0);
+
+    while i < base.len() && j < other.len() {
+        if is_whitespace(base[i]) && is_whitespace(other[j]) {
+            while i < base.len() && is_whitespace(base[i]) {
+                i += 1;
+            }
+            while j < other.len() && is_whitespace(other[j]) {
+                j += 1;
+            }
+            continue;
+        }
+
+        if bytes_differ(Some(&base[i]), Some(&other[j]), threshold) {
+            base_diffs[i] = true;
+            other_diffs[j] = true;
+        }
+        i += 1;
+        j += 1;
+    }
+
+    base_diffs[i..base.len()].fill(true);
+    other_diffs[j..other.len()].fill(true);
+}
+
+/// Moves the reference view to the front, since the first view is the one diffed against
+fn reference_first(mut views: Vec<&HexView>, ref_id: Option<usize>) -> Vec<&HexView> {
+    if let Some(i) = views.iter().position(|hv| Some(hv.id) == ref_id) {
+        let reference = views.remove(i);
+        views.insert(0, reference);
+    }
+    views
+}
+
+/// Marks every byte of a word as different if any byte in it differs
+fn widen_to_words(diffs: &mut [bool], word_size: usize) {
+    for word in diffs.chunks_mut(word_size) {
+        if word.contains(&true) {
+            word.fill(true);
+        }
+    }
+}
+
+fn classify_region(start: usize, end: usize, files: &[&[u8]]) -> Option<DiffRegionKind> {
+    if files.iter().any(|data| data.len() <= end) {
+        return Some(DiffRegionKind::LengthMismatch);
+    }
+
+    let base = &files[0][start..=end];
+    let others: Vec<&[u8]> = files[1..].iter().map(|data| &data[start..=end]).collect();
+
+    let text_change = others.iter().all(|other| {
+        (base.iter().all(is_printable) && !other.iter().any(is_printable))
+            || (!base.iter().any(is_printable) && other.iter().all(is_printable))
+    });
+    if text_change {
+        return Some(DiffRegionKind::TextChange);
+    }
+
+    let nibble_swap = others.iter().all(|other| {
+        base.iter()
+            .zip(other.iter())
+            .all(|(a, b)| *a == b.rotate_left(4))
+    });
+    if nibble_swap {
+        return Some(DiffRegionKind::NibbleSwap);
+    }
+
+    None
+}
+
+/// Longest stretch between the common prefix and suffix of two files that is aligned.
+/// Aligning runs on the UI thread and takes up to this times `MAX_ALIGN_EDITS` steps.
+const MAX_ALIGN_LEN: usize = 64 * 1024;
+/// Most inserted and deleted bytes an alignment may have before giving up
+const MAX_ALIGN_EDITS: usize = 256;
+
+/// One step of the edit script that turns the base file into another file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffOp {
+    /// Bytes present in both files
+    Equal(usize),
+    /// Bytes only in the other file
+    Insert(usize),
+    /// Bytes only in the base file
+    Delete(usize),
+    /// Bytes of the base file replaced with bytes of the other file, with their lengths
+    Replace(usize, usize),
+}
+
+impl DiffOp {
+    /// Number of bytes the step covers in the base file and in the other file
+    fn lens(&self) -> (usize, usize) {
+        match *self {
+            Self::Equal(len) => (len, len),
+            Self::Insert(len) => (0, len),
+            Self::Delete(len) => (len, 0),
+            Self::Replace(base_len, len) => (base_len, len),
+        }
+    }
+}
+
+/// Aligns two byte sequences with Myers' algorithm. Returns None when they differ by more
+/// than `max_edits` inserted and deleted bytes.
+fn myers_diff(a: &[u8], b: &[u8], max_edits: usize) -> Option<Vec<DiffOp>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Edit {
+        Equal,
+        Insert,
+        Delete,
+    }
+
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max_d = max_edits.min(a.len() + b.len()) as isize;
+    let offset = max_d + 1;
+    // Furthest x reached on each diagonal k = x - y, indexed by k + offset
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // The diagonals -d..=d of v before each step d, indexed by k + d
+    let mut trace = Vec::new();
+
+    let mut found = None;
+    'search: for d in 0..=max_d {
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let i = (k + offset) as usize;
+            let mut x = match k == -d || (k != d && v[i - 1] < v[i + 1]) {
+                true => v[i + 1],
+                false => v[i - 1] + 1,
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                found = Some(d);
+                break 'search;
+            }
+        }
+    }
+
+    // Walk back from the end to recover the path, one edit per step
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (0..=found?).rev() {
+        let v = &trace[d as usize];
+        let k = x - y;
+        let prev_k = match k == -d || (k != d && v[(k - 1 + d) as usize] < v[(k + 1 + d) as usize])
+        {
+            true => k + 1,
+            false => k - 1,
+        };
+        let prev_x = match d {
+            0 => 0,
+            _ => v[(prev_k + d) as usize],
+        };
+        let prev_y = match d {
+            0 => 0,
+            _ => prev_x - prev_k,
+        };
+
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Equal);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            edits.push(match x == prev_x {
+                true => Edit::Insert,
+                false => Edit::Delete,
+            });
+        }
+        (x, y) = (prev_x, prev_y);
+    }
+    edits.reverse();
+
+    // Merge the edits into runs, pairing deletions and insertions between equal bytes
+    let mut ops = Vec::new();
+    let (mut deleted, mut inserted) = (0, 0);
+    for edits in edits.chunk_by(|a, b| (*a == Edit::Equal) == (*b == Edit::Equal)) {
+        if edits[0] == Edit::Equal {
+            ops.push(DiffOp::Equal(edits.len()));
+            continue;
+        }
+        for edit in edits {
+            match edit {
+                Edit::Delete => deleted += 1,
+                _ => inserted += 1,
+            }
+        }
+        ops.push(match (deleted, inserted) {
+            (_, 0) => DiffOp::Delete(deleted),
+            (0, _) => DiffOp::Insert(inserted),
+            _ => DiffOp::Replace(deleted, inserted),
+        });
+        (deleted, inserted) = (0, 0);
+    }
+    Some(ops)
+}
+
+/// Aligns the files so insertions and deletions don't shift every later byte out of place.
+/// The common prefix and suffix are skipped before aligning the rest.
+pub fn align(a: &[u8], b: &[u8]) -> Result<Vec<DiffOp>, Error> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    if a_mid.len().max(b_mid.len()) > MAX_ALIGN_LEN {
+        return Err(Error::msg(
+            "The differing part of the files is too large to align",
+        ));
+    }
+    let middle = myers_diff(a_mid, b_mid, MAX_ALIGN_EDITS).ok_or_else(|| {
+        Error::msg(format!(
+            "The files differ by more than {} inserted or deleted bytes",
+            MAX_ALIGN_EDITS
+        ))
+    })?;
+
+    let mut ops = Vec::new();
+    if prefix > 0 {
+        ops.push(DiffOp::Equal(prefix));
+    }
+    ops.extend(middle);
+    if suffix > 0 {
+        ops.push(DiffOp::Equal(suffix));
+    }
+    Ok(ops)
+}
+
+/// A run of rows in the aligned layout of two files, with where it starts in each file.
+/// A file's start is None where only the other file has bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct LayoutBlock {
+    base: Option<usize>,
+    other: Option<usize>,
+    len: usize,
+}
+
+/// The rows of two aligned files, so bytes only in one file face blank placeholder rows in
+/// the other and both views stay lined up. Rows are cut short where a block ends.
+#[derive(Debug)]
+pub struct AlignedLayout {
+    pub base_id: usize,
+    pub other_id: usize,
+    blocks: Vec<LayoutBlock>,
+    /// Row shown at the top of both views
+    pub top_row: usize,
+    /// Row width the layout is shown with, None while the views' widths differ
+    pub bytes_per_row: Option<usize>,
+    /// Where the two views were scrolled to when they were last lined up
+    positions: Option<(usize, usize)>,
+}
+
+impl AlignedLayout {
+    fn new(base_id: usize, other_id: usize, ops: &[DiffOp]) -> Self {
+        let mut blocks: Vec<LayoutBlock> = Vec::new();
+        let mut push = |base: Option<usize>, other: Option<usize>, len: usize| {
+            if len == 0 {
+                return;
+            }
+            // Bytes paired the same way as the previous block continue it
+            if let Some(last) = blocks.last_mut() {
+                if last.base.map(|start| start + last.len) == base
+                    && last.other.map(|start| start + last.len) == other
+                {
+                    last.len += len;
+                    return;
+                }
+            }
+            blocks.push(LayoutBlock { base, other, len });
+        };
+
+        let (mut base_pos, mut pos) = (0, 0);
+        for op in ops {
+            // Replaced bytes are paired up, and the longer side's rest faces placeholders
+            let (base_len, len) = op.lens();
+            let paired = base_len.min(len);
+            push(Some(base_pos), Some(pos), paired);
+            push(Some(base_pos + paired), None, base_len - paired);
+            push(None, Some(pos + paired), len - paired);
+            base_pos += base_len;
+            pos += len;
+        }
+
+        Self {
+            base_id,
+            other_id,
+            blocks,
+            top_row: 0,
+            bytes_per_row: None,
+            positions: None,
+        }
+    }
+
+    pub fn contains_view(&self, hv_id: usize) -> bool {
+        hv_id == self.base_id || hv_id == self.other_id
+    }
+
+    fn start(&self, block: &LayoutBlock, hv_id: usize) -> Option<usize> {
+        match hv_id == self.base_id {
+            true => block.base,
+            false => block.other,
+        }
+    }
+
+    /// Each block with the row it starts at
+    fn block_rows(&self, bytes_per_row: usize) -> impl Iterator<Item = (usize, &LayoutBlock)> {
+        self.blocks.iter().scan(0, move |row, block| {
+            let first_row = *row;
+            *row += block.len.div_ceil(bytes_per_row);
+            Some((first_row, block))
+        })
+    }
+
+    pub fn num_rows(&self, bytes_per_row: usize) -> usize {
+        self.blocks
+            .iter()
+            .map(|block| block.len.div_ceil(bytes_per_row))
+            .sum()
+    }
+
+    /// The row showing the byte at `pos` of the view, or the row past the end
+    pub fn row_at(&self, hv_id: usize, pos: usize, bytes_per_row: usize) -> usize {
+        for (first_row, block) in self.block_rows(bytes_per_row) {
+            if let Some(start) = self.start(block, hv_id) {
+                if pos < start + block.len {
+                    return first_row + pos.saturating_sub(start) / bytes_per_row;
+                }
+            }
+        }
+        self.num_rows(bytes_per_row)
+    }
+
+    /// The bytes of the view on the row, or None for a placeholder row or a row past the end
+    pub fn row_range(
+        &self,
+        hv_id: usize,
+        row: usize,
+        bytes_per_row: usize,
+    ) -> Option<Range<usize>> {
+        let (first_row, block) = self
+            .block_rows(bytes_per_row)
+            .find(|(first_row, block)| row < first_row + block.len.div_ceil(bytes_per_row))?;
+        let start = self.start(block, hv_id)? + (row - first_row) * bytes_per_row;
+        let end = (start + bytes_per_row).min(self.start(block, hv_id)? + block.len);
+        Some(start..end)
+    }
+
+    /// Moves the top row after either view was scrolled and scrolls both views to it.
+    /// Scrolling by up to a screen moves by rows, so placeholder rows can be scrolled through.
+    pub fn follow(&mut self, hex_views: &mut [HexView]) {
+        let base = hex_views.iter().position(|hv| hv.id == self.base_id);
+        let other = hex_views.iter().position(|hv| hv.id == self.other_id);
+        let (Some(base), Some(other)) = (base, other) else {
+            return;
+        };
+
+        let bytes_per_row = hex_views[base].bytes_per_row;
+        self.bytes_per_row = (hex_views[other].bytes_per_row == bytes_per_row
+            && hex_views[base].row_sort.is_none()
+            && hex_views[other].row_sort.is_none())
+        .then_some(bytes_per_row);
+        if self.bytes_per_row.is_none() {
+            self.positions = None;
+            return;
+        }
+
+        let positions = (hex_views[base].cur_pos, hex_views[other].cur_pos);
+        let scrolled = |hv_id: usize, old: usize, new: usize, num_rows: u32| {
+            let delta = new as isize - old as isize;
+            let screen = (num_rows as usize * bytes_per_row) as isize;
+            match delta % bytes_per_row as isize == 0 && delta.abs() <= screen {
+                true => self
+                    .top_row
+                    .saturating_add_signed(delta / bytes_per_row as isize),
+                false => self.row_at(hv_id, new, bytes_per_row),
+            }
+        };
+        self.top_row = match self.positions {
+            Some(old) if old == positions => return,
+            Some((old, _)) if old != positions.0 => {
+                scrolled(self.base_id, old, positions.0, hex_views[base].num_rows)
+            }
+            Some((_, old)) => scrolled(self.other_id, old, positions.1, hex_views[other].num_rows),
+            None => self.row_at(self.base_id, positions.0, bytes_per_row),
+        }
+        .min(self.num_rows(bytes_per_row).saturating_sub(1));
+
+        // Views on a placeholder row keep their offset until they have bytes on screen again
+        for i in [base, other] {
+            let hv = &mut hex_views[i];
+            if let Some(range) = self.row_range(hv.id, self.top_row, bytes_per_row) {
+                hv.set_cur_pos(range.start);
+            }
+        }
+        self.positions = Some((hex_views[base].cur_pos, hex_views[other].cur_pos));
+    }
+}
+
+/// Alignments of each file with the base file, for the diff mode that follows insertions
+/// and deletions instead of comparing bytes at the same offset
+#[derive(Debug, Default)]
+pub struct AlignedDiffState {
+    pub base_id: Option<usize>,
+    /// The edit script from the base file to each other file, by hex view id
+    pub pairs: Vec<(usize, Vec<DiffOp>)>,
+    /// Ranges of each file that aren't equal in the alignment, sorted by offset. The base file
+    /// only shows its alignment with the first other file.
+    marks: HashMap<usize, Vec<(Range<usize>, DiffOp)>>,
+    /// Why a file couldn't be aligned
+    pub error: Option<String>,
+    /// Rows of the two files with blank placeholder rows, when exactly two files are aligned
+    pub layout: Option<AlignedLayout>,
+}
+
+impl AlignedDiffState {
+    /// Aligns every view with the first one. Returns the edit script from the first view to
+    /// the second, which is empty when they couldn't be aligned.
+    pub fn recalculate(&mut self, hex_views: &[&HexView]) -> Vec<DiffOp> {
+        self.base_id = hex_views.first().map(|hv| hv.id);
+        self.pairs.clear();
+        self.marks.clear();
+        self.error = None;
+        self.layout = None;
+
+        let Some((base, others)) = hex_views.split_first() else {
+            return Vec::new();
+        };
+        for hv in others {
+            match align(&base.file.data, &hv.file.data) {
+                Ok(ops) => self.pairs.push((hv.id, ops)),
+                Err(e) => {
+                    self.error = Some(format!("{}: {}", hv.file_name(), e));
+                }
+            }
+        }
+
+        for (i, (id, ops)) in self.pairs.iter().enumerate() {
+            let (mut base_pos, mut pos) = (0, 0);
+            let mut base_marks = Vec::new();
+            let mut marks = Vec::new();
+            for op in ops {
+                let (base_len, len) = op.lens();
+                if !matches!(op, DiffOp::Equal(_)) {
+                    if base_len > 0 {
+                        base_marks.push((base_pos..base_pos + base_len, *op));
+                    }
+                    if len > 0 {
+                        marks.push((pos..pos + len, *op));
+                    }
+                }
+                base_pos += base_len;
+                pos += len;
+            }
+            self.marks.insert(*id, marks);
+            if i == 0 {
+                self.marks.insert(base.id, base_marks);
+            }
+        }
+
+        match (others.first(), self.pairs.first()) {
+            (Some(hv), Some((id, ops))) if *id == hv.id => ops.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The edit that the byte at `pos` of the view is part of, if it isn't equal
+    pub fn op_at(&self, hv_id: usize, pos: usize) -> Option<DiffOp> {
+        let marks = self.marks.get(&hv_id)?;
+        let i = marks.partition_point(|(range, _)| range.end <= pos);
+        marks
+            .get(i)
+            .filter(|(range, _)| range.contains(&pos))
+            .map(|(_, op)| *op)
+    }
+
+    /// The offset in the view that lines up with `base_pos` in the base file
+    pub fn aligned_offset(&self, hv_id: usize, base_pos: usize) -> Option<usize> {
+        let (_, ops) = self.pairs.iter().find(|(id, _)| *id == hv_id)?;
+        let (mut base_start, mut start) = (0, 0);
+        for op in ops {
+            let (base_len, len) = op.lens();
+            if base_pos < base_start + base_len {
+                return Some(start + (base_pos - base_start).min(len.saturating_sub(1)));
+            }
+            base_start += base_len;
+            start += len;
+        }
+        Some(start + (base_pos - base_start))
+    }
+}
 
 #[derive(Debug)]
 pub struct DiffState {
     pub enabled: bool,
     pub out_of_date: bool,
+    /// Treat runs of null and whitespace bytes as equal regardless of their length
+    pub ignore_whitespace: bool,
+    /// Render bytes that match across all files as dots
+    pub diff_bytes_only: bool,
+    /// Compare aligned groups of this many bytes (1, 2, 4 or 8) so that a single
+    /// differing byte marks its whole group
+    pub word_size: usize,
+    /// Bytes only count as different when they're further apart than this
+    pub diff_threshold: u8,
+    /// Id of the view every other view is compared against. The first view of a diff group is
+    /// used when the reference isn't in it, or isn't set.
+    pub diff_ref_id: Option<usize>,
+    /// Offsets that are never treated as different, such as build dates
+    pub ignore_ranges: Vec<RangeInclusive<usize>>,
+    /// Align the files to follow insertions and deletions, instead of comparing offsets
+    pub aligned_mode: bool,
+    /// The alignments while `aligned_mode` is on, used to color the views
+    pub aligned: Option<AlignedDiffState>,
     pub diffs: Vec<bool>,
+    /// Number of differing bytes per block, sorted by descending diff count
+    pub rollup: Vec<DiffBlock>,
+    pub regions: Vec<DiffRegion>,
+    /// Number of bytes in each file that differ from the base file, keyed by hex view id
+    pub file_diff_counts: HashMap<usize, usize>,
+    /// Id of the file every other file is compared against
+    pub base_id: Option<usize>,
+    /// Differences between the base file and each other file, in the order the files are open,
+    /// at the other file's offsets
+    pub pair_diffs: Vec<(usize, Vec<bool>)>,
+    /// The same differences at the base file's offsets. Only set when ignoring whitespace, as
+    /// the offsets are otherwise the same in both files.
+    base_pair_diffs: Vec<Vec<bool>>,
+    /// Incremented every time the diffs are recalculated, so views can tell their caches are stale
+    pub generation: usize,
+    /// Diffs of the views in each diff group. The fields above only cover the ungrouped views.
+    pub groups: HashMap<ViewGroup, DiffState>,
 }
 
 impl Default for DiffState {
@@ -12,36 +631,134 @@ impl Default for DiffState {
         Self {
             enabled: true,
             out_of_date: false,
+            ignore_whitespace: false,
+            diff_bytes_only: false,
+            word_size: 1,
+            diff_threshold: 0,
+            diff_ref_id: None,
+            ignore_ranges: Vec::new(),
+            aligned_mode: false,
+            aligned: None,
             diffs: Vec::new(),
+            rollup: Vec::new(),
+            regions: Vec::new(),
+            file_diff_counts: HashMap::new(),
+            base_id: None,
+            pair_diffs: Vec::new(),
+            base_pair_diffs: Vec::new(),
+            generation: 0,
+            groups: HashMap::new(),
         }
     }
 }
 
 impl DiffState {
-    pub fn is_diff_at(&self, index: usize) -> bool {
+    /// The differences at the given view's offsets. The base view sees those of every pair.
+    pub fn diffs_for(&self, hv_id: usize) -> &[bool] {
+        self.pair_diffs
+            .iter()
+            .find(|(id, _)| *id == hv_id)
+            .map_or(&self.diffs, |(_, diffs)| diffs)
+    }
+
+    /// The differences of a pair at the base file's offsets
+    fn base_side(&self, pair: usize) -> &[bool] {
+        self.base_pair_diffs
+            .get(pair)
+            .unwrap_or(&self.pair_diffs[pair].1)
+    }
+
+    pub fn is_diff_at(&self, hv_id: usize, index: usize) -> bool {
         if !self.enabled {
             return false;
         }
 
-        if index >= self.diffs.len() {
-            return false;
+        self.diffs_for(hv_id).get(index).copied().unwrap_or(false)
+    }
+
+    /// Whether the byte at the index should be drawn as a dot in diff bytes only mode
+    pub fn is_masked_at(&self, hv_id: usize, index: usize) -> bool {
+        self.enabled && self.diff_bytes_only && !self.is_diff_at(hv_id, index)
+    }
+
+    /// Which base/file pair the difference at the index belongs to, as seen from the given view.
+    /// The base view shows the first pair that differs there.
+    pub fn pair_index_at(&self, hv_id: usize, index: usize) -> Option<usize> {
+        if !self.is_diff_at(hv_id, index) {
+            return None;
         }
 
-        self.diffs[index]
+        let differs = |diffs: &[bool]| diffs.get(index).copied().unwrap_or(false);
+
+        match self.base_id == Some(hv_id) {
+            true => (0..self.pair_diffs.len()).find(|&pair| differs(self.base_side(pair))),
+            false => self
+                .pair_diffs
+                .iter()
+                .position(|(id, diffs)| *id == hv_id && differs(diffs)),
+        }
+    }
+
+    /// The first diff at or after `start`. With `wrap`, the search continues from the start of
+    /// the file, so a result before `start` means it wrapped.
+    pub fn get_next_diff(&self, start: usize, wrap: bool) -> Option<usize> {
+        if !self.enabled {
+            return None;
+        }
+
+        let find = |start: usize| {
+            self.diffs
+                .iter()
+                .skip(start)
+                .position(|diff| *diff)
+                .map(|i| start + i)
+        };
+
+        match find(start) {
+            None if wrap => find(0),
+            next => next,
+        }
     }
 
-    pub fn get_next_diff(&self, start: usize) -> Option<usize> {
+    /// The start of the last run of diffs that begins before `before`. With `wrap`, the search
+    /// continues from the end of the file, so a result at or after `before` means it wrapped.
+    pub fn get_prev_diff(&self, before: usize, wrap: bool) -> Option<usize> {
         if !self.enabled {
             return None;
         }
 
-        for (i, diff) in self.diffs.iter().enumerate().skip(start) {
-            if *diff {
-                return Some(i);
+        let find = |before: usize| {
+            let mut pos = self.diffs[..before.min(self.diffs.len())]
+                .iter()
+                .rposition(|diff| *diff)?;
+            while pos > 0 && self.diffs[pos - 1] {
+                pos -= 1;
             }
+            Some(pos)
+        };
+
+        match find(before) {
+            None if wrap => find(self.diffs.len()),
+            prev => prev,
+        }
+    }
+
+    /// The diffs the view is part of: those of its diff group, or the ungrouped ones
+    pub fn for_view(&self, hv: &HexView) -> &DiffState {
+        if !self.enabled {
+            return self;
         }
 
-        None
+        hv.diff_group
+            .and_then(|group| self.groups.get(&group))
+            .unwrap_or(self)
+    }
+
+    /// Copies the display options that don't need a recalculation to the groups
+    pub fn sync_groups(&mut self) {
+        for state in self.groups.values_mut() {
+            state.diff_bytes_only = self.diff_bytes_only;
+        }
     }
 
     pub fn recalculate(&mut self, hex_views: &[HexView]) {
@@ -59,16 +776,605 @@ impl DiffState {
             return;
         }
 
-        let max_size = hex_views.iter().map(|hv| hv.file.data.len()).max().unwrap();
+        self.generation += 1;
 
-        self.diffs = Vec::with_capacity(max_size);
+        let ref_id = self.diff_ref_id;
 
-        for i in 0..max_size {
-            let diff = !hex_views
+        let ungrouped = reference_first(
+            hex_views
                 .iter()
-                .all(|hv| i < hv.file.data.len() && hv.file.data[i] == hex_views[0].file.data[i]);
-            self.diffs.push(diff);
-        }
+                .filter(|hv| hv.diff_group.is_none())
+                .collect(),
+            ref_id,
+        );
+        self.calculate(&ungrouped);
+
+        self.groups = ViewGroup::ALL
+            .into_iter()
+            .filter_map(|group| {
+                let views = reference_first(
+                    hex_views
+                        .iter()
+                        .filter(|hv| hv.diff_group == Some(group))
+                        .collect(),
+                    ref_id,
+                );
+                if views.is_empty() {
+                    return None;
+                }
+
+                let mut state = DiffState {
+                    ignore_whitespace: self.ignore_whitespace,
+                    diff_bytes_only: self.diff_bytes_only,
+                    word_size: self.word_size,
+                    diff_threshold: self.diff_threshold,
+                    ignore_ranges: self.ignore_ranges.clone(),
+                    aligned_mode: self.aligned_mode,
+                    generation: self.generation,
+                    ..Default::default()
+                };
+                state.calculate(&views);
+                Some((group, state))
+            })
+            .collect();
+
         self.out_of_date = false;
     }
+
+    /// Diffs the views against the first one. With fewer than two views nothing differs.
+    fn calculate(&mut self, hex_views: &[&HexView]) {
+        let Some(max_size) = hex_views.iter().map(|hv| hv.file.data.len()).max() else {
+            self.diffs.clear();
+            self.rollup.clear();
+            self.regions.clear();
+            self.file_diff_counts.clear();
+            self.base_id = None;
+            self.pair_diffs.clear();
+            self.base_pair_diffs.clear();
+            self.aligned = None;
+            return;
+        };
+
+        self.aligned = self.aligned_mode.then(|| {
+            let mut aligned = AlignedDiffState::default();
+            let ops = aligned.recalculate(hex_views);
+            if let [base, other] = hex_views {
+                if !ops.is_empty() {
+                    aligned.layout = Some(AlignedLayout::new(base.id, other.id, &ops));
+                }
+            }
+            aligned
+        });
+
+        let base = &hex_views[0].file.data;
+        self.base_id = Some(hex_views[0].id);
+        let finish = |diffs: &mut Vec<bool>| {
+            if self.word_size > 1 {
+                widen_to_words(diffs, self.word_size);
+            }
+            // Clearing the ignored bytes here keeps them out of every count and region
+            for range in self.ignore_ranges.iter() {
+                let end = range.end().saturating_add(1).min(diffs.len());
+                if *range.start() < end {
+                    diffs[*range.start()..end].fill(false);
+                }
+            }
+        };
+        self.pair_diffs.clear();
+        self.base_pair_diffs.clear();
+        for hv in hex_views[1..].iter() {
+            let data = &hv.file.data;
+            let mut diffs = vec![false; base.len().max(data.len())];
+            match self.ignore_whitespace {
+                true => {
+                    let mut base_diffs = diffs.clone();
+                    mark_diffs_ignoring_whitespace(
+                        base,
+                        data,
+                        &mut base_diffs,
+                        &mut diffs,
+                        self.diff_threshold,
+                    );
+                    finish(&mut base_diffs);
+                    self.base_pair_diffs.push(base_diffs);
+                }
+                false => {
+                    for (i, diff) in diffs.iter_mut().enumerate() {
+                        *diff = bytes_differ(base.get(i), data.get(i), self.diff_threshold);
+                    }
+                }
+            }
+            finish(&mut diffs);
+            self.pair_diffs.push((hv.id, diffs));
+        }
+
+        // Only count the offsets that belong to each file
+        self.file_diff_counts = hex_views[1..]
+            .iter()
+            .zip(self.pair_diffs.iter())
+            .map(|(hv, (_, diffs))| {
+                let count = diffs[..hv.file.data.len()].iter().filter(|d| **d).count();
+                (hv.id, count)
+            })
+            .collect();
+
+        // The base file's view of every pair, which navigation and the regions go by
+        let mut diffs = vec![false; max_size];
+        for pair in 0..self.pair_diffs.len() {
+            for (diff, pair_diff) in diffs.iter_mut().zip(self.base_side(pair).iter()) {
+                *diff |= *pair_diff;
+            }
+        }
+        self.diffs = diffs;
+
+        self.rollup = self
+            .diffs
+            .chunks(ROLLUP_BLOCK_SIZE)
+            .enumerate()
+            .map(|(index, block)| DiffBlock {
+                index,
+                start: index * ROLLUP_BLOCK_SIZE,
+                len: block.len(),
+                diff_count: block.iter().filter(|d| **d).count(),
+            })
+            .collect();
+        self.rollup
+            .sort_by(|a, b| b.diff_count.cmp(&a.diff_count).then(a.index.cmp(&b.index)));
+
+        let files: Vec<&[u8]> = hex_views.iter().map(|hv| hv.file.data.as_slice()).collect();
+        self.regions.clear();
+        let mut region_start: Option<usize> = None;
+        for i in 0..=self.diffs.len() {
+            let diff = self.diffs.get(i).copied().unwrap_or(false);
+            match (diff, region_start) {
+                (true, None) => region_start = Some(i),
+                (false, Some(start)) => {
+                    self.regions.push(DiffRegion {
+                        start,
+                        end: i - 1,
+                        kind: classify_region(start, i - 1, &files),
+                    });
+                    region_start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The offset that reads as "EOF" in an IPS patch, so no record may start there
+const IPS_EOF_OFFSET: usize = 0x454F46;
+const IPS_MAX_RECORD_LEN: usize = 0xFFFF;
+/// Runs of identical bytes at least this long are stored as RLE records
+const IPS_MIN_RLE_LEN: usize = 9;
+/// Unchanged gaps shorter than a record header are cheaper to include in the record
+const IPS_MAX_GAP: usize = 5;
+
+fn write_ips_record(patch: &mut Vec<u8>, offset: usize, data: &[u8], rle: bool) {
+    patch.extend_from_slice(&(offset as u32).to_be_bytes()[1..]);
+    match rle {
+        true => {
+            patch.extend_from_slice(&[0, 0]);
+            patch.extend_from_slice(&(data.len() as u16).to_be_bytes());
+            patch.push(data[0]);
+        }
+        false => {
+            patch.extend_from_slice(&(data.len() as u16).to_be_bytes());
+            patch.extend_from_slice(data);
+        }
+    }
+}
+
+/// Writes literal bytes, split into records of the maximum length without
+/// starting one at the EOF offset
+fn write_ips_literal(patch: &mut Vec<u8>, mut offset: usize, mut data: &[u8]) {
+    while !data.is_empty() {
+        let mut len = data.len().min(IPS_MAX_RECORD_LEN);
+        if offset + len == IPS_EOF_OFFSET && len < data.len() {
+            len -= 1;
+        }
+        write_ips_record(patch, offset, &data[..len], false);
+        offset += len;
+        data = &data[len..];
+    }
+}
+
+/// Writes the records for a changed span, using RLE for long runs of the same byte
+fn write_ips_span(patch: &mut Vec<u8>, start: usize, span: &[u8]) {
+    let mut literal_start = 0;
+    let mut i = 0;
+
+    while i < span.len() {
+        let mut run = 1;
+        while i + run < span.len() && span[i + run] == span[i] && run < IPS_MAX_RECORD_LEN {
+            run += 1;
+        }
+        // A record can't start at the EOF offset, so a run ending right before it gives up
+        // its last byte to the literal that follows
+        if start + i + run == IPS_EOF_OFFSET {
+            run -= 1;
+        }
+
+        if run >= IPS_MIN_RLE_LEN && start + i != IPS_EOF_OFFSET {
+            write_ips_literal(patch, start + literal_start, &span[literal_start..i]);
+            write_ips_record(patch, start + i, &span[i..i + run], true);
+            literal_start = i + run;
+        }
+        i += run.max(1);
+    }
+
+    write_ips_literal(patch, start + literal_start, &span[literal_start..]);
+}
+
+/// Builds an IPS patch that turns `base` into `patched`
+fn ips_patch(base: &[u8], patched: &[u8]) -> Result<Vec<u8>, Error> {
+    if patched.len() > 0x1000000 || (patched.len() < base.len() && patched.len() > 0xFFFFFF) {
+        return Err(Error::msg(
+            "IPS patches can't address files larger than 16 MiB",
+        ));
+    }
+
+    let differs = |i: usize| base.get(i) != Some(&patched[i]);
+    let mut patch = b"PATCH".to_vec();
+
+    let mut i = 0;
+    while i < patched.len() {
+        if !differs(i) {
+            i += 1;
+            continue;
+        }
+
+        // Start one byte early rather than at the offset that reads as "EOF"
+        let start = match i == IPS_EOF_OFFSET {
+            true => i - 1,
+            false => i,
+        };
+
+        let mut end = i + 1;
+        loop {
+            while end < patched.len() && differs(end) {
+                end += 1;
+            }
+            match (end..patched.len().min(end + IPS_MAX_GAP)).find(|&j| differs(j)) {
+                Some(next) => end = next,
+                None => break,
+            }
+        }
+
+        write_ips_span(&mut patch, start, &patched[start..end]);
+        i = end;
+    }
+
+    patch.extend_from_slice(b"EOF");
+    if patched.len() < base.len() {
+        patch.extend_from_slice(&(patched.len() as u32).to_be_bytes()[1..]);
+    }
+    Ok(patch)
+}
+
+pub fn export_ips(path: &Path, base_data: &[u8], patched_data: &[u8]) -> Result<(), Error> {
+    std::fs::write(path, ips_patch(base_data, patched_data)?)?;
+    Ok(())
+}
+
+fn write_bps_number(patch: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let x = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            patch.push(0x80 | x);
+            break;
+        }
+        patch.push(x);
+        value -= 1;
+    }
+}
+
+/// Builds a BPS patch that turns `base` into `patched`, copying unchanged bytes from the source
+/// and storing everything else literally
+fn bps_patch(base: &[u8], patched: &[u8]) -> Vec<u8> {
+    const SOURCE_READ: u64 = 0;
+    const TARGET_READ: u64 = 1;
+
+    let mut patch = b"BPS1".to_vec();
+    write_bps_number(&mut patch, base.len() as u64);
+    write_bps_number(&mut patch, patched.len() as u64);
+    write_bps_number(&mut patch, 0);
+
+    let same = |i: usize| base.get(i) == Some(&patched[i]);
+    let mut i = 0;
+    while i < patched.len() {
+        let start = i;
+        let run_same = same(i);
+        while i < patched.len() && same(i) == run_same {
+            i += 1;
+        }
+
+        let len = (i - start) as u64;
+        match run_same {
+            true => write_bps_number(&mut patch, ((len - 1) << 2) | SOURCE_READ),
+            false => {
+                write_bps_number(&mut patch, ((len - 1) << 2) | TARGET_READ);
+                patch.extend_from_slice(&patched[start..i]);
+            }
+        }
+    }
+
+    patch.extend_from_slice(&crc32fast::hash(base).to_le_bytes());
+    patch.extend_from_slice(&crc32fast::hash(patched).to_le_bytes());
+    let patch_crc = crc32fast::hash(&patch);
+    patch.extend_from_slice(&patch_crc.to_le_bytes());
+    patch
+}
+
+pub fn export_bps(path: &Path, base_data: &[u8], patched_data: &[u8]) -> Result<(), Error> {
+    std::fs::write(path, bps_patch(base_data, patched_data))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whitespace_diffs_are_marked_at_each_files_offsets() {
+        let base = b"ab cd";
+        let other = b"ab   cZ!";
+        let mut base_diffs = vec![false; other.len()];
+        let mut other_diffs = vec![false; other.len()];
+        mark_diffs_ignoring_whitespace(base, other, &mut base_diffs, &mut other_diffs, 0);
+
+        let marked = |diffs: &[bool]| -> Vec<usize> {
+            diffs
+                .iter()
+                .enumerate()
+                .filter(|(_, d)| **d)
+                .map(|(i, _)| i)
+                .collect()
+        };
+        // "d" and "Z" differ, and "!" is only in the other file
+        assert_eq!(marked(&base_diffs), vec![4]);
+        assert_eq!(marked(&other_diffs), vec![6, 7]);
+    }
+
+    #[test]
+    fn align_finds_insertions() {
+        assert_eq!(
+            align(b"hello world", b"hello, world").unwrap(),
+            vec![DiffOp::Equal(5), DiffOp::Insert(1), DiffOp::Equal(6)]
+        );
+    }
+
+    #[test]
+    fn align_finds_deletions() {
+        assert_eq!(
+            align(b"hello, world", b"hello world").unwrap(),
+            vec![DiffOp::Equal(5), DiffOp::Delete(1), DiffOp::Equal(6)]
+        );
+    }
+
+    #[test]
+    fn align_pairs_deletions_and_insertions_as_replacements() {
+        assert_eq!(
+            align(b"abcXdef", b"abcYZdef").unwrap(),
+            vec![DiffOp::Equal(3), DiffOp::Replace(1, 2), DiffOp::Equal(3)]
+        );
+        assert_eq!(
+            myers_diff(b"axbxc", b"aybyc", 10).unwrap(),
+            vec![
+                DiffOp::Equal(1),
+                DiffOp::Replace(1, 1),
+                DiffOp::Equal(1),
+                DiffOp::Replace(1, 1),
+                DiffOp::Equal(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn align_handles_empty_files() {
+        assert_eq!(align(b"", b"").unwrap(), vec![]);
+        assert_eq!(align(b"", b"abc").unwrap(), vec![DiffOp::Insert(3)]);
+        assert_eq!(align(b"abc", b"").unwrap(), vec![DiffOp::Delete(3)]);
+        assert_eq!(myers_diff(b"", b"", 0).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn align_gives_up_past_the_edit_limit() {
+        assert_eq!(myers_diff(&[0; 10], &[1; 10], 19), None);
+        assert_eq!(
+            myers_diff(&[0; 10], &[1; 10], 20).unwrap(),
+            vec![DiffOp::Replace(10, 10)]
+        );
+        assert!(align(&[0; MAX_ALIGN_EDITS], &[1; MAX_ALIGN_EDITS]).is_err());
+    }
+
+    #[test]
+    fn layout_faces_deleted_bytes_with_placeholder_rows() {
+        let ops = [DiffOp::Equal(4), DiffOp::Delete(20), DiffOp::Equal(4)];
+        let layout = AlignedLayout::new(0, 1, &ops);
+
+        assert_eq!(layout.num_rows(16), 4);
+        let base_rows: Vec<_> = (0..4).map(|row| layout.row_range(0, row, 16)).collect();
+        assert_eq!(
+            base_rows,
+            vec![Some(0..4), Some(4..20), Some(20..24), Some(24..28)]
+        );
+        let other_rows: Vec<_> = (0..4).map(|row| layout.row_range(1, row, 16)).collect();
+        assert_eq!(other_rows, vec![Some(0..4), None, None, Some(4..8)]);
+
+        assert_eq!(layout.row_at(0, 22, 16), 2);
+        assert_eq!(layout.row_at(1, 5, 16), 3);
+        assert_eq!(layout.row_at(1, 8, 16), 4);
+    }
+
+    fn assert_ips_round_trip(base: &[u8], patched: &[u8]) {
+        let patch = ips_patch(base, patched).unwrap();
+        let mut data = base.to_vec();
+        crate::bin_file::apply_ips_patch(&mut data, &patch).unwrap();
+        assert!(
+            data == patched,
+            "IPS patch didn't reproduce the patched data"
+        );
+    }
+
+    #[test]
+    fn ips_patch_round_trips() {
+        let base: Vec<u8> = (0..=255).collect();
+
+        let mut patched = base.clone();
+        patched[3] = 0;
+        patched[10..14].copy_from_slice(b"abcd");
+        assert_ips_round_trip(&base, &patched);
+
+        // Long runs of one byte are written as RLE records
+        let mut patched = base.clone();
+        patched[20..60].fill(0xEE);
+        assert_ips_round_trip(&base, &patched);
+
+        // Growing and shrinking the file
+        let mut patched = base.clone();
+        patched.extend_from_slice(&[0; 40]);
+        assert_ips_round_trip(&base, &patched);
+        assert_ips_round_trip(&base, &base[..100]);
+        assert_ips_round_trip(&[], &base);
+    }
+
+    #[test]
+    fn ips_patch_avoids_the_eof_offset() {
+        let base = vec![0; IPS_EOF_OFFSET + 16];
+        let mut patched = base.clone();
+        patched[IPS_EOF_OFFSET] = 1;
+        assert_ips_round_trip(&base, &patched);
+
+        patched[IPS_EOF_OFFSET - 12..IPS_EOF_OFFSET + 12].fill(2);
+        assert_ips_round_trip(&base, &patched);
+    }
+
+    fn read_bps_number(patch: &[u8], pos: &mut usize) -> u64 {
+        let mut value = 0;
+        let mut shift = 1;
+        loop {
+            let x = patch[*pos];
+            *pos += 1;
+            value += (x & 0x7F) as u64 * shift;
+            if x & 0x80 != 0 {
+                return value;
+            }
+            shift <<= 7;
+            value += shift;
+        }
+    }
+
+    #[test]
+    fn bps_numbers_round_trip() {
+        for value in [0, 1, 0x7F, 0x80, 0x407F, 0x4080, 0xFFFF_FFFF, u64::MAX >> 8] {
+            let mut encoded = Vec::new();
+            write_bps_number(&mut encoded, value);
+            let mut pos = 0;
+            assert_eq!(read_bps_number(&encoded, &mut pos), value);
+            assert_eq!(pos, encoded.len());
+        }
+
+        let mut encoded = Vec::new();
+        write_bps_number(&mut encoded, 0x80);
+        assert_eq!(encoded, vec![0x00, 0x80]);
+    }
+
+    #[test]
+    fn bps_patch_has_header_actions_and_crcs() {
+        let base = b"abcdef";
+        let patched = b"abXYefgh";
+        let patch = bps_patch(base, patched);
+
+        assert_eq!(&patch[..4], b"BPS1");
+        let mut pos = 4;
+        assert_eq!(read_bps_number(&patch, &mut pos), base.len() as u64);
+        assert_eq!(read_bps_number(&patch, &mut pos), patched.len() as u64);
+        assert_eq!(read_bps_number(&patch, &mut pos), 0);
+
+        // Apply the actions to rebuild the target
+        let footer = patch.len() - 12;
+        let mut actions = Vec::new();
+        let mut target = Vec::new();
+        while pos < footer {
+            let data = read_bps_number(&patch, &mut pos);
+            let (kind, len) = (data & 3, (data >> 2) as usize + 1);
+            actions.push((kind, len));
+            match kind {
+                0 => target.extend_from_slice(&base[target.len()..target.len() + len]),
+                1 => {
+                    target.extend_from_slice(&patch[pos..pos + len]);
+                    pos += len;
+                }
+                _ => panic!("Unexpected action {}", kind),
+            }
+        }
+        assert_eq!(pos, footer);
+        assert_eq!(actions, vec![(0, 2), (1, 2), (0, 2), (1, 2)]);
+        assert_eq!(target, patched);
+
+        let crc = |i: usize| u32::from_le_bytes(patch[i..i + 4].try_into().unwrap());
+        assert_eq!(crc(footer), crc32fast::hash(base));
+        assert_eq!(crc(footer + 4), crc32fast::hash(patched));
+        assert_eq!(crc(footer + 8), crc32fast::hash(&patch[..footer + 8]));
+    }
+
+    fn state_with_diffs(diffs: &[u8]) -> DiffState {
+        DiffState {
+            enabled: true,
+            diffs: diffs.iter().map(|d| *d != 0).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn next_diff_finds_the_first_diff_at_or_after_start() {
+        let state = state_with_diffs(&[0, 1, 1, 0, 0, 1, 0]);
+        assert_eq!(state.get_next_diff(0, false), Some(1));
+        assert_eq!(state.get_next_diff(1, false), Some(1));
+        assert_eq!(state.get_next_diff(3, false), Some(5));
+        assert_eq!(state.get_next_diff(6, false), None);
+        assert_eq!(state.get_next_diff(100, false), None);
+    }
+
+    #[test]
+    fn next_diff_wraps_to_the_start() {
+        let state = state_with_diffs(&[0, 1, 1, 0, 0, 1, 0]);
+        assert_eq!(state.get_next_diff(6, true), Some(1));
+        assert_eq!(state.get_next_diff(100, true), Some(1));
+    }
+
+    #[test]
+    fn prev_diff_returns_the_start_of_the_run() {
+        let state = state_with_diffs(&[0, 1, 1, 1, 0, 1, 0]);
+        assert_eq!(state.get_prev_diff(7, false), Some(5));
+        assert_eq!(state.get_prev_diff(5, false), Some(1));
+        // Starting inside a run finds the start of that run
+        assert_eq!(state.get_prev_diff(3, false), Some(1));
+        assert_eq!(state.get_prev_diff(1, false), None);
+        assert_eq!(state.get_prev_diff(0, false), None);
+    }
+
+    #[test]
+    fn prev_diff_wraps_to_the_end() {
+        let state = state_with_diffs(&[0, 1, 1, 0, 1, 1, 1]);
+        assert_eq!(state.get_prev_diff(1, true), Some(4));
+        assert_eq!(state.get_prev_diff(0, true), Some(4));
+    }
+
+    #[test]
+    fn no_diffs_are_found_without_diffs() {
+        for state in [state_with_diffs(&[0, 0, 0]), state_with_diffs(&[])] {
+            for wrap in [false, true] {
+                assert_eq!(state.get_next_diff(0, wrap), None);
+                assert_eq!(state.get_prev_diff(3, wrap), None);
+            }
+        }
+
+        let mut disabled = state_with_diffs(&[1, 1]);
+        disabled.enabled = false;
+        assert_eq!(disabled.get_next_diff(0, true), None);
+        assert_eq!(disabled.get_prev_diff(2, true), None);
+    }
 }
@@ -0,0 +1,51 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{Context, Error};
+
+fn csv_ascii(row: &[u8]) -> String {
+    let ascii: String = row
+        .iter()
+        .map(|b| match b {
+            32..=126 => *b as char,
+            _ => '.',
+        })
+        .collect();
+
+    // Quote the field so commas and quotes in the data don't break the columns
+    format!("\"{}\"", ascii.replace('"', "\"\""))
+}
+
+/// Writes the data as CSV with one line per hex grid row: the offset, each byte and the row's ASCII
+pub fn write_csv(data: &[u8], bytes_per_row: usize, path: &Path) -> Result<(), Error> {
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create CSV file {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+
+    let byte_columns: Vec<String> = (0..bytes_per_row).map(|i| format!("byte_{}", i)).collect();
+    writeln!(writer, "offset,{},ascii", byte_columns.join(","))?;
+
+    for (i, row) in data.chunks(bytes_per_row).enumerate() {
+        // Pad the last row so every line has the same number of columns
+        let bytes: Vec<String> = (0..bytes_per_row)
+            .map(|j| match row.get(j) {
+                Some(b) => format!("{:02X}", b),
+                None => String::new(),
+            })
+            .collect();
+
+        writeln!(
+            writer,
+            "0x{:X},{},{}",
+            i * bytes_per_row,
+            bytes.join(","),
+            csv_ascii(row)
+        )?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
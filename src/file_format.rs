@@ -0,0 +1,143 @@
+/// A file format recognized from the magic number at the start of the file
+#[derive(Clone, Debug, PartialEq)]
+pub struct DetectedFormat {
+    /// Short name shown in the title bar
+    pub name: &'static str,
+    pub detail: String,
+}
+
+struct Magic {
+    name: &'static str,
+    description: &'static str,
+    bytes: &'static [u8],
+}
+
+const MAGICS: &[Magic] = &[
+    Magic {
+        name: "PNG",
+        description: "PNG image",
+        bytes: b"\x89PNG\r\n\x1a\n",
+    },
+    Magic {
+        name: "JPEG",
+        description: "JPEG image",
+        bytes: b"\xff\xd8\xff",
+    },
+    Magic {
+        name: "GIF",
+        description: "GIF image",
+        bytes: b"GIF8",
+    },
+    Magic {
+        name: "BMP",
+        description: "Windows bitmap",
+        bytes: b"BM",
+    },
+    Magic {
+        name: "ELF",
+        description: "ELF executable",
+        bytes: b"\x7fELF",
+    },
+    Magic {
+        name: "PE",
+        description: "DOS/Windows executable",
+        bytes: b"MZ",
+    },
+    Magic {
+        name: "Mach-O",
+        description: "Mach-O executable (32-bit)",
+        bytes: b"\xce\xfa\xed\xfe",
+    },
+    Magic {
+        name: "Mach-O",
+        description: "Mach-O executable (64-bit)",
+        bytes: b"\xcf\xfa\xed\xfe",
+    },
+    Magic {
+        name: "ZIP",
+        description: "ZIP archive",
+        bytes: b"PK\x03\x04",
+    },
+    Magic {
+        name: "gzip",
+        description: "gzip compressed data",
+        bytes: b"\x1f\x8b",
+    },
+    Magic {
+        name: "xz",
+        description: "xz compressed data",
+        bytes: b"\xfd7zXZ\x00",
+    },
+    Magic {
+        name: "7z",
+        description: "7-Zip archive",
+        bytes: b"7z\xbc\xaf\x27\x1c",
+    },
+    Magic {
+        name: "PDF",
+        description: "PDF document",
+        bytes: b"%PDF",
+    },
+    Magic {
+        name: "RIFF",
+        description: "RIFF container (WAV, AVI, WebP)",
+        bytes: b"RIFF",
+    },
+    Magic {
+        name: "Yaz0",
+        description: "Yaz0 compressed data",
+        bytes: b"Yaz0",
+    },
+    Magic {
+        name: "N64",
+        description: "Nintendo 64 ROM (big-endian)",
+        bytes: b"\x80\x37\x12\x40",
+    },
+    Magic {
+        name: "N64",
+        description: "Nintendo 64 ROM (byte-swapped)",
+        bytes: b"\x37\x80\x40\x12",
+    },
+    Magic {
+        name: "N64",
+        description: "Nintendo 64 ROM (little-endian)",
+        bytes: b"\x40\x12\x37\x80",
+    },
+];
+
+/// Extra information from the ELF header: word size and byte order
+fn elf_detail(data: &[u8]) -> Option<String> {
+    let class = match data.get(4)? {
+        1 => "32-bit",
+        2 => "64-bit",
+        _ => return None,
+    };
+    let endianness = match data.get(5)? {
+        1 => "little-endian",
+        2 => "big-endian",
+        _ => return None,
+    };
+    Some(format!("{}, {}", class, endianness))
+}
+
+/// Looks at the first 16 bytes for a known magic number
+pub fn detect(data: &[u8]) -> Option<DetectedFormat> {
+    let header = &data[..data.len().min(16)];
+
+    let magic = MAGICS
+        .iter()
+        .find(|magic| header.starts_with(magic.bytes))?;
+
+    let detail = match magic.name {
+        "ELF" => match elf_detail(header) {
+            Some(extra) => format!("{} ({})", magic.description, extra),
+            None => magic.description.to_owned(),
+        },
+        _ => magic.description.to_owned(),
+    };
+
+    Some(DetectedFormat {
+        name: magic.name,
+        detail,
+    })
+}
@@ -0,0 +1,249 @@
+use anyhow::Error;
+
+/// How deeply parentheses and negations may nest, so a long input can't overflow the stack
+const MAX_DEPTH: usize = 64;
+
+/// Values the goto expression can refer to by name
+pub struct GotoVariables {
+    pub filesize: usize,
+    pub cur_pos: usize,
+    pub selection_start: usize,
+    pub selection_end: usize,
+}
+
+impl GotoVariables {
+    fn get(&self, name: &str) -> Option<usize> {
+        match name {
+            "filesize" => Some(self.filesize),
+            "cur_pos" => Some(self.cur_pos),
+            "selection_start" => Some(self.selection_start),
+            "selection_end" => Some(self.selection_end),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(i128),
+    Op(char),
+    LParen,
+    RParen,
+}
+
+fn tokenize(expr: &str, vars: &GotoVariables) -> Result<Vec<Token>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '+' | '-' | '*' | '/' => {
+                tokens.push(Token::Op(c));
+                chars.next();
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                chars.next();
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                chars.next();
+            }
+            c if c.is_ascii_alphanumeric() || c == '_' => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+
+                let value = match word.chars().next() {
+                    Some('0'..='9') => parse_int::parse::<i128>(&word)
+                        .map_err(|_| Error::msg(format!("Invalid number \"{}\"", word)))?,
+                    _ => vars
+                        .get(&word)
+                        .ok_or_else(|| Error::msg(format!("Unknown variable \"{}\"", word)))?
+                        as i128,
+                };
+                tokens.push(Token::Number(value));
+            }
+            _ => return Err(Error::msg(format!("Unexpected character '{}'", c))),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let ret = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        ret
+    }
+
+    // expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<i128, Error> {
+        let mut value = self.term()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek().cloned() {
+            self.next();
+            let rhs = self.term()?;
+            value = match op {
+                '+' => value.checked_add(rhs),
+                _ => value.checked_sub(rhs),
+            }
+            .ok_or_else(overflow)?;
+        }
+        Ok(value)
+    }
+
+    // term := factor (('*' | '/') factor)*
+    fn term(&mut self) -> Result<i128, Error> {
+        let mut value = self.factor()?;
+        while let Some(Token::Op(op @ ('*' | '/'))) = self.peek().cloned() {
+            self.next();
+            let rhs = self.factor()?;
+            value = match op {
+                '*' => value.checked_mul(rhs).ok_or_else(overflow)?,
+                _ => value
+                    .checked_div(rhs)
+                    .ok_or_else(|| Error::msg("Division by zero"))?,
+            };
+        }
+        Ok(value)
+    }
+
+    // factor := number | '-' factor | '(' expr ')'
+    fn factor(&mut self) -> Result<i128, Error> {
+        if self.depth >= MAX_DEPTH {
+            return Err(Error::msg("Expression is nested too deeply"));
+        }
+        self.depth += 1;
+        let value = self.nested_factor();
+        self.depth -= 1;
+        value
+    }
+
+    fn nested_factor(&mut self) -> Result<i128, Error> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(value),
+            Some(Token::Op('-')) => self.factor()?.checked_neg().ok_or_else(overflow),
+            Some(Token::LParen) => {
+                let value = self.expr()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(value),
+                    _ => Err(Error::msg("Missing closing parenthesis")),
+                }
+            }
+            _ => Err(Error::msg("Expected a number or variable")),
+        }
+    }
+}
+
+fn overflow() -> Error {
+    Error::msg("Expression is too large")
+}
+
+/// Evaluates a simple arithmetic expression such as `filesize - 0x100` to an address
+pub fn evaluate(expr: &str, vars: &GotoVariables) -> Result<usize, Error> {
+    let mut parser = Parser {
+        tokens: tokenize(expr, vars)?,
+        pos: 0,
+        depth: 0,
+    };
+
+    let value = parser.expr()?;
+    if parser.peek().is_some() {
+        return Err(Error::msg("Unexpected trailing input"));
+    }
+
+    usize::try_from(value).map_err(|_| Error::msg("Address out of range"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VARS: GotoVariables = GotoVariables {
+        filesize: 0x1000,
+        cur_pos: 0x20,
+        selection_start: 0x30,
+        selection_end: 0x3F,
+    };
+
+    fn eval(expr: &str) -> Result<usize, String> {
+        evaluate(expr, &VARS).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn evaluate_follows_precedence_and_parentheses() {
+        assert_eq!(eval("1 + 2 * 3"), Ok(7));
+        assert_eq!(eval("(1 + 2) * 3"), Ok(9));
+        assert_eq!(eval("10 - 4 - 3"), Ok(3));
+        assert_eq!(eval("100 / 10 / 5"), Ok(2));
+        assert_eq!(eval("-2 * -3"), Ok(6));
+        assert_eq!(eval("0x10 + 0b11"), Ok(0x13));
+    }
+
+    #[test]
+    fn evaluate_resolves_variables() {
+        assert_eq!(eval("filesize - 0x100"), Ok(0xF00));
+        assert_eq!(eval("cur_pos + 4"), Ok(0x24));
+        assert_eq!(eval("selection_end - selection_start + 1"), Ok(0x10));
+        assert_eq!(eval("size"), Err("Unknown variable \"size\"".to_owned()));
+    }
+
+    #[test]
+    fn evaluate_rejects_overflow_and_out_of_range_results() {
+        let max = i128::MAX.to_string();
+        assert_eq!(
+            eval(&format!("{} + 1", max)),
+            Err("Expression is too large".to_owned())
+        );
+        assert_eq!(
+            eval(&format!("{} * 2", max)),
+            Err("Expression is too large".to_owned())
+        );
+        assert_eq!(eval("1 - 2"), Err("Address out of range".to_owned()));
+        assert_eq!(eval("1 / 0"), Err("Division by zero".to_owned()));
+    }
+
+    #[test]
+    fn evaluate_rejects_malformed_input() {
+        assert!(eval("").is_err());
+        assert!(eval("1 +").is_err());
+        assert!(eval("(1 + 2").is_err());
+        assert!(eval("1 + 2)").is_err());
+        assert!(eval("1 2").is_err());
+        assert!(eval("1 % 2").is_err());
+        assert!(eval("0xZZ").is_err());
+    }
+
+    #[test]
+    fn evaluate_limits_nesting_depth() {
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(eval(&nested(MAX_DEPTH - 1)), Ok(1));
+        assert_eq!(
+            eval(&nested(100_000)),
+            Err("Expression is nested too deeply".to_owned())
+        );
+        assert_eq!(
+            eval(&"-".repeat(100_000)),
+            Err("Expression is nested too deeply".to_owned())
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use std::{
+    io::Read,
+    net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Context, Error};
+use eframe::egui;
+
+use crate::search::parse_hex_pattern;
+
+/// Largest dump accepted from one connection
+const MAX_DUMP_SIZE: u64 = 64 * 1024 * 1024;
+/// How long a connection may stay silent before it's dropped
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Data received from one connection
+pub struct ReceivedDump {
+    pub peer: SocketAddr,
+    pub data: Vec<u8>,
+}
+
+impl ReceivedDump {
+    /// Name for the view the data is opened in
+    pub fn file_name(&self) -> String {
+        format!("tcp-{}-{}.bin", self.peer.ip(), self.peer.port())
+    }
+}
+
+/// Text made only of hex digits and whitespace is decoded, anything else is kept as raw bytes
+fn decode_dump(data: Vec<u8>) -> Vec<u8> {
+    match std::str::from_utf8(&data).map(parse_hex_pattern) {
+        Ok(Ok(bytes)) => bytes,
+        _ => data,
+    }
+}
+
+/// Accepts connections on a background thread until dropped
+struct HexListener {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    received: Receiver<Result<ReceivedDump, Error>>,
+}
+
+impl HexListener {
+    fn start(ip: IpAddr, port: u16, ctx: egui::Context) -> Result<Self, Error> {
+        let listener = TcpListener::bind((ip, port))
+            .with_context(|| format!("Failed to listen on {}:{}", ip, port))?;
+        // Non-blocking so the thread notices when it should stop
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, received) = mpsc::channel();
+
+        let thread_stop = stop.clone();
+        thread::spawn(move || {
+            while !thread_stop.load(Ordering::Relaxed) {
+                let (mut stream, peer) = match listener.accept() {
+                    Ok(connection) => connection,
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        thread::sleep(Duration::from_millis(100));
+                        continue;
+                    }
+                    Err(e) => {
+                        let _ = sender.send(Err(e.into()));
+                        continue;
+                    }
+                };
+
+                let sender = sender.clone();
+                let ctx = ctx.clone();
+                thread::spawn(move || {
+                    let mut data = Vec::new();
+                    let result = stream
+                        .set_nonblocking(false)
+                        .and_then(|_| stream.set_read_timeout(Some(READ_TIMEOUT)))
+                        // One byte past the limit tells a full dump from one that's too large
+                        .and_then(|_| (&mut stream).take(MAX_DUMP_SIZE + 1).read_to_end(&mut data))
+                        .map_err(Error::from)
+                        .and_then(|len| match len as u64 > MAX_DUMP_SIZE {
+                            true => Err(Error::msg(format!(
+                                "More than {} bytes were sent",
+                                MAX_DUMP_SIZE
+                            ))),
+                            false => Ok(ReceivedDump {
+                                peer,
+                                data: decode_dump(data),
+                            }),
+                        })
+                        .with_context(|| format!("Failed to receive data from {}", peer));
+                    let _ = sender.send(result);
+                    ctx.request_repaint();
+                });
+            }
+        });
+
+        Ok(Self {
+            addr,
+            stop,
+            received,
+        })
+    }
+}
+
+impl Drop for HexListener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[derive(Default)]
+pub struct HexReceiverDialog {
+    pub show: bool,
+    /// Address to listen on, the loopback address when empty so only this machine can send
+    address: String,
+    port: String,
+    listener: Option<HexListener>,
+    status: String,
+}
+
+impl HexReceiverDialog {
+    /// Returns the dumps received since the last frame
+    pub fn display(&mut self, ctx: &egui::Context) -> Vec<ReceivedDump> {
+        let mut received = Vec::new();
+        if let Some(listener) = &self.listener {
+            for result in listener.received.try_iter() {
+                match result {
+                    Ok(dump) => {
+                        self.status =
+                            format!("Received {} bytes from {}", dump.data.len(), dump.peer);
+                        received.push(dump);
+                    }
+                    Err(e) => self.status = e.to_string(),
+                }
+            }
+        }
+
+        let mut open = self.show;
+        egui::Window::new("Listen for hex dump")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label("Raw bytes or hex text sent to this port are opened as a new file");
+
+                match self.listener.as_ref().map(|listener| listener.addr) {
+                    Some(addr) => {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("Listening on {}", addr));
+                            if ui.button("Stop").clicked() {
+                                self.listener = None;
+                                self.status.clear();
+                            }
+                        });
+                    }
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.label("Address");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.address)
+                                    .desired_width(100.0)
+                                    .hint_text("127.0.0.1"),
+                            )
+                            .on_hover_text("Use 0.0.0.0 to accept dumps from other machines");
+                            ui.label("Port");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.port)
+                                    .desired_width(60.0)
+                                    .hint_text("9999"),
+                            );
+                            if ui.button("Listen").clicked() {
+                                let ip = match self.address.trim() {
+                                    "" => Ok(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+                                    address => address
+                                        .parse::<IpAddr>()
+                                        .map_err(|_| Error::msg("Invalid address")),
+                                };
+                                let port = self
+                                    .port
+                                    .trim()
+                                    .parse::<u16>()
+                                    .map_err(|_| Error::msg("Invalid port"));
+                                let result =
+                                    ip.and_then(|ip| HexListener::start(ip, port?, ctx.clone()));
+                                match result {
+                                    Ok(listener) => {
+                                        self.listener = Some(listener);
+                                        self.status.clear();
+                                    }
+                                    Err(e) => self.status = format!("{:#}", e),
+                                }
+                            }
+                        });
+                    }
+                }
+
+                ui.label(&self.status);
+            });
+        self.show = open;
+
+        received
+    }
+}
@@ -1,20 +1,44 @@
-use anyhow::Error;
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+    ops::{Range, RangeInclusive},
+    str::FromStr,
+    sync::{atomic::Ordering, Arc},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Error};
+use base64::{prelude::BASE64_STANDARD, Engine};
 use eframe::{
     egui::{self, Id, Sense, Separator},
-    epaint::Color32,
+    epaint::{Color32, Rect, Stroke},
 };
 
 use crate::{
     app::CursorState,
     bin_file::BinFile,
-    bin_file::{read_file_bytes, Endianness},
+    bin_file::{apply_ips_patch, read_file_bytes, Endianness},
+    bookmarks::{Bookmark, BookmarkAction, BookmarksPanel},
+    byte_colors::{density_level, gradient, max_entropy, zero_distances, ByteColoring, EntropyMap},
+    comments::{read_comments, write_comments, Comments, MAX_COMMENT_LEN},
     config::Config,
     data_viewer::DataViewer,
-    diff_state::DiffState,
-    map_tool::MapTool,
-    settings::{Settings, ThemeSettings},
+    diff_state::{DiffOp, DiffState},
+    file_format,
+    map_tool::{MapTool, NewSymbol},
+    row_zoom::RowZoom,
+    search::SearchMatches,
+    settings::{ByteGrouping, CopyFormat, Settings, ThemeSettings, ViewPreset},
     string_viewer::StringViewer,
-    widget::spacer::Spacer,
+    template::Template,
+    watch::{log_watches, Watch, WatchEvent},
+    widget::{
+        badge::Badge,
+        fingerprint::{compute_fingerprint, Fingerprint, FINGERPRINT_SIZE},
+        scrollbar_overlay::ScrollbarOverlay,
+        spacer::Spacer,
+        sparkline::Sparkline,
+    },
 };
 
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -23,6 +47,34 @@ pub struct HexViewSelectionRange {
     pub second: usize,
 }
 
+impl FromStr for HexViewSelectionRange {
+    type Err = Error;
+
+    /// Parses a pair of hex addresses such as `0x100 0x1FF` or `100-1FF`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let addresses = s
+            .split(|c: char| c.is_whitespace() || c == '-' || c == ',')
+            .filter(|token| !token.is_empty())
+            .map(|token| {
+                let digits = token
+                    .strip_prefix("0x")
+                    .or_else(|| token.strip_prefix("0X"))
+                    .unwrap_or(token);
+                usize::from_str_radix(digits, 16)
+                    .map_err(|_| Error::msg(format!("Invalid address \"{}\"", token)))
+            })
+            .collect::<Result<Vec<usize>, Error>>()?;
+
+        match addresses[..] {
+            [first, second] => Ok(Self {
+                first: first.min(second),
+                second: first.max(second),
+            }),
+            _ => Err(Error::msg("Expected a start and an end address")),
+        }
+    }
+}
+
 #[derive(Clone, Default, Debug, PartialEq)]
 pub enum HexViewSelectionState {
     #[default]
@@ -38,11 +90,23 @@ pub enum HexViewSelectionSide {
     Ascii,
 }
 
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub enum HexViewSelectionMode {
+    /// Every byte from the start to the end
+    #[default]
+    Linear,
+    /// The columns between the start and end in each row between them, started with Alt+drag
+    Block,
+}
+
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct HexViewSelection {
     pub range: HexViewSelectionRange,
     pub state: HexViewSelectionState,
     pub side: HexViewSelectionSide,
+    pub mode: HexViewSelectionMode,
+    /// First and last bit selected in the bit view, only used while a single byte is selected
+    pub bits: Option<(u8, u8)>,
 }
 
 impl HexViewSelection {
@@ -54,10 +118,67 @@ impl HexViewSelection {
         self.range.second.max(self.range.first)
     }
 
-    fn contains(&self, grid_pos: usize) -> bool {
-        self.state != HexViewSelectionState::None
-            && grid_pos >= self.start()
-            && grid_pos <= self.end()
+    /// The selected bits as (high, low), if exactly one byte is selected in the bit view
+    pub fn bit_range(&self) -> Option<(u8, u8)> {
+        match self.bits {
+            Some((first, second)) if self.start() == self.end() => {
+                Some((first.max(second), first.min(second)))
+            }
+            _ => None,
+        }
+    }
+
+    /// The first and last column of a block selection
+    fn block_columns(&self, bytes_per_row: usize) -> (usize, usize) {
+        let first = self.range.first % bytes_per_row;
+        let second = self.range.second % bytes_per_row;
+        (first.min(second), first.max(second))
+    }
+
+    fn contains(&self, grid_pos: usize, bytes_per_row: usize) -> bool {
+        if self.state == HexViewSelectionState::None {
+            return false;
+        }
+
+        match self.mode {
+            HexViewSelectionMode::Linear => (self.start()..=self.end()).contains(&grid_pos),
+            HexViewSelectionMode::Block => {
+                let (start_col, end_col) = self.block_columns(bytes_per_row);
+                let rows = self.start() / bytes_per_row..=self.end() / bytes_per_row;
+                rows.contains(&(grid_pos / bytes_per_row))
+                    && (start_col..=end_col).contains(&(grid_pos % bytes_per_row))
+            }
+        }
+    }
+
+    /// Number of selected bytes, counting only the block's columns in a block selection
+    pub fn num_bytes(&self, bytes_per_row: usize) -> usize {
+        match self.state {
+            HexViewSelectionState::None => 0,
+            _ => self
+                .ranges(bytes_per_row)
+                .iter()
+                .map(|range| range.end() - range.start() + 1)
+                .sum(),
+        }
+    }
+
+    /// The selected offsets as one range per row of a block selection, or a single range
+    pub fn ranges(&self, bytes_per_row: usize) -> Vec<RangeInclusive<usize>> {
+        match self.mode {
+            HexViewSelectionMode::Linear => vec![self.start()..=self.end()],
+            HexViewSelectionMode::Block => {
+                let (start_col, end_col) = self.block_columns(bytes_per_row);
+                let start_row = self.start() / bytes_per_row;
+                let end_row = self.end() / bytes_per_row;
+                (start_row..=end_row)
+                    .map(|row| {
+                        let row_start = row * bytes_per_row;
+                        row_start + start_col..=row_start + end_col
+                    })
+                    .collect()
+            }
+        }
     }
 
     pub fn begin(&mut self, grid_pos: usize, side: HexViewSelectionSide) {
@@ -65,6 +186,8 @@ impl HexViewSelection {
         self.range.second = grid_pos;
         self.state = HexViewSelectionState::Selecting;
         self.side = side;
+        self.mode = HexViewSelectionMode::Linear;
+        self.bits = None;
     }
 
     pub fn update(&mut self, grid_pos: usize) {
@@ -81,6 +204,8 @@ impl HexViewSelection {
         self.range.second = 0;
         self.state = HexViewSelectionState::None;
         self.side = HexViewSelectionSide::default();
+        self.mode = HexViewSelectionMode::default();
+        self.bits = None;
     }
 
     pub fn adjust_cur_pos(&mut self, delta: isize) {
@@ -89,6 +214,233 @@ impl HexViewSelection {
     }
 }
 
+/// Another open hex view, used to offer cross-view actions
+#[derive(Clone)]
+pub struct OtherView {
+    pub id: usize,
+    pub name: String,
+    /// The start of the view's selection, only read while a data viewer shows the XOR rows
+    pub selected_bytes: Vec<u8>,
+    pub selection_len: usize,
+    pub data_len: usize,
+}
+
+/// Actions a hex view requests from the app, which applies them after the view is shown
+pub enum HexViewAction {
+    /// Scroll the view with the given id to the offset
+    FollowOffset { target: usize, offset: usize },
+    /// The view's data was modified, so the diff needs recalculating
+    DataChanged,
+    /// The view moved to another diff group, so the diff needs recalculating
+    DiffGroupChanged,
+    /// Scroll every view to the row containing the offset
+    GoToOffset(usize),
+    /// Select the range in the view with the given id and scroll to it
+    SelectRange {
+        target: usize,
+        start: usize,
+        end: usize,
+    },
+}
+
+pub fn hex_view_combo(
+    ui: &mut egui::Ui,
+    label: &str,
+    selected: &mut Option<usize>,
+    hex_views: &[HexView],
+) {
+    let selected_text = selected
+        .and_then(|id| hex_views.iter().find(|hv| hv.id == id))
+        .map(HexView::file_name)
+        .unwrap_or_default();
+
+    egui::ComboBox::from_label(label)
+        .selected_text(selected_text)
+        .show_ui(ui, |ui| {
+            for hv in hex_views.iter() {
+                ui.selectable_value(selected, Some(hv.id), hv.file_name());
+            }
+        });
+}
+
+pub struct SessionStats {
+    pub opened_at: Instant,
+    pub bytes_scrolled: usize,
+    pub edits: usize,
+    pub reloads: usize,
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self {
+            opened_at: Instant::now(),
+            bytes_scrolled: 0,
+            edits: 0,
+            reloads: 0,
+        }
+    }
+}
+
+/// Number of reloads kept in the reload timeline
+const TIMELINE_SAMPLES: usize = 60;
+/// How long the notice is shown after the file is reloaded
+const RELOAD_NOTICE_DURATION: Duration = Duration::from_secs(2);
+
+/// How far the file has drifted from its first load over the reloads
+#[derive(Default)]
+pub struct ReloadTimeline {
+    /// The data as first loaded, kept from the first reload on
+    initial: Option<Vec<u8>>,
+    /// When each reload happened and how many bytes differed from the initial data
+    pub samples: VecDeque<(Instant, usize)>,
+}
+
+impl ReloadTimeline {
+    fn record(&mut self, previous: Vec<u8>, data: &[u8]) {
+        let initial = self.initial.get_or_insert(previous);
+        let diff_count = initial.iter().zip(data).filter(|(a, b)| a != b).count()
+            + initial.len().abs_diff(data.len());
+
+        if self.samples.len() == TIMELINE_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((Instant::now(), diff_count));
+    }
+}
+
+/// A byte being overwritten from the keyboard in the hex area
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HexEdit {
+    pub pos: usize,
+    /// The first digit typed, waiting for the second one
+    pub high_nibble: Option<u8>,
+}
+
+/// A view's state from before an IPS patch was applied in memory
+pub struct PrePatch {
+    data: Vec<u8>,
+    unsaved: bool,
+    dirty_bytes: HashSet<usize>,
+}
+
+/// Offsets in `new` whose byte isn't the same in `old`
+fn changed_offsets<'a>(old: &'a [u8], new: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+    new.iter()
+        .enumerate()
+        .filter(|(i, b)| old.get(*i) != Some(b))
+        .map(|(i, _)| i)
+}
+
+/// Display rows ordered by the byte in one column instead of by offset
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RowSort {
+    pub column: usize,
+    pub descending: bool,
+}
+
+/// A lettered set of views. Views in the same scroll group move together, and views in the
+/// same diff group are only compared with each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ViewGroup {
+    A,
+    B,
+    C,
+}
+
+impl ViewGroup {
+    pub const ALL: [ViewGroup; 3] = [Self::A, Self::B, Self::C];
+
+    pub fn color(&self) -> Color32 {
+        match self {
+            Self::A => Color32::from_rgb(230, 120, 40),
+            Self::B => Color32::from_rgb(60, 160, 230),
+            Self::C => Color32::from_rgb(180, 90, 220),
+        }
+    }
+}
+
+impl fmt::Display for ViewGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::A => "A",
+            Self::B => "B",
+            Self::C => "C",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FloatViewKind {
+    F32,
+    F64,
+}
+
+impl FloatViewKind {
+    fn size(&self) -> usize {
+        match self {
+            Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+
+    fn format(&self, bytes: &[u8], endianness: Endianness) -> String {
+        let mut buffer = dtoa::Buffer::new();
+        match self {
+            Self::F32 => {
+                let bytes: [u8; 4] = bytes.try_into().unwrap_or_default();
+                buffer
+                    .format(match endianness {
+                        Endianness::Little => f32::from_le_bytes(bytes),
+                        Endianness::Big => f32::from_be_bytes(bytes),
+                    })
+                    .to_owned()
+            }
+            Self::F64 => {
+                let bytes: [u8; 8] = bytes.try_into().unwrap_or_default();
+                buffer
+                    .format(match endianness {
+                        Endianness::Little => f64::from_le_bytes(bytes),
+                        Endianness::Big => f64::from_be_bytes(bytes),
+                    })
+                    .to_owned()
+            }
+        }
+    }
+}
+
+/// Width of the longest value on screen, so all float columns line up
+fn float_column_width(bytes: &[u8], kind: FloatViewKind, endianness: Endianness) -> usize {
+    bytes
+        .chunks_exact(kind.size())
+        .map(|chunk| kind.format(chunk, endianness).len())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Horizontal gaps between the bytes, byte groups and sections of the hex grid
+struct GridSpacing {
+    byte: f32,
+    group: f32,
+    section: f32,
+}
+
+impl GridSpacing {
+    fn new(compact: bool) -> Self {
+        match compact {
+            true => Self {
+                byte: 1.0,
+                group: 2.0,
+                section: 4.0,
+            },
+            false => Self {
+                byte: 4.0,
+                group: 4.0,
+                section: 8.0,
+            },
+        }
+    }
+}
+
 pub struct HexView {
     pub id: usize,
     pub file: BinFile,
@@ -96,14 +448,80 @@ pub struct HexView {
     pub bytes_per_row: usize,
     pub cur_pos: usize,
     pub pos_locked: bool,
+    /// Views without a group scroll together like before groups existed
+    pub scroll_group: Option<ViewGroup>,
+    /// Views in a diff group are only diffed against the rest of the group
+    pub diff_group: Option<ViewGroup>,
     pub selection: HexViewSelection,
     pub cursor_pos: Option<usize>,
+    /// Offset hovered in another view, highlighted in this one
+    pub hover_highlight: Option<usize>,
+    pub search_matches: SearchMatches,
     pub show_selection_info: bool,
     pub show_cursor_info: bool,
+    pub detected_byte_grouping: ByteGrouping,
+    pub virtual_base: Option<usize>,
+    virtual_base_input: String,
+    virtual_base_error: Option<String>,
+    /// Row width set for this view only, instead of the one from the settings
+    pub bytes_per_row_override: Option<usize>,
+    /// Show the hex column as floats instead of bytes
+    pub float_view: Option<FloatViewKind>,
+    /// Show each byte as its 8 bits, most significant first, so single bits can be selected
+    pub bit_view: bool,
+    pub byte_coloring: ByteColoring,
+    entropy: EntropyMap,
+    /// Distance from each byte to the nearest null byte, computed when density coloring is first used
+    zero_distances: Option<Arc<Vec<u16>>>,
+    pub row_sort: Option<RowSort>,
+    /// Row indices in display order while `row_sort` is set
+    sorted_rows: Vec<usize>,
+    fingerprint: [u8; FINGERPRINT_SIZE],
+    /// Incremented whenever the data changes, so results computed from it can tell they're stale
+    pub data_generation: usize,
     sv: StringViewer,
     dv: DataViewer,
     pub mt: MapTool,
+    pub bookmarks: Vec<Bookmark>,
+    /// Notes on single bytes, saved next to the file
+    pub comments: Comments,
+    /// The offset and text of the comment being written in the context menu
+    comment_input: Option<(usize, String)>,
+    /// Offsets whose values are logged on every reload
+    pub watches: Vec<Watch>,
+    /// The state before an IPS patch was applied in memory
+    pub pre_patch: Option<PrePatch>,
+    pub watch_log: VecDeque<WatchEvent>,
+    /// When the file was last reloaded from disk, cleared once the notice is dismissed
+    pub last_reload_time: Option<Instant>,
+    /// Whether changes on disk are reloaded. Turned off, they're kept pending until it's back on.
+    pub auto_reload: bool,
+    bp: BookmarksPanel,
+    /// Set from the context menu, handled with the bookmark panel's actions
+    pending_bookmark_action: Option<BookmarkAction>,
+    /// Row opened in a large panel by double-clicking its offset
+    row_zoom: Option<RowZoom>,
+    /// Text template the data is checked against
+    pub template: Option<Template>,
     pub closed: bool,
+    /// Rect to move the window to on the next frame, set when tiling views
+    pub tile_rect: Option<Rect>,
+    pub stats: SessionStats,
+    pub timeline: ReloadTimeline,
+    scrollbar_ticks: Vec<bool>,
+    /// Height, diff generation and diff enabled state the ticks were computed for
+    scrollbar_ticks_key: Option<(usize, usize, bool)>,
+    pub editing: Option<HexEdit>,
+    /// Offsets overwritten by hex editing since the file was loaded or saved
+    pub dirty_bytes: HashSet<usize>,
+    /// Offsets and previous values of the bytes changed by each edit, most recent last
+    pub edit_history: Vec<Vec<(usize, u8)>>,
+    redo_history: Vec<Vec<(usize, u8)>>,
+    /// The data differs from the file on disk
+    pub unsaved: bool,
+    /// The data isn't backed by a file on disk, so it can't be saved and has no comments file
+    pub virtual_file: bool,
+    pub actions: Vec<HexViewAction>,
 }
 
 impl Default for HexView {
@@ -115,41 +533,112 @@ impl Default for HexView {
             bytes_per_row: 0,
             cur_pos: 0,
             pos_locked: false,
+            scroll_group: None,
+            diff_group: None,
             selection: HexViewSelection::default(),
             cursor_pos: None,
+            hover_highlight: None,
+            search_matches: SearchMatches::default(),
             show_selection_info: true,
             show_cursor_info: true,
+            detected_byte_grouping: ByteGrouping::default(),
+            virtual_base: None,
+            virtual_base_input: String::new(),
+            virtual_base_error: None,
+            bytes_per_row_override: None,
+            float_view: None,
+            bit_view: false,
+            byte_coloring: ByteColoring::default(),
+            entropy: EntropyMap::default(),
+            zero_distances: None,
+            row_sort: None,
+            sorted_rows: Vec::new(),
+            fingerprint: [0; FINGERPRINT_SIZE],
+            data_generation: 0,
             sv: StringViewer::default(),
             dv: DataViewer::default(),
             mt: MapTool::default(),
+            bookmarks: Vec::new(),
+            comments: Comments::new(),
+            comment_input: None,
+            watches: Vec::new(),
+            watch_log: VecDeque::new(),
+            pre_patch: None,
+            last_reload_time: None,
+            auto_reload: true,
+            bp: BookmarksPanel::default(),
+            pending_bookmark_action: None,
+            row_zoom: None,
+            template: None,
             closed: false,
+            tile_rect: None,
+            stats: SessionStats::default(),
+            timeline: ReloadTimeline::default(),
+            scrollbar_ticks: Vec::new(),
+            scrollbar_ticks_key: None,
+            editing: None,
+            dirty_bytes: HashSet::new(),
+            edit_history: Vec::new(),
+            redo_history: Vec::new(),
+            unsaved: false,
+            virtual_file: false,
+            actions: Vec::new(),
         }
     }
 }
 
 impl HexView {
-    pub fn new(file: BinFile, id: usize) -> Self {
+    pub fn new(file: BinFile, id: usize, bytes_per_row: usize) -> Self {
         let min_rows = 10;
         let max_rows = 25;
-        let default_bytes_per_row = 0x10;
-        let num_rows = (file.data.len() / default_bytes_per_row).clamp(min_rows, max_rows) as u32;
+        let num_rows = (file.data.len() / bytes_per_row).clamp(min_rows, max_rows) as u32;
+        let detected_byte_grouping = ByteGrouping::detect(&file.data);
+        let fingerprint = compute_fingerprint(&file.data);
+        let comments = read_comments(&file.path).unwrap_or_else(|e| {
+            log::error!("Failed to read comments: {}", e);
+            Comments::new()
+        });
 
         Self {
             id,
             file,
             num_rows,
-            bytes_per_row: default_bytes_per_row,
+            bytes_per_row,
+            detected_byte_grouping,
+            fingerprint,
+            comments,
             ..Default::default()
         }
     }
 
+    pub fn set_virtual_base(&mut self, virtual_base: Option<usize>) {
+        self.virtual_base = virtual_base;
+        self.virtual_base_error = None;
+        self.virtual_base_input = match virtual_base {
+            Some(base) => format!("0x{:X}", base),
+            None => String::new(),
+        };
+    }
+
+    /// Converts a file offset to the address shown to the user
+    pub fn virtual_addr(&self, pos: usize) -> usize {
+        pos.saturating_add(self.virtual_base.unwrap_or(0))
+    }
+
+    /// Converts an address entered by the user back to a file offset
+    pub fn file_offset(&self, addr: usize) -> Option<usize> {
+        addr.checked_sub(self.virtual_base.unwrap_or(0))
+    }
+
     pub fn set_cur_pos(&mut self, val: usize) {
         if self.pos_locked {
             return;
         }
         let last_line_start_address =
             (self.file.data.len() / self.bytes_per_row) * self.bytes_per_row;
-        self.cur_pos = val.clamp(0, last_line_start_address);
+        let new_pos = val.clamp(0, last_line_start_address);
+        self.stats.bytes_scrolled += new_pos.abs_diff(self.cur_pos);
+        self.cur_pos = new_pos;
     }
 
     pub fn adjust_cur_pos(&mut self, delta: isize) {
@@ -158,15 +647,161 @@ impl HexView {
         }
         let last_line_start_address =
             (self.file.data.len() / self.bytes_per_row) * self.bytes_per_row;
-        self.cur_pos =
+        let new_pos =
             (self.cur_pos as isize + delta).clamp(0, last_line_start_address as isize) as usize;
+        self.stats.bytes_scrolled += new_pos.abs_diff(self.cur_pos);
+        self.cur_pos = new_pos;
+    }
+
+    /// Like `adjust_cur_pos`, but with `wrap` set, moving past either end of the file
+    /// from the first or last line continues from the other end
+    pub fn scroll_cur_pos(&mut self, delta: isize, wrap: bool) {
+        if !wrap || self.pos_locked {
+            self.adjust_cur_pos(delta);
+            return;
+        }
+        let last_line_start_address =
+            (self.file.data.len() / self.bytes_per_row) * self.bytes_per_row;
+        let new_pos = self.cur_pos as isize + delta;
+
+        if new_pos < 0 && self.cur_pos == 0 {
+            let last_page = self.file.data.len().saturating_sub(self.bytes_per_screen());
+            self.set_cur_pos(last_page.next_multiple_of(self.bytes_per_row));
+        } else if new_pos > last_line_start_address as isize
+            && self.cur_pos == last_line_start_address
+        {
+            self.set_cur_pos(0);
+        } else {
+            self.adjust_cur_pos(delta);
+        }
+    }
+
+    pub fn view_preset(&self) -> ViewPreset {
+        ViewPreset {
+            cur_pos: self.cur_pos,
+            bytes_per_row: self.bytes_per_row,
+            num_rows: self.num_rows,
+            show_selection_info: self.show_selection_info,
+            show_cursor_info: self.show_cursor_info,
+            show_data_viewer: self.dv.show,
+            show_string_viewer: self.sv.show,
+            show_map_tool: self.mt.show,
+        }
+    }
+
+    pub fn apply_view_preset(
+        &mut self,
+        preset: &ViewPreset,
+        byte_grouping: ByteGrouping,
+    ) -> Result<(), String> {
+        Settings::validate_row_layout(preset.bytes_per_row, byte_grouping)?;
+        if preset.num_rows == 0 {
+            return Err("Rows per screen must be greater than 0".to_owned());
+        }
+
+        self.set_bytes_per_row(preset.bytes_per_row);
+        self.num_rows = preset.num_rows;
+        self.set_cur_pos(preset.cur_pos);
+        self.show_selection_info = preset.show_selection_info;
+        self.show_cursor_info = preset.show_cursor_info;
+        self.dv.show = preset.show_data_viewer;
+        self.sv.show = preset.show_string_viewer;
+        self.mt.show = preset.show_map_tool;
+        Ok(())
+    }
+
+    /// Moves on by whole pages while the page on screen only holds zero bytes.
+    /// Stays put if every page up to the end of the file in that direction is empty.
+    pub fn skip_null_pages(&mut self, forward: bool) {
+        let page = self.bytes_per_screen();
+        let len = self.file.data.len();
+        if page == 0 || self.pos_locked {
+            return;
+        }
+
+        let is_null_page = |pos: usize| {
+            self.file.data[pos.min(len)..(pos + page).min(len)]
+                .iter()
+                .all(|b| *b == 0)
+        };
+
+        let mut pos = self.cur_pos;
+        // At most one step per page, so an all-zero file can't loop forever
+        for _ in 0..=len / page {
+            if !is_null_page(pos) {
+                self.set_cur_pos(pos);
+                return;
+            }
+            pos = match forward {
+                true if pos + page < len => pos + page,
+                false if pos > 0 => pos.saturating_sub(page),
+                _ => return,
+            };
+        }
+    }
+
+    pub fn set_bytes_per_row(&mut self, bytes_per_row: usize) {
+        self.bytes_per_row = bytes_per_row;
+        self.cur_pos -= self.cur_pos % bytes_per_row;
+        self.update_row_sort();
+    }
+
+    /// Recomputes the display order of the rows after the sort or the data changed
+    pub fn update_row_sort(&mut self) {
+        let Some(sort) = self.row_sort else {
+            self.sorted_rows.clear();
+            return;
+        };
+
+        if sort.column >= self.bytes_per_row {
+            self.row_sort = None;
+            self.sorted_rows.clear();
+            return;
+        }
+
+        // Only full rows have every column, so a partial last row always stays at the end
+        let full_rows = self.file.data.len() / self.bytes_per_row;
+        let data = &self.file.data;
+        let bytes_per_row = self.bytes_per_row;
+        let value = |row: usize| data[row * bytes_per_row + sort.column];
+
+        self.sorted_rows = (0..full_rows).collect();
+        match sort.descending {
+            true => self
+                .sorted_rows
+                .sort_by_key(|row| std::cmp::Reverse(value(*row))),
+            false => self.sorted_rows.sort_by_key(|row| value(*row)),
+        }
+
+        if !self.file.data.len().is_multiple_of(self.bytes_per_row) {
+            self.sorted_rows.push(full_rows);
+        }
     }
 
     pub fn bytes_per_screen(&self) -> usize {
         self.bytes_per_row * self.num_rows as usize
     }
 
+    /// File offsets of the rows on screen while the rows are sorted
+    fn sorted_screen_rows(&self) -> &[usize] {
+        let first = (self.cur_pos / self.bytes_per_row).min(self.sorted_rows.len());
+        let last = (first + self.num_rows as usize).min(self.sorted_rows.len());
+        &self.sorted_rows[first..last]
+    }
+
     pub fn get_cur_bytes(&self) -> Vec<u8> {
+        if self.row_sort.is_some() {
+            return self
+                .sorted_screen_rows()
+                .iter()
+                .flat_map(|row| {
+                    let start = row * self.bytes_per_row;
+                    let end = (start + self.bytes_per_row).min(self.file.data.len());
+                    self.file.data[start..end].iter().copied()
+                })
+                .collect();
+        }
+
         let max_end = self.cur_pos + self.bytes_per_screen();
         let end = max_end.min(self.file.data.len());
 
@@ -174,16 +809,372 @@ impl HexView {
     }
 
     pub fn get_selected_bytes(&self) -> Vec<u8> {
-        match self.selection.state {
+        self.get_selected_rows().concat()
+    }
+
+    /// The selected bytes of each row of a block selection, or all of them as one row
+    pub fn get_selected_rows(&self) -> Vec<Vec<u8>> {
+        self.selected_slices().map(<[u8]>::to_vec).collect()
+    }
+
+    /// Up to `max_len` selected bytes, without copying the rest of the selection
+    pub fn get_selected_prefix(&self, max_len: usize) -> Vec<u8> {
+        self.selected_slices()
+            .flatten()
+            .take(max_len)
+            .copied()
+            .collect()
+    }
+
+    /// Number of selected bytes that are in the file
+    pub fn selection_len(&self) -> usize {
+        self.selected_slices().map(<[u8]>::len).sum()
+    }
+
+    fn selected_slices(&self) -> impl Iterator<Item = &[u8]> {
+        let ranges = match self.selection.state {
             HexViewSelectionState::None => vec![],
             HexViewSelectionState::Selecting | HexViewSelectionState::Selected => {
-                self.file.data[self.selection.start()..self.selection.end() + 1].to_vec()
+                self.selection.ranges(self.bytes_per_row)
             }
-        }
+        };
+        // The last row of a block may be cut short by the end of the file
+        let len = self.file.data.len();
+        ranges.into_iter().map(move |range| {
+            &self.file.data[(*range.start()).min(len)..(range.end() + 1).min(len)]
+        })
+    }
+
+    /// Whether the data viewer is showing the XOR rows, which need the other views' selections
+    pub fn shows_xor(&self) -> bool {
+        self.dv.show && self.dv.xor
+    }
+
+    pub fn file_name(&self) -> String {
+        self.file
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
     }
 
     pub fn reload_file(&mut self) -> Result<(), Error> {
-        self.file.data = read_file_bytes(self.file.path.clone())?;
+        let data = read_file_bytes(self.file.path.clone())?;
+        let previous = std::mem::replace(&mut self.file.data, data);
+        log_watches(
+            &mut self.watch_log,
+            &self.watches,
+            &previous,
+            &self.file.data,
+        );
+        self.timeline.record(previous, &self.file.data);
+        self.stats.reloads += 1;
+        self.last_reload_time = Some(Instant::now());
+
+        // Any edits and patches were lost with the reload
+        self.pre_patch = None;
+        self.editing = None;
+        self.dirty_bytes.clear();
+        self.edit_history.clear();
+        self.redo_history.clear();
+        self.unsaved = false;
+        self.on_data_changed();
+        Ok(())
+    }
+
+    /// Applies an IPS patch in memory, keeping the unpatched data so it can be reverted
+    pub fn apply_ips_patch(&mut self, patch: &[u8]) -> Result<(), Error> {
+        let original = self.file.data.clone();
+        apply_ips_patch(&mut self.file.data, patch)?;
+
+        let len = self.file.data.len();
+        let mut dirty_bytes: HashSet<usize> = self
+            .dirty_bytes
+            .iter()
+            .copied()
+            .filter(|&pos| pos < len)
+            .collect();
+        dirty_bytes.extend(changed_offsets(&original, &self.file.data));
+        let dirty_bytes = std::mem::replace(&mut self.dirty_bytes, dirty_bytes);
+
+        // Reverting goes back to before the first patch
+        self.pre_patch.get_or_insert(PrePatch {
+            data: original,
+            unsaved: self.unsaved,
+            dirty_bytes,
+        });
+
+        // Offsets in the edit history may no longer line up with the data
+        self.editing = None;
+        self.edit_history.clear();
+        self.redo_history.clear();
+        self.stats.edits += 1;
+        self.unsaved = true;
+        self.on_data_changed();
+        Ok(())
+    }
+
+    /// Restores the data from before the first IPS patch was applied
+    pub fn revert_ips_patch(&mut self) {
+        if let Some(pre_patch) = self.pre_patch.take() {
+            self.file.data = pre_patch.data;
+            self.dirty_bytes = pre_patch.dirty_bytes;
+            self.editing = None;
+            self.edit_history.clear();
+            self.redo_history.clear();
+            self.unsaved = pre_patch.unsaved;
+            self.on_data_changed();
+        }
+    }
+
+    /// Removes the bytes from `start` to `end` (inclusive), shifting the rest of the file down
+    pub fn strip_range(&mut self, start: usize, end: usize) -> Result<(), Error> {
+        if start > end || end >= self.file.data.len() {
+            return Err(Error::msg(format!(
+                "Range 0x{:X} - 0x{:X} is outside of the file",
+                start, end
+            )));
+        }
+
+        self.file.data.drain(start..=end);
+
+        // Offsets in the edit history may no longer line up with the data
+        let len = end - start + 1;
+        self.editing = None;
+        self.edit_history.clear();
+        self.redo_history.clear();
+        self.dirty_bytes = self
+            .dirty_bytes
+            .iter()
+            .filter(|pos| !(start..=end).contains(*pos))
+            .map(|pos| if *pos > end { pos - len } else { *pos })
+            .collect();
+        self.stats.edits += 1;
+        self.unsaved = true;
+        self.on_data_changed();
+        Ok(())
+    }
+
+    /// Reverses the byte order of the selected 2, 4 or 8 byte value in place
+    pub fn swap_selection_endianness(&mut self) -> Result<(), Error> {
+        let ranges = self.selection.ranges(self.bytes_per_row);
+        let [range] = ranges.as_slice() else {
+            return Err(Error::msg(
+                "Can't swap the endianness of a block selection spanning several rows",
+            ));
+        };
+        let (start, end) = (*range.start(), *range.end());
+        let len = end - start + 1;
+
+        if !matches!(len, 2 | 4 | 8) || end >= self.file.data.len() {
+            return Err(Error::msg(format!(
+                "Can't swap the endianness of a 0x{:X} byte selection",
+                len
+            )));
+        }
+
+        let old = self.file.data[start..=end].to_vec();
+        self.file.data[start..=end].reverse();
+        let edit: Vec<(usize, u8)> = (start..=end)
+            .zip(old)
+            .filter(|(pos, old)| self.file.data[*pos] != *old)
+            .collect();
+        if edit.is_empty() {
+            return Ok(());
+        }
+        self.dirty_bytes.extend(edit.iter().map(|(pos, _)| *pos));
+        self.edit_history.push(edit);
+        self.redo_history.clear();
+        self.stats.edits += 1;
+        self.unsaved = true;
+        self.on_data_changed();
+        Ok(())
+    }
+
+    /// Writes the data back to the file it was loaded from
+    pub fn save(&mut self) -> Result<(), Error> {
+        if self.virtual_file {
+            return Err(Error::msg(format!(
+                "{} isn't backed by a file on disk",
+                self.file_name()
+            )));
+        }
+        self.file.write_to_path(&self.file.path)?;
+        self.dirty_bytes.clear();
+        self.unsaved = false;
+
+        // The patched data is on disk now, so reverting the patch is an unsaved change
+        if let Some(pre_patch) = self.pre_patch.as_mut() {
+            pre_patch.unsaved = true;
+            pre_patch.dirty_bytes = changed_offsets(&self.file.data, &pre_patch.data).collect();
+        }
+        Ok(())
+    }
+
+    fn write_byte(&mut self, pos: usize, value: u8) {
+        let old = self.file.data[pos];
+        if old == value {
+            return;
+        }
+
+        self.edit_history.push(vec![(pos, old)]);
+        self.redo_history.clear();
+        self.file.data[pos] = value;
+        self.dirty_bytes.insert(pos);
+        self.stats.edits += 1;
+        self.unsaved = true;
+        self.on_data_changed();
+        self.actions.push(HexViewAction::DataChanged);
+    }
+
+    /// Overwrites the selected bytes with the value as a single edit, so it's undone in one step
+    fn fill_selection(&mut self, value: u8) {
+        let last = self.file.data.len().saturating_sub(1);
+        let positions = self
+            .selection
+            .ranges(self.bytes_per_row)
+            .into_iter()
+            .flatten()
+            .filter(|pos| *pos <= last);
+        let mut edit = Vec::new();
+        for pos in positions {
+            let old = std::mem::replace(&mut self.file.data[pos], value);
+            if old != value {
+                edit.push((pos, old));
+                self.dirty_bytes.insert(pos);
+                self.stats.edits += 1;
+            }
+        }
+
+        if !edit.is_empty() {
+            self.edit_history.push(edit);
+            self.redo_history.clear();
+            self.unsaved = true;
+            self.on_data_changed();
+            self.actions.push(HexViewAction::DataChanged);
+        }
+    }
+
+    /// Writes the bytes of an edit and returns the values they replaced, in the same order
+    fn swap_edit_bytes(&mut self, edit: Vec<(usize, u8)>) -> Vec<(usize, u8)> {
+        let mut replaced: Vec<(usize, u8)> = edit
+            .into_iter()
+            .rev()
+            .filter_map(|(pos, value)| {
+                let byte = self.file.data.get_mut(pos)?;
+                Some((pos, std::mem::replace(byte, value)))
+            })
+            .collect();
+        replaced.reverse();
+        replaced
+    }
+
+    pub fn undo_edit(&mut self) {
+        let Some(edit) = self.edit_history.pop() else {
+            return;
+        };
+
+        let redo = self.swap_edit_bytes(edit);
+        for (pos, _) in &redo {
+            if !self
+                .edit_history
+                .iter()
+                .flatten()
+                .any(|(edited, _)| edited == pos)
+            {
+                self.dirty_bytes.remove(pos);
+            }
+        }
+        self.redo_history.push(redo);
+        self.unsaved = true;
+        self.on_data_changed();
+        self.actions.push(HexViewAction::DataChanged);
+    }
+
+    pub fn redo_edit(&mut self) {
+        let Some(edit) = self.redo_history.pop() else {
+            return;
+        };
+
+        let undo = self.swap_edit_bytes(edit);
+        self.dirty_bytes.extend(undo.iter().map(|(pos, _)| *pos));
+        self.edit_history.push(undo);
+        self.unsaved = true;
+        self.on_data_changed();
+        self.actions.push(HexViewAction::DataChanged);
+    }
+
+    /// Starts editing with the edit key and types hex digits into the byte being edited
+    fn handle_edit_input(&mut self, ctx: &egui::Context, settings: &Settings) {
+        if self.editing.is_none() {
+            let edit_key = egui::Key::from_name(&settings.edit_key);
+            if let (Some(key), Some(pos)) = (edit_key, self.cursor_pos) {
+                if !ctx.wants_keyboard_input() && ctx.input(|i| i.key_pressed(key)) {
+                    self.editing = Some(HexEdit {
+                        pos,
+                        high_nibble: None,
+                    });
+                }
+            }
+            return;
+        }
+
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape) || i.key_pressed(egui::Key::Enter)) {
+            self.editing = None;
+            return;
+        }
+
+        let digits: Vec<u8> = ctx.input(|i| {
+            i.events
+                .iter()
+                .filter_map(|event| match event {
+                    egui::Event::Text(text) => Some(text.clone()),
+                    _ => None,
+                })
+                .flat_map(|text| text.chars().collect::<Vec<char>>())
+                .filter_map(|c| c.to_digit(16).map(|digit| digit as u8))
+                .collect()
+        });
+
+        for digit in digits {
+            let Some(edit) = self.editing.as_mut() else {
+                break;
+            };
+            match edit.high_nibble {
+                None => edit.high_nibble = Some(digit),
+                Some(high) => {
+                    let pos = edit.pos;
+                    self.write_byte(pos, (high << 4) | digit);
+
+                    // Continue with the next byte
+                    self.editing = (pos + 1 < self.file.data.len()).then_some(HexEdit {
+                        pos: pos + 1,
+                        high_nibble: None,
+                    });
+                }
+            }
+        }
+
+        // Keep the cursor blinking
+        ctx.request_repaint_after(std::time::Duration::from_millis(500));
+    }
+
+    /// Updates everything derived from the file's data after it changed
+    fn on_data_changed(&mut self) {
+        self.file.detected_format = file_format::detect(&self.file.data);
+        self.update_row_sort();
+        self.detected_byte_grouping = ByteGrouping::detect(&self.file.data);
+        self.fingerprint = compute_fingerprint(&self.file.data);
+        self.data_generation += 1;
+        self.entropy.invalidate();
+        self.zero_distances = None;
+        if let Some(template) = self.template.as_mut() {
+            template.update(&self.file.data);
+        }
+
+        let last_line_start_address =
+            (self.file.data.len() / self.bytes_per_row) * self.bytes_per_row;
+        self.cur_pos = self.cur_pos.min(last_line_start_address);
 
         if self.selection.range.first >= self.file.data.len()
             && self.selection.range.second >= self.file.data.len()
@@ -193,7 +1184,6 @@ impl HexView {
             self.selection.range.first = self.selection.range.first.min(self.file.data.len() - 1);
             self.selection.range.second = self.selection.range.second.min(self.file.data.len() - 1);
         }
-        Ok(())
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -205,12 +1195,57 @@ impl HexView {
         cursor_state: CursorState,
         can_selection_change: bool,
         font_size: f32,
-        byte_grouping: usize,
-        theme_settings: ThemeSettings,
-    ) {
+        settings: &Settings,
+        other_views: &[OtherView],
+    ) -> Rect {
+        let byte_grouping: usize = match settings.byte_grouping {
+            ByteGrouping::Auto => self.detected_byte_grouping,
+            grouping => grouping,
+        }
+        .into();
+        let theme_settings = &settings.theme_settings;
+
+        self.handle_edit_input(ctx, settings);
+
+        let group_rows_every = settings.group_rows_every.filter(|n| *n > 0);
+        let spacing = GridSpacing::new(settings.compact_mode);
+
+        if self.byte_coloring == ByteColoring::Entropy {
+            self.entropy
+                .update(&self.file.data, settings.entropy_window);
+        }
+        let entropy_values = self.entropy.values.clone();
+        let max_entropy = max_entropy(settings.entropy_window);
+        let entropy =
+            (self.byte_coloring == ByteColoring::Entropy).then(|| entropy_values.lock().unwrap());
+        if self.byte_coloring == ByteColoring::Density && self.zero_distances.is_none() {
+            self.zero_distances = Some(Arc::new(zero_distances(&self.file.data)));
+        }
+        let zero_distances = self
+            .zero_distances
+            .clone()
+            .filter(|_| self.byte_coloring == ByteColoring::Density);
+
+        // The bytes of each row on screen when lined up with another file, None for placeholders
+        let aligned_rows: Option<Vec<Option<Range<usize>>>> = diff_state
+            .aligned
+            .as_ref()
+            .filter(|_| diff_state.enabled)
+            .and_then(|aligned| aligned.layout.as_ref())
+            .filter(|layout| {
+                layout.contains_view(self.id) && layout.bytes_per_row == Some(self.bytes_per_row)
+            })
+            .map(|layout| {
+                (layout.top_row..layout.top_row + self.num_rows as usize)
+                    .map(|row| layout.row_range(self.id, row, self.bytes_per_row))
+                    .collect()
+            });
+
         let grid_rect = ui
             .group(|ui| {
-                egui::Grid::new(format!("hex_grid{}", self.id))
+                let mut group_boundaries: Vec<f32> = Vec::new();
+
+                let grid_rect = egui::Grid::new(format!("hex_grid{}", self.id))
                     .striped(true)
                     .spacing([0.0, 0.0])
                     .min_col_width(0.0)
@@ -218,24 +1253,99 @@ impl HexView {
                     .show(ui, |ui| {
                         let screen_bytes = self.get_cur_bytes();
                         let mut current_pos = self.cur_pos;
+                        let float_width = self.float_view.map_or(0, |kind| {
+                            float_column_width(&screen_bytes, kind, self.file.endianness)
+                        });
 
                         let mut row_chunks = screen_bytes.chunks(self.bytes_per_row);
+                        let sorted_rows = self.sorted_screen_rows().to_vec();
+
+                        let num_digits = match self.virtual_addr(self.file.data.len()) {
+                            //0..=0xFFFF => 4,
+                            0x10000..=0xFFFFFFFF => 8,
+                            0x100000000..=0xFFFFFFFFFFFF => 12,
+                            _ => 8,
+                        };
+
+                        // The float and bit views don't have one cell per byte to label
+                        if settings.show_column_header
+                            && self.float_view.is_none()
+                            && !self.bit_view
+                        {
+                            self.show_column_header(
+                                ui,
+                                num_digits,
+                                byte_grouping,
+                                font_size,
+                                settings,
+                            );
+                        }
 
                         let mut r = 0;
                         while r < self.num_rows {
-                            let row: &[u8] = row_chunks.next().unwrap_or_default();
+                            let aligned_row = aligned_rows
+                                .as_ref()
+                                .map(|rows| rows.get(r as usize).cloned().flatten());
+                            let aligned_bytes;
+                            let row: &[u8] = match &aligned_row {
+                                Some(range) => {
+                                    aligned_bytes = range
+                                        .clone()
+                                        .map(|range| self.file.data[range].to_vec())
+                                        .unwrap_or_default();
+                                    &aligned_bytes
+                                }
+                                None => row_chunks.next().unwrap_or_default(),
+                            };
+                            let placeholder = matches!(aligned_row, Some(None));
+                            if let Some(Some(range)) = &aligned_row {
+                                current_pos = range.start;
+                            }
 
-                            let num_digits = match self.file.data.len() {
-                                //0..=0xFFFF => 4,
-                                0x10000..=0xFFFFFFFF => 8,
-                                0x100000000..=0xFFFFFFFFFFFF => 12,
-                                _ => 8,
+                            // Sorted rows keep showing their original offset
+                            if let Some(row) = sorted_rows.get(r as usize) {
+                                current_pos = row * self.bytes_per_row;
+                            }
+
+                            let row_index = current_pos / self.bytes_per_row;
+                            let group_start = match group_rows_every {
+                                Some(n) => self.row_sort.is_none() && row_index.is_multiple_of(n),
+                                None => false,
                             };
+                            if group_start && r > 0 {
+                                group_boundaries.push(ui.cursor().top());
+                            }
+
+                            let row_address = self.virtual_addr(current_pos);
                             let mut i = num_digits;
                             let mut offset_leading_zeros = true;
 
+                            if !self.bookmarks.is_empty() {
+                                let row_range = current_pos..current_pos + row.len();
+                                let names: Vec<&str> = self
+                                    .bookmarks
+                                    .iter()
+                                    .filter(|b| row_range.contains(&b.offset))
+                                    .map(|b| b.name.as_str())
+                                    .collect();
+                                let glyph = egui::Label::new(
+                                    egui::RichText::new(egui_phosphor::regular::BOOKMARK_SIMPLE)
+                                        .size(font_size)
+                                        .color(match names.is_empty() {
+                                            true => Color32::TRANSPARENT,
+                                            false => Color32::from(
+                                                theme_settings.offset_text_color.clone(),
+                                            ),
+                                        }),
+                                );
+                                let res = ui.add(glyph);
+                                if !names.is_empty() {
+                                    res.on_hover_text(names.join("\n"));
+                                }
+                            }
+
                             while i > 0 {
-                                let digit = current_pos >> ((i - 1) * 4) & 0xF;
+                                let digit = row_address >> ((i - 1) * 4) & 0xF;
 
                                 if offset_leading_zeros && digit > 0 {
                                     offset_leading_zeros = false;
@@ -246,7 +1356,9 @@ impl HexView {
                                         .monospace()
                                         .size(font_size)
                                         .color({
-                                            if offset_leading_zeros {
+                                            if placeholder {
+                                                Color32::TRANSPARENT
+                                            } else if offset_leading_zeros {
                                                 Color32::from(
                                                     theme_settings
                                                         .offset_leading_zero_color
@@ -261,87 +1373,200 @@ impl HexView {
                                 );
 
                                 if i < num_digits && (i % 4) == 0 {
-                                    ui.add(Spacer::default().spacing_x(4.0));
+                                    ui.add(Spacer::default().spacing_x(spacing.group));
+                                }
+                                if ui.add(offset_digit.sense(Sense::click())).double_clicked() {
+                                    self.row_zoom = Some(RowZoom::new(ui.ctx(), current_pos));
                                 }
-                                ui.add(offset_digit);
                                 i -= 1;
                             }
 
-                            ui.add(Spacer::default().spacing_x(8.0));
+                            ui.add(Spacer::default().spacing_x(spacing.section));
                             ui.add(Separator::default().vertical().spacing(0.0));
-                            ui.add(Spacer::default().spacing_x(8.0));
+                            ui.add(Spacer::default().spacing_x(spacing.section));
 
                             // hex view
-                            let mut i = 0;
-                            while i < self.bytes_per_row {
-                                if i > 0 && (i % byte_grouping) == 0 {
-                                    ui.add(Spacer::default().spacing_x(4.0));
-                                }
-                                let row_current_pos = current_pos + i;
-
-                                let byte: Option<u8> = row.get(i).copied();
-
-                                let byte_text = match byte {
-                                    Some(byte) => format!("{:02X}", byte),
-                                    None => "  ".to_string(),
-                                };
-
-                                let hex_label = egui::Label::new(
-                                    egui::RichText::new(byte_text)
-                                        .monospace()
-                                        .size(font_size)
-                                        .color(
-                                            if diff_state.enabled
-                                                && diff_state.is_diff_at(row_current_pos)
-                                            {
-                                                Color32::from(theme_settings.diff_color.clone())
-                                            } else {
-                                                match byte {
-                                                    Some(0) => Color32::from(
-                                                        theme_settings.hex_null_color.clone(),
-                                                    ),
-                                                    _ => Color32::from(
-                                                        theme_settings.other_hex_color.clone(),
-                                                    ),
-                                                }
-                                            },
-                                        )
-                                        .background_color({
-                                            if self.selection.contains(row_current_pos) {
-                                                theme_settings.selection_color.clone().into()
-                                            } else {
-                                                Color32::TRANSPARENT
-                                            }
-                                        }),
-                                )
-                                .sense(Sense::click_and_drag());
+                            if let Some(kind) = self.float_view {
+                                self.show_float_row(
+                                    ui,
+                                    row,
+                                    current_pos,
+                                    kind,
+                                    float_width,
+                                    diff_state,
+                                    ctx,
+                                    cursor_state,
+                                    can_selection_change,
+                                    font_size,
+                                    settings,
+                                    other_views,
+                                );
+                            } else if self.bit_view {
+                                self.show_bit_row(
+                                    ui,
+                                    row,
+                                    current_pos,
+                                    diff_state,
+                                    ctx,
+                                    cursor_state,
+                                    can_selection_change,
+                                    font_size,
+                                    settings,
+                                    other_views,
+                                );
+                            } else {
+                                let mut i = 0;
+                                while i < self.bytes_per_row {
+                                    if i > 0 && (i % byte_grouping) == 0 {
+                                        ui.add(Spacer::default().spacing_x(spacing.group));
+                                    }
+                                    let row_current_pos = current_pos + i;
+
+                                    let byte: Option<u8> = row.get(i).copied();
+
+                                    let edit =
+                                        self.editing.filter(|edit| edit.pos == row_current_pos);
+
+                                    let byte_text = match (byte, edit) {
+                                        // Preview the typed digit until the byte is complete
+                                        (
+                                            Some(_),
+                                            Some(HexEdit {
+                                                high_nibble: Some(high),
+                                                ..
+                                            }),
+                                        ) => format!("{:X}_", high),
+                                        (Some(_), _)
+                                            if diff_state
+                                                .is_masked_at(self.id, row_current_pos) =>
+                                        {
+                                            "..".to_string()
+                                        }
+                                        (Some(0), _) => settings.null_display.text().to_string(),
+                                        (Some(byte), _) => format!("{:02X}", byte),
+                                        (None, _) => "  ".to_string(),
+                                    };
+
+                                    let mut byte_text = egui::RichText::new(byte_text);
+                                    if edit.is_some()
+                                        && (ctx.input(|i| i.time) * 2.0) as i64 % 2 == 0
+                                    {
+                                        byte_text = byte_text.underline();
+                                    }
 
-                                let res = ui.add(hex_label);
+                                    let hex_label = egui::Label::new(
+                                        byte_text
+                                            .monospace()
+                                            .size(font_size)
+                                            .color(
+                                                if let Some(color) = self.diff_color_at(
+                                                    diff_state,
+                                                    theme_settings,
+                                                    row_current_pos,
+                                                ) {
+                                                    color
+                                                } else if let Some(&bits) = entropy
+                                                    .as_ref()
+                                                    .and_then(|e| e.get(row_current_pos))
+                                                {
+                                                    gradient(
+                                                        theme_settings
+                                                            .entropy_low_color
+                                                            .clone()
+                                                            .into(),
+                                                        theme_settings
+                                                            .entropy_high_color
+                                                            .clone()
+                                                            .into(),
+                                                        bits / max_entropy,
+                                                    )
+                                                } else if let Some(&distance) = zero_distances
+                                                    .as_ref()
+                                                    .and_then(|d| d.get(row_current_pos))
+                                                {
+                                                    gradient(
+                                                        theme_settings
+                                                            .density_near_color
+                                                            .clone()
+                                                            .into(),
+                                                        theme_settings
+                                                            .density_far_color
+                                                            .clone()
+                                                            .into(),
+                                                        density_level(distance),
+                                                    )
+                                                } else {
+                                                    match byte {
+                                                        Some(0) => Color32::from(
+                                                            theme_settings.hex_null_color.clone(),
+                                                        ),
+                                                        _ => Color32::from(
+                                                            theme_settings.other_hex_color.clone(),
+                                                        ),
+                                                    }
+                                                },
+                                            )
+                                            .background_color(self.cell_background(
+                                                row_current_pos,
+                                                byte,
+                                                diff_state,
+                                                theme_settings,
+                                            )),
+                                    )
+                                    .sense(Sense::click_and_drag());
+
+                                    let res = ui.add(hex_label);
+                                    let comment = byte.and(self.comments.get(&row_current_pos));
+                                    let res = match comment {
+                                        Some(comment) => {
+                                            ui.painter().circle_filled(
+                                                res.rect.right_top() + egui::vec2(-2.0, 2.0),
+                                                1.5,
+                                                ui.visuals().warn_fg_color,
+                                            );
+                                            res.on_hover_text(comment)
+                                        }
+                                        None => res,
+                                    };
 
-                                if byte.is_some() {
-                                    if res.hovered() {
-                                        self.cursor_pos = Some(row_current_pos);
-                                    }
-                                    if can_selection_change {
+                                    if byte.is_some() {
+                                        if res.hovered() {
+                                            self.cursor_pos = Some(row_current_pos);
+                                        }
+                                        if res.double_clicked() {
+                                            self.editing = Some(HexEdit {
+                                                pos: row_current_pos,
+                                                high_nibble: None,
+                                            });
+                                        }
+                                        res.context_menu(|ui| {
+                                            self.handle_context_menu(
+                                                ui,
+                                                row_current_pos,
+                                                settings,
+                                                other_views,
+                                            )
+                                        });
                                         self.handle_selection(
                                             res,
                                             cursor_state,
                                             row_current_pos,
                                             ctx,
                                             HexViewSelectionSide::Hex,
+                                            can_selection_change,
                                         );
                                     }
-                                }
-                                i += 1;
+                                    i += 1;
 
-                                if i < self.bytes_per_row {
-                                    ui.add(Spacer::default().spacing_x(4.0));
+                                    if i < self.bytes_per_row {
+                                        ui.add(Spacer::default().spacing_x(spacing.byte));
+                                    }
                                 }
                             }
 
-                            ui.add(Spacer::default().spacing_x(8.0));
+                            ui.add(Spacer::default().spacing_x(spacing.section));
                             ui.add(Separator::default().vertical().spacing(0.0));
-                            ui.add(Spacer::default().spacing_x(8.0));
+                            ui.add(Spacer::default().spacing_x(spacing.section));
 
                             // ascii view
                             let mut i = 0;
@@ -351,6 +1576,11 @@ impl HexView {
                                 let row_current_pos = current_pos + i;
 
                                 let ascii_char = match byte {
+                                    Some(_)
+                                        if diff_state.is_masked_at(self.id, row_current_pos) =>
+                                    {
+                                        '.'
+                                    }
                                     Some(32..=126) => byte.unwrap() as char,
                                     Some(_) => '·',
                                     None => ' ',
@@ -371,13 +1601,12 @@ impl HexView {
                                                 theme_settings.other_ascii_color.clone(),
                                             ),
                                         })
-                                        .background_color({
-                                            if self.selection.contains(row_current_pos) {
-                                                theme_settings.selection_color.clone().into()
-                                            } else {
-                                                Color32::TRANSPARENT
-                                            }
-                                        }),
+                                        .background_color(self.cell_background(
+                                            row_current_pos,
+                                            byte,
+                                            diff_state,
+                                            theme_settings,
+                                        )),
                                 )
                                 .sense(Sense::click_and_drag());
 
@@ -388,24 +1617,55 @@ impl HexView {
                                     if res.hovered() {
                                         self.cursor_pos = Some(row_current_pos);
                                     }
-                                    if can_selection_change {
-                                        self.handle_selection(
-                                            res,
-                                            cursor_state,
+                                    res.context_menu(|ui| {
+                                        self.handle_context_menu(
+                                            ui,
                                             row_current_pos,
-                                            ctx,
-                                            HexViewSelectionSide::Ascii,
-                                        );
-                                    }
+                                            settings,
+                                            other_views,
+                                        )
+                                    });
+                                    self.handle_selection(
+                                        res,
+                                        cursor_state,
+                                        row_current_pos,
+                                        ctx,
+                                        HexViewSelectionSide::Ascii,
+                                        can_selection_change,
+                                    );
                                 }
                                 i += 1;
                             }
 
+                            if let Some(n) = group_rows_every {
+                                if group_start {
+                                    ui.label(
+                                        egui::RichText::new(format!("#{}", row_index / n))
+                                            .monospace()
+                                            .size(font_size)
+                                            .color(Color32::from(
+                                                theme_settings.offset_text_color.clone(),
+                                            )),
+                                    );
+                                }
+                            }
+
                             current_pos += self.bytes_per_row;
                             r += 1;
                             ui.end_row();
                         }
-                    });
+                    })
+                    .response
+                    .rect;
+
+                // Draw a thicker separator between row groups
+                for y in group_boundaries {
+                    ui.painter().hline(
+                        grid_rect.x_range(),
+                        y,
+                        Stroke::new(2.0, Color32::from(theme_settings.offset_text_color.clone())),
+                    );
+                }
             })
             .response
             .rect;
@@ -415,6 +1675,652 @@ impl HexView {
                 self.cursor_pos = None;
             }
         }
+
+        grid_rect
+    }
+
+    /// Recomputes which pixel rows of the scrollbar have diffs when the height or the diffs changed
+    fn update_scrollbar_ticks(&mut self, diff_state: &DiffState, height: usize) {
+        let key = (height, diff_state.generation, diff_state.enabled);
+        if self.scrollbar_ticks_key == Some(key) {
+            return;
+        }
+        self.scrollbar_ticks_key = Some(key);
+
+        let len = self.file.data.len();
+        let diffs = diff_state.diffs_for(self.id);
+        let diffs = &diffs[..diffs.len().min(len)];
+        self.scrollbar_ticks = match diff_state.enabled && len > 0 {
+            true => (0..height)
+                .map(|i| {
+                    let start = (i * len / height).min(diffs.len());
+                    let end = ((i + 1) * len / height).max(start + 1).min(diffs.len());
+                    diffs[start..end].contains(&true)
+                })
+                .collect(),
+            false => Vec::new(),
+        };
+    }
+
+    fn show_scrollbar_overlay(
+        &mut self,
+        ui: &mut egui::Ui,
+        diff_state: &DiffState,
+        settings: &Settings,
+        height: f32,
+    ) {
+        self.update_scrollbar_ticks(diff_state, height as usize);
+
+        let len = self.file.data.len();
+        let viewport = self.cur_pos..(self.cur_pos + self.bytes_per_screen()).min(len);
+        let selection = match self.selection.state {
+            HexViewSelectionState::None => None,
+            _ => {
+                let ranges = self.selection.ranges(self.bytes_per_row);
+                ranges
+                    .first()
+                    .zip(ranges.last())
+                    .map(|(first, last)| *first.start()..last.end() + 1)
+            }
+        };
+        let theme_settings = &settings.theme_settings;
+
+        let res = ui.add(
+            ScrollbarOverlay::new(len, viewport)
+                .size(settings.scrollbar_width as f32, height)
+                .ticks(
+                    &self.scrollbar_ticks,
+                    theme_settings.diff_color.clone().into(),
+                )
+                .selection(selection, theme_settings.selection_color.clone().into()),
+        );
+
+        if let Some(offset) = ScrollbarOverlay::clicked_offset(&res, len) {
+            // Center the clicked offset on screen
+            let pos = offset.saturating_sub(self.bytes_per_screen() / 2);
+            self.set_cur_pos(pos - pos % self.bytes_per_row);
+        }
+    }
+
+    /// The color of a differing byte, picked from the palette per file pair when comparing more than two files
+    fn diff_color_at(
+        &self,
+        diff_state: &DiffState,
+        theme_settings: &ThemeSettings,
+        pos: usize,
+    ) -> Option<Color32> {
+        if !diff_state.enabled {
+            return None;
+        }
+
+        if let Some(aligned) = &diff_state.aligned {
+            return aligned
+                .op_at(self.id, pos)
+                .map(|_| theme_settings.diff_color.clone().into());
+        }
+
+        if !diff_state.is_diff_at(self.id, pos) {
+            return None;
+        }
+
+        if diff_state.pair_diffs.len() < 2 {
+            return Some(theme_settings.diff_color.clone().into());
+        }
+
+        let pair = diff_state.pair_index_at(self.id, pos)?;
+        Some(theme_settings.diff_pair_color(pair))
+    }
+
+    fn cell_background(
+        &self,
+        pos: usize,
+        byte: Option<u8>,
+        diff_state: &DiffState,
+        theme_settings: &ThemeSettings,
+    ) -> Color32 {
+        if byte.is_none() {
+            return Color32::TRANSPARENT;
+        }
+
+        if self.selection.contains(pos, self.bytes_per_row) {
+            return theme_settings.selection_color.clone().into();
+        }
+
+        if self.hover_highlight == Some(pos) {
+            return theme_settings.hover_highlight_color.clone().into();
+        }
+
+        if self.dirty_bytes.contains(&pos) {
+            return theme_settings.edit_color.clone().into();
+        }
+
+        if self.search_matches.contains(pos) {
+            return theme_settings.search_match_color.clone().into();
+        }
+
+        let aligned = diff_state.aligned.as_ref().filter(|_| diff_state.enabled);
+        match aligned.and_then(|aligned| aligned.op_at(self.id, pos)) {
+            Some(DiffOp::Insert(_)) => return theme_settings.diff_insert_color.clone().into(),
+            Some(DiffOp::Delete(_)) => return theme_settings.diff_delete_color.clone().into(),
+            _ => {}
+        }
+
+        if let (Some(template), Some(byte)) = (&self.template, byte) {
+            if template.mismatch_at(pos, byte) {
+                return theme_settings.template_mismatch_color.clone().into();
+            }
+        }
+
+        if let Some(map_file) = &self.mt.map_file {
+            if self.mt.highlight_uncovered && !map_file.is_covered(pos) {
+                return theme_settings.uncovered_color.clone().into();
+            }
+        }
+
+        Color32::TRANSPARENT
+    }
+
+    /// Asks where to save the selected bytes and writes them to that file.
+    /// The rows of a block selection are written one after another.
+    pub fn export_selection(&self) {
+        let start = self.selection.start();
+        let end = self.selection.end();
+        if let Some(path) = rfd::FileDialog::new()
+            .set_title(format!(
+                "Export selection ({} bytes)",
+                self.selection.num_bytes(self.bytes_per_row)
+            ))
+            .set_file_name(format!("{}_{:X}-{:X}.bin", self.file_name(), start, end))
+            .save_file()
+        {
+            let res = match self.selection.mode {
+                HexViewSelectionMode::Linear => self.file.write_range(path, start, end),
+                HexViewSelectionMode::Block => std::fs::write(&path, self.get_selected_bytes())
+                    .with_context(|| format!("Failed to write file {}", path.display())),
+            };
+            if let Err(e) = res {
+                log::error!("Failed to export selection: {}", e);
+            }
+        }
+    }
+
+    /// Sets or removes the comment on a byte and saves the side-car file. Empty comments are removed.
+    fn set_comment(&mut self, pos: usize, comment: Option<String>) {
+        match comment.filter(|comment| !comment.is_empty()) {
+            Some(comment) => self.comments.insert(pos, comment),
+            None => self.comments.remove(&pos),
+        };
+        self.comment_input = None;
+
+        // Comments on data that isn't backed by a file only last until it's closed
+        if self.virtual_file {
+            return;
+        }
+        if let Err(e) = write_comments(&self.file.path, &self.comments) {
+            log::error!("Failed to save comments: {}", e);
+        }
+    }
+
+    /// The menu shown when right-clicking the cell at `pos`
+    fn handle_context_menu(
+        &mut self,
+        ui: &mut egui::Ui,
+        pos: usize,
+        settings: &Settings,
+        other_views: &[OtherView],
+    ) {
+        let selected_bytes = self.get_selected_bytes();
+
+        if ui.button("Copy offset (hex)").clicked() {
+            ui.ctx()
+                .output_mut(|o| o.copied_text = format!("0x{:X}", self.virtual_addr(pos)));
+            ui.close_menu();
+        }
+        if let Some(byte) = self.file.data.get(pos) {
+            if ui.button("Copy byte value").clicked() {
+                ui.ctx()
+                    .output_mut(|o| o.copied_text = format!("{:02X}", byte));
+                ui.close_menu();
+            }
+        }
+        ui.menu_button("Comment", |ui| {
+            if self
+                .comment_input
+                .as_ref()
+                .is_none_or(|(offset, _)| *offset != pos)
+            {
+                let existing = self.comments.get(&pos).cloned().unwrap_or_default();
+                self.comment_input = Some((pos, existing));
+            }
+            let Some((_, text)) = self.comment_input.as_mut() else {
+                return;
+            };
+
+            let res = ui.add(
+                egui::TextEdit::singleline(text)
+                    .char_limit(MAX_COMMENT_LEN)
+                    .hint_text("Comment")
+                    .desired_width(200.0),
+            );
+            let submitted = res.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            let comment = text.trim().to_owned();
+
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() || submitted {
+                    self.set_comment(pos, Some(comment));
+                    ui.close_menu();
+                }
+                if ui
+                    .add_enabled(
+                        self.comments.contains_key(&pos),
+                        egui::Button::new("Remove"),
+                    )
+                    .clicked()
+                {
+                    self.set_comment(pos, None);
+                    ui.close_menu();
+                }
+            });
+        });
+        if ui.button("Add bookmark here").clicked() {
+            self.bp.show = true;
+            self.pending_bookmark_action = Some(BookmarkAction::AddAt(pos));
+            ui.close_menu();
+        }
+        if ui.button("Go to this offset in all views").clicked() {
+            self.actions.push(HexViewAction::GoToOffset(pos));
+            ui.close_menu();
+        }
+        if ui
+            .add_enabled(
+                self.editing.is_some() && self.selection.state != HexViewSelectionState::None,
+                egui::Button::new("Fill selection with 0x00"),
+            )
+            .on_hover_text("Can be undone with Ctrl+Z, one byte at a time")
+            .on_disabled_hover_text(
+                "Start editing a byte with the edit key or a double click first",
+            )
+            .clicked()
+        {
+            self.fill_selection(0);
+            ui.close_menu();
+        }
+        ui.separator();
+
+        if ui
+            .add_enabled(
+                !selected_bytes.is_empty(),
+                egui::Button::new("Copy as Base64"),
+            )
+            .clicked()
+        {
+            let mut text = BASE64_STANDARD.encode(&selected_bytes);
+            if settings.base64_wrap {
+                text = text
+                    .as_bytes()
+                    .chunks(76)
+                    .map(|line| String::from_utf8_lossy(line).into_owned())
+                    .collect::<Vec<String>>()
+                    .join("\n");
+            }
+            ui.ctx().output_mut(|o| o.copied_text = text);
+            ui.close_menu();
+        }
+
+        for format in [CopyFormat::CArray, CopyFormat::PythonBytes] {
+            if ui
+                .add_enabled(
+                    !selected_bytes.is_empty(),
+                    egui::Button::new(format!("Copy as {}", format)),
+                )
+                .clicked()
+            {
+                ui.ctx()
+                    .output_mut(|o| o.copied_text = format.format(&selected_bytes));
+                ui.close_menu();
+            }
+        }
+
+        if ui
+            .add_enabled(
+                self.selection.state != HexViewSelectionState::None,
+                egui::Button::new("Export selection..."),
+            )
+            .clicked()
+        {
+            ui.close_menu();
+            self.export_selection();
+        }
+
+        let can_swap = self.selection.state != HexViewSelectionState::None
+            && matches!(selected_bytes.len(), 2 | 4 | 8);
+        if ui
+            .add_enabled(can_swap, egui::Button::new("Swap endianness"))
+            .on_hover_text("Reverse the order of the selected bytes in the file")
+            .on_disabled_hover_text("Select exactly 2, 4 or 8 bytes to swap their byte order")
+            .clicked()
+        {
+            match self.swap_selection_endianness() {
+                Ok(()) => self.actions.push(HexViewAction::DataChanged),
+                Err(e) => log::error!("Failed to swap endianness: {}", e),
+            }
+            ui.close_menu();
+        }
+
+        if self.mt.map_file.is_some()
+            && ui
+                .add_enabled(
+                    self.selection.state != HexViewSelectionState::None,
+                    egui::Button::new("Add as map symbol..."),
+                )
+                .clicked()
+        {
+            self.mt.new_symbol = Some(NewSymbol {
+                name: String::new(),
+                start: self.selection.start(),
+                end: self.selection.end(),
+                vram: self.virtual_addr(self.selection.start()),
+            });
+            ui.close_menu();
+        }
+
+        if !other_views.is_empty() {
+            ui.separator();
+        }
+
+        let offset: Option<usize> = selected_bytes.as_slice().try_into().ok().map(|bytes| {
+            let value = match self.file.endianness {
+                Endianness::Little => u32::from_le_bytes(bytes),
+                Endianness::Big => u32::from_be_bytes(bytes),
+            };
+            value as usize
+        });
+
+        for other in other_views {
+            let res = ui.add_enabled(
+                offset.is_some(),
+                egui::Button::new(format!("Follow as offset in {}", other.name)),
+            );
+            if res.clicked() {
+                self.actions.push(HexViewAction::FollowOffset {
+                    target: other.id,
+                    offset: offset.unwrap(),
+                });
+                ui.close_menu();
+            }
+            res.on_disabled_hover_text("Select exactly 4 bytes to follow them as an offset");
+        }
+
+        if self.selection.state == HexViewSelectionState::None {
+            return;
+        }
+        let (start, end) = (self.selection.start(), self.selection.end());
+        let is_block = self.selection.mode == HexViewSelectionMode::Block;
+        for other in other_views {
+            let res = ui.add_enabled(
+                !is_block && end < other.data_len,
+                egui::Button::new(format!("Highlight same range in {}", other.name)),
+            );
+            if res.clicked() {
+                self.actions.push(HexViewAction::SelectRange {
+                    target: other.id,
+                    start,
+                    end,
+                });
+                ui.close_menu();
+            }
+            res.on_disabled_hover_text(match is_block {
+                true => "Only a range of bytes can be highlighted, not a block".to_owned(),
+                false => format!(
+                    "{} is only 0x{:X} bytes long, but the range ends at 0x{:X}",
+                    other.name, other.data_len, end
+                ),
+            });
+        }
+    }
+
+    /// Shows a row of the hex column as decimal floating-point values
+    #[allow(clippy::too_many_arguments)]
+    fn show_float_row(
+        &mut self,
+        ui: &mut egui::Ui,
+        row: &[u8],
+        current_pos: usize,
+        kind: FloatViewKind,
+        width: usize,
+        diff_state: &DiffState,
+        ctx: &egui::Context,
+        cursor_state: CursorState,
+        can_selection_change: bool,
+        font_size: f32,
+        settings: &Settings,
+        other_views: &[OtherView],
+    ) {
+        let theme_settings = &settings.theme_settings;
+        let size = kind.size();
+        let spacing = GridSpacing::new(settings.compact_mode);
+
+        let mut i = 0;
+        while i + size <= self.bytes_per_row {
+            let row_current_pos = current_pos + i;
+            let chunk = row.get(i..i + size);
+
+            let text = match chunk {
+                Some(chunk) => kind.format(chunk, self.file.endianness),
+                None => String::new(),
+            };
+
+            let diff_color = (row_current_pos..row_current_pos + size)
+                .find_map(|pos| self.diff_color_at(diff_state, theme_settings, pos));
+
+            let label = egui::Label::new(
+                egui::RichText::new(format!("{:>width$}", text, width = width))
+                    .monospace()
+                    .size(font_size)
+                    .color(diff_color.unwrap_or(theme_settings.other_hex_color.clone().into()))
+                    .background_color(self.cell_background(
+                        row_current_pos,
+                        chunk.map(|c| c[0]),
+                        diff_state,
+                        theme_settings,
+                    )),
+            )
+            .sense(Sense::click_and_drag());
+
+            let res = ui.add(label);
+
+            if chunk.is_some() {
+                if res.hovered() {
+                    self.cursor_pos = Some(row_current_pos);
+                }
+                res.context_menu(|ui| {
+                    self.handle_context_menu(ui, row_current_pos, settings, other_views)
+                });
+                self.handle_selection(
+                    res,
+                    cursor_state,
+                    row_current_pos,
+                    ctx,
+                    HexViewSelectionSide::Hex,
+                    can_selection_change,
+                );
+            }
+            i += size;
+
+            if i + size <= self.bytes_per_row {
+                ui.add(Spacer::default().spacing_x(spacing.group));
+            }
+        }
+    }
+
+    /// A row of byte indices above the hex grid, with the same cells as a data row so the columns line up
+    fn show_column_header(
+        &self,
+        ui: &mut egui::Ui,
+        num_digits: usize,
+        byte_grouping: usize,
+        font_size: f32,
+        settings: &Settings,
+    ) {
+        let spacing = GridSpacing::new(settings.compact_mode);
+        let color = Color32::from(settings.theme_settings.offset_leading_zero_color.clone());
+        let text = |text: String| {
+            egui::Label::new(
+                egui::RichText::new(text)
+                    .monospace()
+                    .size(font_size)
+                    .color(color),
+            )
+        };
+
+        if !self.bookmarks.is_empty() {
+            ui.add(text(" ".to_owned()));
+        }
+        for i in (1..=num_digits).rev() {
+            if i < num_digits && (i % 4) == 0 {
+                ui.add(Spacer::default().spacing_x(spacing.group));
+            }
+            ui.add(text(" ".to_owned()));
+        }
+
+        ui.add(Spacer::default().spacing_x(spacing.section));
+        ui.add(Separator::default().vertical().spacing(0.0));
+        ui.add(Spacer::default().spacing_x(spacing.section));
+
+        for i in 0..self.bytes_per_row {
+            if i > 0 && (i % byte_grouping) == 0 {
+                ui.add(Spacer::default().spacing_x(spacing.group));
+            }
+            ui.add(text(format!("{:02X}", i)));
+        }
+        ui.end_row();
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn show_bit_row(
+        &mut self,
+        ui: &mut egui::Ui,
+        row: &[u8],
+        current_pos: usize,
+        diff_state: &DiffState,
+        ctx: &egui::Context,
+        cursor_state: CursorState,
+        can_selection_change: bool,
+        font_size: f32,
+        settings: &Settings,
+        other_views: &[OtherView],
+    ) {
+        let theme_settings = &settings.theme_settings;
+        let spacing = GridSpacing::new(settings.compact_mode);
+
+        for i in 0..self.bytes_per_row {
+            if i > 0 {
+                ui.add(Spacer::default().spacing_x(spacing.group));
+            }
+            let row_current_pos = current_pos + i;
+            let byte = row.get(i).copied();
+
+            let color = self
+                .diff_color_at(diff_state, theme_settings, row_current_pos)
+                .unwrap_or(theme_settings.other_hex_color.clone().into());
+            let selected_bits = match self.selection.contains(row_current_pos, self.bytes_per_row) {
+                true => self.selection.bit_range(),
+                false => None,
+            };
+
+            for bit in (0..8u8).rev() {
+                let text = match byte {
+                    Some(byte) => ((byte >> bit) & 1).to_string(),
+                    None => " ".to_owned(),
+                };
+                let background = match selected_bits {
+                    Some((high, low)) if (low..=high).contains(&bit) => {
+                        theme_settings.selection_color.clone().into()
+                    }
+                    Some(_) => Color32::TRANSPARENT,
+                    None => self.cell_background(row_current_pos, byte, diff_state, theme_settings),
+                };
+
+                let label = egui::Label::new(
+                    egui::RichText::new(text)
+                        .monospace()
+                        .size(font_size)
+                        .color(color)
+                        .background_color(background),
+                )
+                .sense(Sense::click_and_drag());
+                let res = ui.add(label);
+
+                if byte.is_none() {
+                    continue;
+                }
+                if res.hovered() {
+                    self.cursor_pos = Some(row_current_pos);
+                }
+                res.context_menu(|ui| {
+                    self.handle_context_menu(ui, row_current_pos, settings, other_views)
+                });
+                self.handle_bit_selection(
+                    res,
+                    cursor_state,
+                    row_current_pos,
+                    bit,
+                    ctx,
+                    can_selection_change,
+                );
+            }
+        }
+    }
+
+    /// Like `handle_selection`, but selects a range of bits within the byte the drag started on
+    fn handle_bit_selection(
+        &mut self,
+        res: egui::Response,
+        cursor_state: CursorState,
+        row_current_pos: usize,
+        bit: u8,
+        ctx: &egui::Context,
+        can_selection_change: bool,
+    ) {
+        if res.middle_clicked() {
+            self.selection.clear();
+        }
+
+        if !can_selection_change {
+            return;
+        }
+
+        if res.hovered() && cursor_state == CursorState::Pressed {
+            self.selection
+                .begin(row_current_pos, HexViewSelectionSide::Hex);
+            self.selection.bits = Some((bit, bit));
+        }
+
+        let Some((first_bit, _)) = self.selection.bits else {
+            return;
+        };
+        if self.selection.range.first != row_current_pos {
+            return;
+        }
+
+        if let Some(cursor_pos) = ctx.input(|i| i.pointer.hover_pos()) {
+            if res.rect.contains(cursor_pos) {
+                match cursor_state {
+                    CursorState::StillDown
+                        if self.selection.state == HexViewSelectionState::Selecting =>
+                    {
+                        self.selection.bits = Some((first_bit, bit));
+                    }
+                    CursorState::Released
+                        if self.selection.state == HexViewSelectionState::Selecting =>
+                    {
+                        self.selection.bits = Some((first_bit, bit));
+                        self.selection.finalize(row_current_pos);
+                    }
+                    _ => {}
+                }
+            }
+        }
     }
 
     fn handle_selection(
@@ -424,10 +2330,23 @@ impl HexView {
         row_current_pos: usize,
         ctx: &egui::Context,
         side: HexViewSelectionSide,
+        can_selection_change: bool,
     ) {
+        // Clearing always works, even while the selection is locked
+        if res.middle_clicked() {
+            self.selection.clear();
+        }
+
+        if !can_selection_change {
+            return;
+        }
+
         if res.hovered() {
             if cursor_state == CursorState::Pressed {
                 self.selection.begin(row_current_pos, side);
+                if ctx.input(|i| i.modifiers.alt) {
+                    self.selection.mode = HexViewSelectionMode::Block;
+                }
             }
 
             self.cursor_pos = Some(row_current_pos);
@@ -436,26 +2355,56 @@ impl HexView {
         if let Some(cursor_pos) = ctx.input(|i| i.pointer.hover_pos()) {
             if res.rect.contains(cursor_pos) {
                 match cursor_state {
-                    CursorState::StillDown => {
-                        if self.selection.state == HexViewSelectionState::Selecting {
-                            self.selection.update(row_current_pos);
-                        }
+                    CursorState::StillDown
+                        if self.selection.state == HexViewSelectionState::Selecting =>
+                    {
+                        self.selection.update(row_current_pos);
                     }
-                    CursorState::Released => {
-                        if self.selection.state == HexViewSelectionState::Selecting {
-                            self.selection.finalize(row_current_pos);
-                        }
+                    CursorState::Released
+                        if self.selection.state == HexViewSelectionState::Selecting =>
+                    {
+                        self.selection.finalize(row_current_pos);
                     }
                     _ => {}
                 }
             }
         }
+    }
 
-        if res.middle_clicked() {
-            self.selection.clear();
-        }
+    fn show_session_stats(&self, ui: &mut egui::Ui) {
+        let elapsed = self.stats.opened_at.elapsed().as_secs();
+
+        egui::Grid::new(format!("session_stats{}", self.id))
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Bytes scrolled");
+                ui.label(format!("0x{:X}", self.stats.bytes_scrolled));
+                ui.end_row();
+
+                ui.label("Bytes selected");
+                ui.label(format!("0x{:X}", self.get_selected_bytes().len()));
+                ui.end_row();
+
+                ui.label("Edits");
+                ui.label(self.stats.edits.to_string());
+                ui.end_row();
+
+                ui.label("Reloads");
+                ui.label(self.stats.reloads.to_string());
+                ui.end_row();
+
+                ui.label("Time open");
+                ui.label(format!(
+                    "{}h {:02}m {:02}s",
+                    elapsed / 3600,
+                    elapsed / 60 % 60,
+                    elapsed % 60
+                ));
+                ui.end_row();
+            });
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn show(
         &mut self,
         config: &mut Config,
@@ -464,185 +2413,692 @@ impl HexView {
         ctx: &egui::Context,
         cursor_state: CursorState,
         can_selection_change: bool,
+        other_views: &[OtherView],
     ) {
-        let font_size = 14.0;
-
-        egui::Window::new(self.file.path.to_str().unwrap())
+        let mut window = egui::Window::new(self.file.path.to_str().unwrap())
             .id(Id::new(format!("hex_view_window_{}", self.id)))
-            .title_bar(false)
-            .show(ctx, |ui| {
-                let file_name = self.file.path.as_path().to_str().unwrap();
-
-                ui.with_layout(
-                    egui::Layout::left_to_right(eframe::emath::Align::Min),
-                    |ui| {
-                        ui.label(
-                            egui::RichText::new(file_name)
-                                .monospace()
-                                .size(font_size)
-                                .color(Color32::LIGHT_GRAY),
-                        );
+            .title_bar(false);
+
+        let file_config = config.files.iter().find(|a| a.path == self.file.path);
+        if let Some([x, y]) = file_config.and_then(|f| f.window_pos) {
+            window = window.default_pos([x, y]);
+        }
+        if let Some([width, height]) = file_config.and_then(|f| f.window_size) {
+            window = window.default_size([width, height]);
+        }
+
+        let tiled = self.tile_rect.take();
+        if let Some(rect) = tiled {
+            window = window.current_pos(rect.min).min_size(rect.size());
+        }
+
+        let response = window.show(ctx, |ui| {
+            self.show_contents(
+                ui,
+                config,
+                settings,
+                diff_state,
+                ctx,
+                cursor_state,
+                can_selection_change,
+                other_views,
+                true,
+            );
+        });
+
+        // Remember where the window is so the workspace can restore it
+        if let Some(rect) = response.map(|response| response.response.rect) {
+            let frame = egui::Frame::window(&ctx.style());
+            let size = rect.size()
+                - frame.inner_margin.sum()
+                - egui::Vec2::splat(2.0 * frame.stroke.width);
+            let pos = Some([rect.min.x, rect.min.y]);
+            let size = Some([size.x, size.y]);
+
+            // Only moving, resizing or tiling the window is a change to the workspace.
+            // The first geometry and resizes that follow the contents are just recorded.
+            let dragged = ctx.input(|i| i.pointer.is_decidedly_dragging());
+            if let Some(file_config) = config.files.iter_mut().find(|a| a.path == self.file.path) {
+                if file_config.window_pos != pos || file_config.window_size != size {
+                    if tiled.is_some() || (file_config.window_pos.is_some() && dragged) {
+                        config.changed = true;
+                    }
+                    file_config.window_pos = pos;
+                    file_config.window_size = size;
+                }
+            }
+        }
+    }
+
+    /// Shows the view without a window, e.g. when docked into a paired layout
+    #[allow(clippy::too_many_arguments)]
+    pub fn show_docked(
+        &mut self,
+        ui: &mut egui::Ui,
+        rect: Rect,
+        config: &mut Config,
+        settings: &Settings,
+        diff_state: &DiffState,
+        ctx: &egui::Context,
+        cursor_state: CursorState,
+        can_selection_change: bool,
+        other_views: &[OtherView],
+    ) {
+        ui.allocate_ui_at_rect(rect, |ui| {
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                self.show_contents(
+                    ui,
+                    config,
+                    settings,
+                    diff_state,
+                    ctx,
+                    cursor_state,
+                    can_selection_change,
+                    other_views,
+                    false,
+                );
+            });
+        });
+    }
+
+    fn handle_bookmark_action(&mut self, action: BookmarkAction, config: &mut Config) {
+        match action {
+            BookmarkAction::Add(name) => {
+                let offset = match self.selection.state {
+                    HexViewSelectionState::None => self.cur_pos,
+                    _ => self.selection.start(),
+                };
+                self.bookmarks.push(Bookmark { name, offset });
+                self.bookmarks.sort_by_key(|b| b.offset);
+            }
+            BookmarkAction::AddAt(offset) => {
+                let name = format!("0x{:X}", self.virtual_addr(offset));
+                self.bookmarks.push(Bookmark { name, offset });
+                self.bookmarks.sort_by_key(|b| b.offset);
+            }
+            BookmarkAction::Delete(i) => {
+                self.bookmarks.remove(i);
+            }
+            BookmarkAction::GoTo(offset) => {
+                self.set_cur_pos(offset - offset % self.bytes_per_row);
+                return;
+            }
+        }
+
+        if let Some(file_config) = config.files.iter_mut().find(|a| a.path == self.file.path) {
+            file_config.bookmarks = self.bookmarks.clone();
+            config.changed = true;
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn show_contents(
+        &mut self,
+        ui: &mut egui::Ui,
+        config: &mut Config,
+        settings: &Settings,
+        diff_state: &DiffState,
+        ctx: &egui::Context,
+        cursor_state: CursorState,
+        can_selection_change: bool,
+        other_views: &[OtherView],
+        closable: bool,
+    ) {
+        let font_size = settings.font_size;
+
+        let file_name = self.file.path.as_path().to_str().unwrap().to_owned();
+
+        ui.with_layout(
+            egui::Layout::left_to_right(eframe::emath::Align::Min),
+            |ui| {
+                ui.add(Fingerprint::new(&self.fingerprint).pixel_size(1.25))
+                    .on_hover_text("Fingerprint of the file's average byte values");
+
+                ui.label(
+                    egui::RichText::new(file_name)
+                        .monospace()
+                        .size(font_size)
+                        .color(Color32::LIGHT_GRAY),
+                );
+
+                if let Some(format) = &self.file.detected_format {
+                    ui.add(Badge::new(format.name).color(Color32::from_rgb(40, 80, 140)))
+                        .on_hover_text(&format.detail);
+                }
 
-                        let (lock_text, hover_text) = match self.pos_locked {
-                            true => (
-                                egui::RichText::new(egui_phosphor::regular::LOCK_SIMPLE)
-                                    .color(Color32::RED),
-                                "Unlock scroll position",
-                            ),
-                            false => (
-                                egui::RichText::new(egui_phosphor::regular::LOCK_SIMPLE_OPEN)
-                                    .color(Color32::GREEN),
-                                "Lock scroll position",
-                            ),
+                if diff_state.enabled {
+                    if let Some(count) = diff_state.file_diff_counts.get(&self.id) {
+                        let pair = diff_state
+                            .pair_diffs
+                            .iter()
+                            .position(|(id, _)| *id == self.id);
+                        let color = match (diff_state.pair_diffs.len(), pair) {
+                            (2.., Some(pair)) => settings.theme_settings.diff_pair_color(pair),
+                            _ => settings.theme_settings.diff_color.clone().into(),
                         };
-                        if ui.button(lock_text).on_hover_text(hover_text).clicked() {
-                            self.pos_locked = !self.pos_locked;
+                        ui.add(Badge::new(count.to_string()).color(color))
+                            .on_hover_text("Bytes that differ from the base file");
+                    }
+                }
+
+                if let Some(time) = self.last_reload_time {
+                    let elapsed = time.elapsed();
+                    if elapsed < RELOAD_NOTICE_DURATION {
+                        ui.add(
+                            Badge::new(format!("File reloaded: {}", self.file_name()))
+                                .color(Color32::from_rgb(40, 110, 60)),
+                        )
+                        .on_hover_text("The file changed on disk and was reloaded");
+                        if ui
+                            .small_button(egui_phosphor::regular::X)
+                            .on_hover_text("Dismiss")
+                            .clicked()
+                        {
+                            self.last_reload_time = None;
                         }
+                        ctx.request_repaint_after(RELOAD_NOTICE_DURATION - elapsed);
+                    } else {
+                        self.last_reload_time = None;
+                    }
+                }
 
-                        match self.file.endianness {
-                            Endianness::Little => {
-                                if ui
-                                    .button("LE")
-                                    .on_hover_text("Switch to big-endian")
-                                    .clicked()
-                                {
-                                    self.file.endianness = Endianness::Big;
-                                }
-                            }
-                            Endianness::Big => {
-                                if ui
-                                    .button("BE")
-                                    .on_hover_text("Switch to little-endian")
-                                    .clicked()
-                                {
-                                    self.file.endianness = Endianness::Little;
+                if let Some(template) = &self.template {
+                    let color = match template.mismatches {
+                        0 => Color32::from_rgb(40, 110, 60),
+                        _ => settings.theme_settings.template_mismatch_color.clone().into(),
+                    };
+                    ui.add(Badge::new(format!("{} mismatched", template.mismatches)).color(color))
+                        .on_hover_text(format!(
+                            "Bytes that differ from the template {} (0x{:X} bytes from 0x{:X})",
+                            template.path.display(),
+                            template.num_bytes(),
+                            template.offset
+                        ));
+                }
+
+                if !self.auto_reload && self.file.modified.load(Ordering::Relaxed) {
+                    ui.add(Badge::new("Changed on disk").color(Color32::from_rgb(140, 100, 30)))
+                        .on_hover_text("Turn on auto-reload in the ... menu to load the changes");
+                }
+
+                if let Some((first, _)) = self.timeline.samples.front() {
+                    let latest = self.timeline.samples.back().map_or(0, |(_, count)| *count);
+                    ui.add(
+                        Sparkline::new(self.timeline.samples.iter().map(|(_, count)| *count as f32))
+                            .color(settings.theme_settings.diff_color.clone().into()),
+                    )
+                    .on_hover_text(format!(
+                        "Bytes changed since the first load over the last {} reloads ({}s)\nLatest: {}",
+                        self.timeline.samples.len(),
+                        first.elapsed().as_secs(),
+                        latest
+                    ));
+                }
+
+                if let Some(group) = self.scroll_group {
+                    ui.label(
+                        egui::RichText::new(group.to_string())
+                            .color(group.color())
+                            .strong(),
+                    )
+                    .on_hover_text(format!("Scrolls with the other views in group {}", group));
+                }
+
+                if let Some(group) = self.diff_group {
+                    ui.label(
+                        egui::RichText::new(format!("diff {}", group))
+                            .color(group.color())
+                            .strong(),
+                    )
+                    .on_hover_text(format!(
+                        "Only diffed against the other views in diff group {}",
+                        group
+                    ));
+                }
+
+                let (lock_text, hover_text) = match self.pos_locked {
+                    true => (
+                        egui::RichText::new(egui_phosphor::regular::LOCK_SIMPLE)
+                            .color(Color32::RED),
+                        "Unlock scroll position",
+                    ),
+                    false => (
+                        egui::RichText::new(egui_phosphor::regular::LOCK_SIMPLE_OPEN)
+                            .color(Color32::GREEN),
+                        "Lock scroll position",
+                    ),
+                };
+                if ui.button(lock_text).on_hover_text(hover_text).clicked() {
+                    self.pos_locked = !self.pos_locked;
+                }
+
+                match self.file.endianness {
+                    Endianness::Little => {
+                        if ui
+                            .button("LE")
+                            .on_hover_text("Switch to big-endian")
+                            .clicked()
+                        {
+                            self.file.endianness = Endianness::Big;
+                        }
+                    }
+                    Endianness::Big => {
+                        if ui
+                            .button("BE")
+                            .on_hover_text("Switch to little-endian")
+                            .clicked()
+                        {
+                            self.file.endianness = Endianness::Little;
+                        }
+                    }
+                }
+
+                ui.menu_button("...", |ui| {
+                    ui.checkbox(&mut self.show_selection_info, "Selection info");
+                    ui.checkbox(&mut self.show_cursor_info, "Cursor info");
+                    ui.checkbox(&mut self.dv.show, "Data viewer");
+                    ui.checkbox(&mut self.sv.show, "String viewer");
+                    ui.checkbox(&mut self.mt.show, "Map tool");
+                    ui.checkbox(&mut self.bp.show, "Bookmarks");
+                    if ui
+                        .checkbox(&mut self.auto_reload, "Auto-reload")
+                        .on_hover_text("Reload the file when it changes on disk")
+                        .changed()
+                    {
+                        if let Some(file_config) =
+                            config.files.iter_mut().find(|a| a.path == self.file.path)
+                        {
+                            file_config.auto_reload = (!self.auto_reload).then_some(false);
+                            config.changed = true;
+                        }
+                    }
+                    ui.menu_button("Sort rows", |ui| {
+                        let mut sorted = self.row_sort.is_some();
+                        let mut changed = ui.checkbox(&mut sorted, "Sort rows by column").changed();
+                        if changed {
+                            self.row_sort = sorted.then_some(RowSort {
+                                column: 0,
+                                descending: false,
+                            });
+                        }
+
+                        if let Some(sort) = self.row_sort.as_mut() {
+                            ui.horizontal(|ui| {
+                                ui.label("Column");
+                                changed |= ui
+                                    .add(
+                                        egui::DragValue::new(&mut sort.column)
+                                            .clamp_range(0..=self.bytes_per_row - 1),
+                                    )
+                                    .changed();
+                            });
+                            changed |= ui.checkbox(&mut sort.descending, "Descending").changed();
+                        }
+
+                        if changed {
+                            self.update_row_sort();
+                        }
+                    });
+                    ui.menu_button("Scroll group", |ui| {
+                        ui.radio_value(&mut self.scroll_group, None, "None");
+                        for group in ViewGroup::ALL {
+                            ui.radio_value(&mut self.scroll_group, Some(group), group.to_string());
+                        }
+                    });
+                    ui.menu_button("Diff group", |ui| {
+                        let old_group = self.diff_group;
+                        ui.radio_value(&mut self.diff_group, None, "None");
+                        for group in ViewGroup::ALL {
+                            ui.radio_value(&mut self.diff_group, Some(group), group.to_string());
+                        }
+                        if self.diff_group != old_group {
+                            self.actions.push(HexViewAction::DiffGroupChanged);
+                        }
+                    })
+                    .response
+                    .on_hover_text("Views in a diff group are only compared with each other");
+                    ui.menu_button("Template", |ui| {
+                        if ui
+                            .button("Compare to template...")
+                            .on_hover_text(
+                                "Highlight bytes that differ from a text file of hex nibbles and ? wildcards, starting at the selection or the start of the file",
+                            )
+                            .clicked()
+                        {
+                            if let Some(path) = rfd::FileDialog::new().pick_file() {
+                                let offset = match self.selection.state {
+                                    HexViewSelectionState::None => 0,
+                                    _ => self.selection.start(),
+                                };
+                                match Template::load(&path, offset, &self.file.data) {
+                                    Ok(template) => self.template = Some(template),
+                                    Err(e) => log::error!("Failed to load template: {}", e),
                                 }
                             }
+                            ui.close_menu();
                         }
+                        if ui
+                            .add_enabled(self.template.is_some(), egui::Button::new("Clear"))
+                            .clicked()
+                        {
+                            self.template = None;
+                            ui.close_menu();
+                        }
+                    });
+                    ui.menu_button("Byte colors", |ui| {
+                        for coloring in ByteColoring::ALL {
+                            ui.radio_value(&mut self.byte_coloring, coloring, coloring.to_string());
+                        }
+                    });
+                    ui.checkbox(&mut self.bit_view, "Bit view");
+                    ui.menu_button("Float array view", |ui| {
+                        ui.radio_value(&mut self.float_view, None, "Off");
+                        ui.radio_value(&mut self.float_view, Some(FloatViewKind::F32), "f32");
+                        ui.radio_value(&mut self.float_view, Some(FloatViewKind::F64), "f64");
+                    });
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label("Bytes per row");
+                        let selected_text = match self.bytes_per_row_override {
+                            Some(bytes_per_row) => bytes_per_row.to_string(),
+                            None => format!("Default ({})", settings.bytes_per_row),
+                        };
+                        let mut bytes_per_row_override = self.bytes_per_row_override;
+                        egui::ComboBox::from_id_source(format!("bytes_per_row_{}", self.id))
+                            .selected_text(selected_text)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut bytes_per_row_override, None, "Default");
+                                for bytes_per_row in (2..=6).map(|shift| 1 << shift) {
+                                    if Settings::validate_row_layout(
+                                        bytes_per_row,
+                                        settings.byte_grouping,
+                                    )
+                                    .is_ok()
+                                    {
+                                        ui.selectable_value(
+                                            &mut bytes_per_row_override,
+                                            Some(bytes_per_row),
+                                            bytes_per_row.to_string(),
+                                        );
+                                    }
+                                }
+                            });
 
-                        ui.menu_button("...", |ui| {
-                            ui.checkbox(&mut self.show_selection_info, "Selection info");
-                            ui.checkbox(&mut self.show_cursor_info, "Cursor info");
-                            ui.checkbox(&mut self.dv.show, "Data viewer");
-                            ui.checkbox(&mut self.sv.show, "String viewer");
-                            ui.checkbox(&mut self.mt.show, "Map tool");
-                        });
-
-                        if ui.button("X").on_hover_text("Close").clicked() {
-                            self.closed = true;
+                        if bytes_per_row_override != self.bytes_per_row_override {
+                            self.bytes_per_row_override = bytes_per_row_override;
+                            self.set_bytes_per_row(
+                                bytes_per_row_override.unwrap_or(settings.bytes_per_row),
+                            );
 
-                            // Remove file from the config if it's closed.
-                            if let Some(pos) =
-                                config.files.iter().position(|a| a.path == self.file.path)
+                            if let Some(file_config) =
+                                config.files.iter_mut().find(|a| a.path == self.file.path)
                             {
-                                config.files.remove(pos);
+                                file_config.bytes_per_row = bytes_per_row_override;
                                 config.changed = true;
                             }
                         }
-                    },
-                );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Virtual base");
+                        let res = ui.add(
+                            egui::TextEdit::singleline(&mut self.virtual_base_input)
+                                .desired_width(100.0)
+                                .hint_text("None"),
+                        );
+                        if res.lost_focus() {
+                            let input = self.virtual_base_input.trim();
+                            let virtual_base = match input.is_empty() {
+                                true => Ok(None),
+                                false => parse_int::parse::<usize>(input).map(Some),
+                            };
+                            match virtual_base {
+                                Ok(virtual_base) => {
+                                    self.set_virtual_base(virtual_base);
+
+                                    if let Some(file_config) =
+                                        config.files.iter_mut().find(|a| a.path == self.file.path)
+                                    {
+                                        file_config.virtual_base = virtual_base;
+                                        config.changed = true;
+                                    }
+                                }
+                                Err(_) => {
+                                    self.virtual_base_error =
+                                        Some(format!("Invalid address \"{}\"", input))
+                                }
+                            }
+                        }
+                    });
+                    if let Some(error) = &self.virtual_base_error {
+                        ui.colored_label(ui.visuals().error_fg_color, error);
+                    }
+                    ui.label(format!(
+                        "Detected byte grouping: {}",
+                        self.detected_byte_grouping
+                    ));
+                    egui::CollapsingHeader::new("Session stats").show(ui, |ui| {
+                        self.show_session_stats(ui);
+                    });
+                });
 
-                ui.with_layout(
-                    egui::Layout::left_to_right(eframe::emath::Align::Min),
-                    |ui: &mut egui::Ui| {
-                        ui.vertical(|ui| {
-                            self.show_hex_grid(
-                                diff_state,
-                                ctx,
-                                ui,
-                                cursor_state,
-                                can_selection_change,
-                                font_size,
-                                settings.byte_grouping.into(),
-                                settings.theme_settings.clone(),
-                            );
+                if closable && ui.button("X").on_hover_text("Close").clicked() {
+                    self.closed = true;
 
-                            if self.show_selection_info {
-                                let selection_text = match self.selection.state {
-                                    HexViewSelectionState::None => "No selection".to_owned(),
-                                    _ => {
-                                        let start = self.selection.start();
-                                        let end = self.selection.end();
-                                        let length = end - start + 1;
+                    // Remove file from the config if it's closed.
+                    if let Some(pos) = config.files.iter().position(|a| a.path == self.file.path) {
+                        config.files.remove(pos);
+                        config.changed = true;
+                    }
+                }
+            },
+        );
+
+        ui.with_layout(
+            egui::Layout::left_to_right(eframe::emath::Align::Min),
+            |ui: &mut egui::Ui| {
+                ui.vertical(|ui| {
+                    ui.horizontal(|ui| {
+                        let grid_rect = self.show_hex_grid(
+                            diff_state,
+                            ctx,
+                            ui,
+                            cursor_state,
+                            can_selection_change,
+                            font_size,
+                            settings,
+                            other_views,
+                        );
+                        self.show_scrollbar_overlay(ui, diff_state, settings, grid_rect.height());
+                    });
 
-                                        let map_entry = match self.mt.map_file {
-                                            Some(ref map_file) => {
-                                                map_file.get_entry(start, end + 1)
-                                            }
-                                            None => None,
-                                        };
+                    if self.show_selection_info {
+                        let selection_text = match self.selection.state {
+                            HexViewSelectionState::None => "No selection".to_owned(),
+                            _ => {
+                                let start = self.selection.start();
+                                let end = self.selection.end();
+                                let length = self.selection.num_bytes(self.bytes_per_row);
+
+                                let map_entry = match self.mt.map_file {
+                                    Some(ref map_file) => map_file.get_entry(start, end + 1),
+                                    None => None,
+                                };
 
-                                        let beginning = match length {
-                                            1 => {
-                                                format!("Selection: 0x{:X}", start)
-                                            }
-                                            _ => {
-                                                format!(
-                                                    "Selection: 0x{:X} - 0x{:X} (len 0x{:X})",
-                                                    start, end, length
-                                                )
-                                            }
-                                        };
-
-                                        match map_entry {
-                                            Some(entry) => {
-                                                format!(
-                                                    "{} ({} + 0x{})",
-                                                    beginning,
-                                                    entry.symbol_name,
-                                                    start - entry.symbol_vrom
-                                                )
-                                            }
-                                            None => beginning,
-                                        }
+                                let beginning = match length {
+                                    1 => {
+                                        format!("Selection: 0x{:X}", self.virtual_addr(start))
+                                    }
+                                    _ => {
+                                        format!(
+                                            "Selection: 0x{:X} - 0x{:X} (len 0x{:X})",
+                                            self.virtual_addr(start),
+                                            self.virtual_addr(end),
+                                            length
+                                        )
                                     }
                                 };
-                                ui.label(egui::RichText::new(selection_text).monospace());
-                            }
 
-                            if self.show_cursor_info {
-                                let hover_text = match self.cursor_pos {
-                                    Some(pos) => {
-                                        let map_entry = match self.mt.map_file {
-                                            Some(ref map_file) => map_file.get_entry(pos, pos + 1),
-                                            None => None,
-                                        };
-
-                                        match map_entry {
-                                            Some(entry) => {
-                                                format!(
-                                                    "Cursor: 0x{:X} ({} + 0x{})",
-                                                    pos,
-                                                    entry.symbol_name,
-                                                    pos - entry.symbol_vrom
-                                                )
-                                            }
-                                            None => format!("Cursor: 0x{:X}", pos),
-                                        }
+                                let beginning = match self.selection.bit_range() {
+                                    Some((high, low)) => {
+                                        let value = self.file.data.get(start).map_or(0, |byte| {
+                                            (*byte as u32 >> low) & ((1 << (high - low + 1)) - 1)
+                                        });
+                                        format!(
+                                            "{}, bits {}:{} = {} (0x{:X})",
+                                            beginning, high, low, value, value
+                                        )
                                     }
-                                    None => "Not hovering".to_owned(),
+                                    None => beginning,
                                 };
-                                ui.label(egui::RichText::new(hover_text).monospace());
+
+                                match map_entry {
+                                    Some(entry) => {
+                                        format!(
+                                            "{} ({} + 0x{})",
+                                            beginning,
+                                            entry.symbol_name,
+                                            start - entry.symbol_vrom
+                                        )
+                                    }
+                                    None => beginning,
+                                }
                             }
-                        });
+                        };
+                        ui.label(egui::RichText::new(selection_text).monospace());
+                    }
 
-                        ui.with_layout(egui::Layout::top_down(eframe::emath::Align::Min), |ui| {
-                            self.dv.display(
-                                ui,
-                                self.id,
-                                self.get_selected_bytes(),
-                                self.file.endianness,
-                            );
-                            self.sv.display(
-                                ui,
-                                self.id,
-                                self.get_selected_bytes(),
-                                self.file.endianness,
-                            );
-                            self.mt.display(ui);
-                        });
-                    },
-                );
-            });
+                    if self.show_cursor_info {
+                        let hover_text = match self.cursor_pos {
+                            Some(pos) => {
+                                let map_entry = match self.mt.map_file {
+                                    Some(ref map_file) => map_file.get_entry(pos, pos + 1),
+                                    None => None,
+                                };
+
+                                match map_entry {
+                                    Some(entry) => {
+                                        format!(
+                                            "Cursor: 0x{:X} ({} + 0x{})",
+                                            self.virtual_addr(pos),
+                                            entry.symbol_name,
+                                            pos - entry.symbol_vrom
+                                        )
+                                    }
+                                    None => {
+                                        format!("Cursor: 0x{:X}", self.virtual_addr(pos))
+                                    }
+                                }
+                            }
+                            None => "Not hovering".to_owned(),
+                        };
+                        ui.label(egui::RichText::new(hover_text).monospace());
+                    }
+                });
+
+                ui.with_layout(egui::Layout::top_down(eframe::emath::Align::Min), |ui| {
+                    self.dv.display(
+                        ui,
+                        self.id,
+                        self.get_selected_bytes(),
+                        self.selection.bit_range(),
+                        self.file.endianness,
+                        other_views,
+                        config,
+                    );
+                    let clicked_string = self.sv.display(
+                        ui,
+                        self.id,
+                        self.get_selected_bytes(),
+                        &self.get_cur_bytes(),
+                        self.cur_pos,
+                        self.virtual_base.unwrap_or(0),
+                    );
+                    if let Some(range) = clicked_string {
+                        self.selection
+                            .begin(*range.start(), HexViewSelectionSide::Ascii);
+                        self.selection.finalize(*range.end());
+                    }
+                    if let Some(offset) = self.mt.display(
+                        ui,
+                        self.file.data.len(),
+                        &self.bookmarks,
+                        self.virtual_base.unwrap_or(0),
+                    ) {
+                        self.set_cur_pos(offset - offset % self.bytes_per_row);
+                    }
+                    self.mt.display_new_symbol_dialog(ui.ctx(), self.id);
+
+                    let action = self.bp.display(
+                        ui,
+                        self.id,
+                        &self.bookmarks,
+                        self.virtual_base.unwrap_or(0),
+                    );
+                    if let Some(action) = action.or_else(|| self.pending_bookmark_action.take()) {
+                        self.handle_bookmark_action(action, config);
+                    }
+
+                    if let Some(row_zoom) = &self.row_zoom {
+                        let keep_open = row_zoom.display(
+                            ui.ctx(),
+                            self.id,
+                            &self.file.data,
+                            self.bytes_per_row,
+                            self.file.endianness,
+                            self.virtual_base.unwrap_or(0),
+                            font_size,
+                        );
+                        if !keep_open {
+                            self.row_zoom = None;
+                        }
+                    }
+                });
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view_with(data: &[u8]) -> HexView {
+        HexView::new(BinFile::from_bytes("test.bin", data.to_vec()), 0, 16)
+    }
+
+    #[test]
+    fn fill_is_undone_and_redone_in_one_step() {
+        let mut hv = view_with(&[1, 2, 3, 4, 5]);
+        hv.write_byte(0, 0xAA);
+        hv.selection.begin(1, HexViewSelectionSide::Hex);
+        hv.selection.finalize(3);
+        hv.fill_selection(0);
+        assert_eq!(hv.file.data, vec![0xAA, 0, 0, 0, 5]);
+
+        hv.undo_edit();
+        assert_eq!(hv.file.data, vec![0xAA, 2, 3, 4, 5]);
+        assert_eq!(hv.dirty_bytes, HashSet::from([0]));
+
+        hv.redo_edit();
+        assert_eq!(hv.file.data, vec![0xAA, 0, 0, 0, 5]);
+        assert_eq!(hv.dirty_bytes, HashSet::from([0, 1, 2, 3]));
+
+        hv.undo_edit();
+        hv.undo_edit();
+        assert_eq!(hv.file.data, vec![1, 2, 3, 4, 5]);
+        assert!(hv.dirty_bytes.is_empty());
+    }
+
+    #[test]
+    fn endianness_swap_is_undone_in_one_step() {
+        let mut hv = view_with(&[1, 2, 3, 4]);
+        hv.selection.begin(0, HexViewSelectionSide::Hex);
+        hv.selection.finalize(3);
+        hv.swap_selection_endianness().unwrap();
+        assert_eq!(hv.file.data, vec![4, 3, 2, 1]);
+
+        hv.undo_edit();
+        assert_eq!(hv.file.data, vec![1, 2, 3, 4]);
+        assert!(hv.edit_history.is_empty());
     }
 }
@@ -0,0 +1,159 @@
+use std::ops::RangeInclusive;
+
+use anyhow::Error;
+use eframe::egui;
+
+/// Parses a range from its start and either an inclusive end offset or `+length`
+fn parse_range(start: &str, end: &str) -> Result<RangeInclusive<usize>, Error> {
+    let start: usize = parse_int::parse(start.trim())
+        .map_err(|_| Error::msg(format!("Invalid start offset \"{}\"", start.trim())))?;
+
+    let end = end.trim();
+    let end = match end.strip_prefix('+') {
+        Some(len) => {
+            let len: usize = parse_int::parse(len.trim())
+                .map_err(|_| Error::msg(format!("Invalid length \"{}\"", len.trim())))?;
+            if len == 0 {
+                return Err(Error::msg("The length must be at least 1"));
+            }
+            start
+                .checked_add(len - 1)
+                .ok_or_else(|| Error::msg("The range is too large"))?
+        }
+        None => parse_int::parse(end)
+            .map_err(|_| Error::msg(format!("Invalid end offset \"{}\"", end)))?,
+    };
+
+    if end < start {
+        return Err(Error::msg("The end offset is before the start"));
+    }
+    Ok(start..=end)
+}
+
+/// Lists the ranges excluded from the diff and lets new ones be added
+#[derive(Default)]
+pub struct IgnoreMaskPanel {
+    pub show: bool,
+    start: String,
+    end: String,
+    error: Option<String>,
+}
+
+impl IgnoreMaskPanel {
+    /// Returns whether the ranges were changed
+    pub fn display(
+        &mut self,
+        ctx: &egui::Context,
+        ranges: &mut Vec<RangeInclusive<usize>>,
+        selection: Vec<RangeInclusive<usize>>,
+    ) -> bool {
+        let mut open = self.show;
+        let mut changed = false;
+
+        egui::Window::new("Diff ignore ranges")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label("Differences inside these ranges are not highlighted");
+
+                egui::Grid::new("diff_ignore_add")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Start");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.start)
+                                .hint_text("0x100")
+                                .desired_width(120.0),
+                        );
+                        ui.end_row();
+
+                        ui.label("End");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.end)
+                                .hint_text("0x10F or +0x10")
+                                .desired_width(120.0),
+                        )
+                        .on_hover_text("An inclusive end offset, or + and a length");
+                        ui.end_row();
+                    });
+
+                ui.horizontal(|ui| {
+                    if ui.button("Add").clicked() {
+                        match parse_range(&self.start, &self.end) {
+                            Ok(range) => {
+                                ranges.push(range);
+                                self.start.clear();
+                                self.end.clear();
+                                self.error = None;
+                                changed = true;
+                            }
+                            Err(e) => self.error = Some(e.to_string()),
+                        }
+                    }
+
+                    // A block selection adds one range per row
+                    let selection_text = match selection.as_slice() {
+                        [] => "Add selection".to_owned(),
+                        [range] => format!(
+                            "Add selection (0x{:X} - 0x{:X})",
+                            range.start(),
+                            range.end()
+                        ),
+                        rows => format!("Add selection ({} rows)", rows.len()),
+                    };
+                    if ui
+                        .add_enabled(!selection.is_empty(), egui::Button::new(selection_text))
+                        .clicked()
+                    {
+                        ranges.extend(selection);
+                        changed = true;
+                    }
+                });
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+
+                ui.separator();
+
+                if ranges.is_empty() {
+                    ui.label("No ranges are ignored");
+                    return;
+                }
+
+                let mut remove = None;
+                egui::Grid::new("diff_ignore_ranges")
+                    .striped(true)
+                    .num_columns(3)
+                    .show(ui, |ui| {
+                        for (i, range) in ranges.iter().enumerate() {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "0x{:X} - 0x{:X}",
+                                    range.start(),
+                                    range.end()
+                                ))
+                                .monospace(),
+                            );
+                            let len = range.end().saturating_sub(*range.start()).saturating_add(1);
+                            ui.label(format!("{} bytes", len));
+                            if ui
+                                .button(egui_phosphor::regular::TRASH)
+                                .on_hover_text("Stop ignoring this range")
+                                .clicked()
+                            {
+                                remove = Some(i);
+                            }
+                            ui.end_row();
+                        }
+                    });
+
+                if let Some(i) = remove {
+                    ranges.remove(i);
+                    changed = true;
+                }
+            });
+
+        self.show = open;
+        changed
+    }
+}
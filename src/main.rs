@@ -1,15 +1,33 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
+mod alignment_tool;
 mod app;
 mod bin_file;
+mod bit_field;
+mod bookmarks;
+mod byte_colors;
+mod cbor;
+mod comments;
 mod config;
 mod data_viewer;
 mod diff_state;
+mod export;
+mod file_format;
+mod goto;
+mod hex_receiver;
 mod hex_view;
+mod ignore_mask;
 mod map_file;
 mod map_tool;
+mod padding_tool;
+mod patch;
+mod row_zoom;
+mod run_summary;
+mod search;
 mod settings;
 mod string_viewer;
+mod template;
+mod watch;
 mod watcher;
 mod widget;
 
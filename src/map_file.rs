@@ -1,24 +1,19 @@
 use std::{
-    path::PathBuf,
+    fmt::Write,
+    path::{Path, PathBuf},
     sync::{atomic::AtomicBool, Arc},
 };
 
 use anyhow::Error;
 use iset::IntervalMap;
 
-use crate::watcher::create_watcher;
+use crate::watcher::{create_watcher, FileWatcher};
 
 #[derive(Clone, Debug)]
 pub struct MapFileEntry {
     pub seg_name: String,
-    pub seg_vram: u64,
-    pub seg_vrom: u64,
-    pub seg_size: u64,
     pub file_path: PathBuf,
     pub file_section_type: String,
-    pub file_vram: u64,
-    pub file_vrom: Option<u64>,
-    pub file_size: u64,
     pub symbol_name: String,
     pub symbol_vram: usize,
     pub symbol_vrom: usize,
@@ -29,8 +24,12 @@ pub struct MapFileEntry {
 pub struct MapFile {
     pub path: PathBuf,
     pub data: IntervalMap<usize, MapFileEntry>,
-    watcher: Option<notify::RecommendedWatcher>,
+    /// Symbols from before the last reload, so the reload can be undone
+    previous_data: Option<IntervalMap<usize, MapFileEntry>>,
+    watcher: Option<FileWatcher>,
     pub modified: Arc<AtomicBool>,
+    /// Symbols have been added since the map was loaded or last saved
+    pub unsaved: bool,
 }
 
 impl MapFile {
@@ -44,7 +43,7 @@ impl MapFile {
             ..Default::default()
         };
 
-        match create_watcher(path, ret.modified.clone()).map_err(anyhow::Error::new) {
+        match create_watcher(path, ret.modified.clone(), None).map_err(anyhow::Error::new) {
             Ok(watcher) => {
                 ret.watcher = Some(watcher);
             }
@@ -55,11 +54,91 @@ impl MapFile {
     }
 
     pub fn reload(&mut self) -> Result<(), Error> {
-        self.data = collect_data(self.path.clone());
+        let data = collect_data(self.path.clone());
+        self.previous_data = Some(std::mem::replace(&mut self.data, data));
 
         Ok(())
     }
 
+    pub fn can_undo_reload(&self) -> bool {
+        self.previous_data.is_some()
+    }
+
+    pub fn undo_reload(&mut self) {
+        if let Some(data) = self.previous_data.take() {
+            self.data = data;
+        }
+    }
+
+    /// Percentage of the first `file_len` bytes that are covered by symbols
+    pub fn coverage(&self, file_len: usize) -> f32 {
+        if file_len == 0 {
+            return 0.0;
+        }
+        self.data.covered_len(0..file_len) as f32 / file_len as f32 * 100.0
+    }
+
+    pub fn is_covered(&self, pos: usize) -> bool {
+        self.data.has_overlap(pos..pos + 1)
+    }
+
+    /// Adds a symbol covering the file offsets `start..=end`, replacing one with the same range
+    pub fn add_symbol(&mut self, name: String, start: usize, end: usize, vram: usize) {
+        let size = end - start + 1;
+        let entry = MapFileEntry {
+            seg_name: "bdiff".to_owned(),
+            file_path: PathBuf::from("bdiff"),
+            file_section_type: ".data".to_owned(),
+            symbol_name: name,
+            symbol_vram: vram,
+            symbol_vrom: start,
+            symbol_size: size,
+        };
+        self.data.insert(start..end + 1, entry);
+        self.unsaved = true;
+    }
+
+    /// Writes the symbols as a GNU ld map file that can be loaded again.
+    /// Every symbol gets its own segment, so its offset and size are kept exactly.
+    pub fn write_to_path(&mut self, path: &Path) -> Result<(), Error> {
+        let mut out = String::from("Linker script and memory map\n\n");
+
+        for (_, entry) in self.data.iter(..) {
+            let no_spaces = |s: &str, fallback: &str| match s.is_empty() {
+                true => fallback.to_owned(),
+                false => s.replace(char::is_whitespace, "_"),
+            };
+
+            writeln!(
+                out,
+                "{} 0x{:016X} 0x{:X} load address 0x{:016X}",
+                no_spaces(&entry.seg_name, "bdiff"),
+                entry.symbol_vram,
+                entry.symbol_size,
+                entry.symbol_vrom
+            )?;
+            writeln!(
+                out,
+                " {} 0x{:016X} 0x{:X} {}",
+                no_spaces(&entry.file_section_type, ".data"),
+                entry.symbol_vram,
+                entry.symbol_size,
+                no_spaces(&entry.file_path.to_string_lossy(), "bdiff")
+            )?;
+            writeln!(
+                out,
+                "                0x{:016X}                {}",
+                entry.symbol_vram,
+                no_spaces(&entry.symbol_name, "symbol")
+            )?;
+            out.push('\n');
+        }
+
+        std::fs::write(path, out)?;
+        self.unsaved = false;
+        Ok(())
+    }
+
     pub fn get_entry(&self, start: usize, end: usize) -> Option<&MapFileEntry> {
         let entries: Vec<_> = self.data.values(start..end).collect();
 
@@ -91,14 +170,8 @@ fn collect_data(path: PathBuf) -> IntervalMap<usize, MapFileEntry> {
 
                 let entry = MapFileEntry {
                     seg_name: segment.name.clone(),
-                    seg_vram: segment.vram,
-                    seg_vrom: segment.vrom,
-                    seg_size: segment.size,
                     file_path: file.filepath.clone(),
                     file_section_type: file.section_type.clone(),
-                    file_vram: file.vram,
-                    file_vrom: file.vrom,
-                    file_size: file.size,
                     symbol_name: symbol.name.clone(),
                     symbol_vram: symbol.vram as usize,
                     symbol_vrom: symbol.vrom.unwrap() as usize,
@@ -1,25 +1,149 @@
-use crate::map_file::MapFile;
+use std::path::{Path, PathBuf};
+
+use crate::{bookmarks::Bookmark, map_file::MapFile};
 use anyhow::Error;
 use eframe::egui;
 
+/// A symbol for the selection that is waiting to be named
+pub struct NewSymbol {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+    pub vram: usize,
+}
+
+/// Looks next to the file for a map with the same name and one of the suffixes,
+/// e.g. `firmware.map` for `firmware.bin` and the suffix `.map`
+pub fn find_associated_map(path: &Path, suffixes: &[String]) -> Option<PathBuf> {
+    let stem = path.file_stem()?.to_string_lossy();
+    suffixes
+        .iter()
+        .map(|suffix| path.with_file_name(format!("{}{}", stem, suffix)))
+        .find(|candidate| candidate != path && candidate.is_file())
+}
+
+/// Builds a map with a size 1 symbol for every bookmark
+pub fn bookmarks_to_map(bookmarks: &[Bookmark], virtual_base: usize) -> MapFile {
+    let mut map_file = MapFile::default();
+    for bookmark in bookmarks {
+        map_file.add_symbol(
+            bookmark.name.clone(),
+            bookmark.offset,
+            bookmark.offset,
+            virtual_base.saturating_add(bookmark.offset),
+        );
+    }
+    map_file
+}
+
 #[derive(Default)]
 pub struct MapTool {
     pub show: bool,
     pub last_status: Option<Error>,
     pub map_file: Option<MapFile>,
+    pub highlight_uncovered: bool,
+    pub new_symbol: Option<NewSymbol>,
+    /// Text the symbol list is filtered by
+    symbol_filter: String,
 }
 
+/// Most symbols listed at once, to keep the list responsive with large maps
+const MAX_LISTED_SYMBOLS: usize = 500;
+
 impl MapTool {
-    pub fn display(&mut self, ui: &mut egui::Ui) {
+    /// Start offset of the first symbol starting after the offset
+    pub fn get_next_symbol(&self, after: usize) -> Option<usize> {
+        let map_file = self.map_file.as_ref()?;
+        map_file
+            .data
+            .intervals(after..)
+            .map(|range| range.start)
+            .find(|start| *start > after)
+    }
+
+    /// Start offset of the last symbol starting before the offset
+    pub fn get_prev_symbol(&self, before: usize) -> Option<usize> {
+        let map_file = self.map_file.as_ref()?;
+        if before == 0 {
+            return None;
+        }
+        map_file
+            .data
+            .intervals(..before)
+            .map(|range| range.start)
+            .filter(|start| *start < before)
+            .last()
+    }
+
+    /// Lists the symbols matching the filter. Returns the offset of the one clicked, if any.
+    fn display_symbol_list(&mut self, ui: &mut egui::Ui) -> Option<usize> {
+        let map_file = self.map_file.as_ref()?;
+        let mut clicked = None;
+
+        ui.add(
+            egui::TextEdit::singleline(&mut self.symbol_filter)
+                .hint_text("Filter symbols")
+                .desired_width(200.0),
+        );
+
+        let filter = self.symbol_filter.to_lowercase();
+        let matches: Vec<_> = map_file
+            .data
+            .iter(..)
+            .filter(|(_, entry)| entry.symbol_name.to_lowercase().contains(&filter))
+            .take(MAX_LISTED_SYMBOLS + 1)
+            .collect();
+
+        egui::ScrollArea::vertical()
+            .id_source("map_symbol_list")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for (range, entry) in matches.iter().take(MAX_LISTED_SYMBOLS) {
+                    let text =
+                        egui::RichText::new(format!("0x{:08X} {}", range.start, entry.symbol_name))
+                            .monospace();
+                    if ui
+                        .selectable_label(false, text)
+                        .on_hover_text(format!("{} bytes", entry.symbol_size))
+                        .clicked()
+                    {
+                        clicked = Some(range.start);
+                    }
+                }
+            });
+
+        if matches.len() > MAX_LISTED_SYMBOLS {
+            ui.label(format!("Showing the first {} matches", MAX_LISTED_SYMBOLS));
+        }
+
+        clicked
+    }
+
+    /// Returns the offset of the symbol clicked in the symbol list, if any
+    pub fn display(
+        &mut self,
+        ui: &mut egui::Ui,
+        file_len: usize,
+        bookmarks: &[Bookmark],
+        virtual_base: usize,
+    ) -> Option<usize> {
         if !self.show {
-            return;
+            return None;
         }
 
+        let mut clicked_symbol = None;
+
         ui.group(|ui| {
             ui.with_layout(egui::Layout::top_down(eframe::emath::Align::Min), |ui| {
-                ui.add(egui::Label::new(
-                    egui::RichText::new("Map File").monospace(),
-                ));
+                ui.horizontal(|ui| {
+                    ui.add(egui::Label::new(
+                        egui::RichText::new("Map File").monospace(),
+                    ));
+
+                    if let Some(map_file) = &self.map_file {
+                        ui.label(format!("Coverage: {:.0}%", map_file.coverage(file_len)));
+                    }
+                });
 
                 ui.label(match self.map_file {
                     Some(ref map_file) => format!(
@@ -54,13 +178,133 @@ impl MapTool {
                         if self.map_file.is_some() && ui.button("Unload").clicked() {
                             self.map_file = None;
                         }
+
+                        if let Some(map_file) = self.map_file.as_mut() {
+                            if ui
+                                .button("Save as")
+                                .on_hover_text(
+                                    "Write the symbols, including added ones, to a map file",
+                                )
+                                .clicked()
+                            {
+                                if let Some(path) = rfd::FileDialog::new()
+                                    .set_file_name(
+                                        map_file
+                                            .path
+                                            .file_name()
+                                            .unwrap_or_default()
+                                            .to_string_lossy(),
+                                    )
+                                    .save_file()
+                                {
+                                    if let Err(e) = map_file.write_to_path(&path) {
+                                        log::error!("Failed to save map file: {}", e);
+                                    }
+                                }
+                            }
+
+                            if ui
+                                .add_enabled(
+                                    map_file.can_undo_reload(),
+                                    egui::Button::new("Undo map reload"),
+                                )
+                                .on_hover_text("Restore the symbols from before the last reload")
+                                .clicked()
+                            {
+                                map_file.undo_reload();
+                            }
+                        }
+
+                        if ui
+                            .add_enabled(
+                                !bookmarks.is_empty(),
+                                egui::Button::new("Export bookmarks as map"),
+                            )
+                            .on_hover_text("Write the bookmarks to a map file as size 1 symbols")
+                            .clicked()
+                        {
+                            if let Some(path) = rfd::FileDialog::new()
+                                .set_file_name("bookmarks.map")
+                                .save_file()
+                            {
+                                if let Err(e) =
+                                    bookmarks_to_map(bookmarks, virtual_base).write_to_path(&path)
+                                {
+                                    log::error!("Failed to export bookmarks: {}", e);
+                                }
+                            }
+                        }
                     },
                 );
+
+                if let Some(map_file) = &self.map_file {
+                    if map_file.unsaved {
+                        ui.label("Added symbols are not saved yet");
+                    }
+                    ui.checkbox(&mut self.highlight_uncovered, "Highlight uncovered bytes");
+                }
+
+                egui::CollapsingHeader::new("Symbols")
+                    .id_source("map_symbols")
+                    .show(ui, |ui| {
+                        clicked_symbol = self.display_symbol_list(ui);
+                    });
             });
         });
+
+        clicked_symbol
     }
 
-    pub fn load_file(&mut self, path: &std::path::Path) {
+    /// Asks for the name of the symbol being added from the selection
+    pub fn display_new_symbol_dialog(&mut self, ctx: &egui::Context, id: usize) {
+        let (Some(new_symbol), Some(map_file)) = (self.new_symbol.as_mut(), self.map_file.as_mut())
+        else {
+            self.new_symbol = None;
+            return;
+        };
+
+        let mut open = true;
+        let mut added = false;
+        egui::Window::new("Add map symbol")
+            .id(egui::Id::new(format!("new_symbol_{}", id)))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "0x{:X} - 0x{:X} ({} bytes)",
+                    new_symbol.start,
+                    new_symbol.end,
+                    new_symbol.end - new_symbol.start + 1
+                ));
+
+                ui.add(egui::TextEdit::singleline(&mut new_symbol.name).hint_text("Symbol name"))
+                    .request_focus();
+
+                let valid =
+                    !new_symbol.name.is_empty() && !new_symbol.name.contains(char::is_whitespace);
+                if ui
+                    .add_enabled(valid, egui::Button::new("Add"))
+                    .on_disabled_hover_text("Symbol names can't be empty or contain spaces")
+                    .clicked()
+                    || (valid && ctx.input(|i| i.key_pressed(egui::Key::Enter)))
+                {
+                    map_file.add_symbol(
+                        new_symbol.name.clone(),
+                        new_symbol.start,
+                        new_symbol.end,
+                        new_symbol.vram,
+                    );
+                    added = true;
+                }
+            });
+
+        if added || !open {
+            self.new_symbol = None;
+        }
+    }
+
+    pub fn load_file(&mut self, path: &Path) {
         let mf = MapFile::from_path(path.to_owned());
 
         match mf {
@@ -74,3 +318,42 @@ impl MapTool {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exported_bookmarks_load_back_as_size_1_symbols() {
+        let bookmarks = vec![
+            Bookmark {
+                name: "header".to_owned(),
+                offset: 0x10,
+            },
+            Bookmark {
+                name: "entry point".to_owned(),
+                offset: 0x40,
+            },
+        ];
+        let path = std::env::temp_dir().join(format!("bdiff_bookmarks_{}.map", std::process::id()));
+
+        bookmarks_to_map(&bookmarks, 0x8000_0000)
+            .write_to_path(&path)
+            .unwrap();
+        let loaded = MapFile::from_path(path.clone()).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let symbols: Vec<_> = loaded
+            .data
+            .iter(..)
+            .map(|(range, entry)| (range, entry.symbol_name.clone(), entry.symbol_vram))
+            .collect();
+        assert_eq!(
+            symbols,
+            vec![
+                (0x10..0x11, "header".to_owned(), 0x8000_0010),
+                (0x40..0x41, "entry_point".to_owned(), 0x8000_0040),
+            ]
+        );
+    }
+}
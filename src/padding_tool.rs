@@ -0,0 +1,269 @@
+use std::fmt;
+
+use eframe::egui;
+
+use crate::hex_view::{hex_view_combo, HexView};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PaddingKind {
+    Leading,
+    Trailing,
+    Interior,
+}
+
+impl fmt::Display for PaddingKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Leading => "Leading",
+            Self::Trailing => "Trailing",
+            Self::Interior => "Interior",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PaddingRegion {
+    pub start: usize,
+    pub len: usize,
+    pub kind: PaddingKind,
+}
+
+/// Finds the null bytes at the start and end of the data, and aligned runs of at least
+/// `threshold` null bytes in between
+pub fn find_padding(data: &[u8], threshold: usize, alignment: usize) -> Vec<PaddingRegion> {
+    let mut regions = Vec::new();
+
+    let leading = data.iter().take_while(|b| **b == 0).count();
+    if leading == data.len() {
+        if leading > 0 {
+            regions.push(PaddingRegion {
+                start: 0,
+                len: leading,
+                kind: PaddingKind::Leading,
+            });
+        }
+        return regions;
+    }
+    let trailing = data.iter().rev().take_while(|b| **b == 0).count();
+
+    if leading > 0 {
+        regions.push(PaddingRegion {
+            start: 0,
+            len: leading,
+            kind: PaddingKind::Leading,
+        });
+    }
+
+    let alignment = alignment.max(1);
+    let interior_end = data.len() - trailing;
+    let mut pos = leading;
+    while pos < interior_end {
+        if data[pos] != 0 {
+            pos += 1;
+            continue;
+        }
+        let run_start = pos;
+        while pos < interior_end && data[pos] == 0 {
+            pos += 1;
+        }
+
+        // Only count the part of the run that covers whole aligned blocks
+        let start = run_start.next_multiple_of(alignment);
+        let end = pos - pos % alignment;
+        if end > start && end - start >= threshold.max(1) {
+            regions.push(PaddingRegion {
+                start,
+                len: end - start,
+                kind: PaddingKind::Interior,
+            });
+        }
+    }
+
+    if trailing > 0 {
+        regions.push(PaddingRegion {
+            start: interior_end,
+            len: trailing,
+            kind: PaddingKind::Trailing,
+        });
+    }
+
+    regions
+}
+
+/// Padding found in a view's data
+struct FoundPadding {
+    view_id: usize,
+    data_generation: usize,
+    regions: Vec<PaddingRegion>,
+}
+
+pub struct PaddingTool {
+    pub show: bool,
+    file: Option<usize>,
+    threshold: usize,
+    alignment: usize,
+    results: Option<FoundPadding>,
+}
+
+impl Default for PaddingTool {
+    fn default() -> Self {
+        Self {
+            show: false,
+            file: None,
+            threshold: 0x100,
+            alignment: 0x10,
+            results: None,
+        }
+    }
+}
+
+impl PaddingTool {
+    pub fn display(&mut self, ctx: &egui::Context, hex_views: &mut [HexView]) {
+        let mut open = self.show;
+
+        egui::Window::new("Detect padding")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                hex_view_combo(ui, "File", &mut self.file, hex_views);
+
+                egui::Grid::new("padding_options")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Minimum interior run");
+                        ui.add(
+                            egui::DragValue::new(&mut self.threshold)
+                                .clamp_range(1..=0x1000000)
+                                .hexadecimal(1, false, true),
+                        );
+                        ui.end_row();
+
+                        ui.label("Alignment");
+                        ui.add(
+                            egui::DragValue::new(&mut self.alignment)
+                                .clamp_range(1..=0x10000)
+                                .hexadecimal(1, false, true),
+                        );
+                        ui.end_row();
+                    });
+
+                let hv = self
+                    .file
+                    .and_then(|id| hex_views.iter().position(|hv| hv.id == id));
+
+                // Drop the results once another file is picked or the data changes
+                if self.results.as_ref().is_some_and(|found| {
+                    hv.is_none_or(|i| {
+                        found.view_id != hex_views[i].id
+                            || found.data_generation != hex_views[i].data_generation
+                    })
+                }) {
+                    self.results = None;
+                }
+
+                if ui
+                    .add_enabled(hv.is_some(), egui::Button::new("Detect"))
+                    .clicked()
+                {
+                    let hv = &hex_views[hv.unwrap()];
+                    self.results = Some(FoundPadding {
+                        view_id: hv.id,
+                        data_generation: hv.data_generation,
+                        regions: find_padding(&hv.file.data, self.threshold, self.alignment),
+                    });
+                }
+
+                let (Some(hv), Some(found)) = (hv, &self.results) else {
+                    return;
+                };
+                let results = &found.regions;
+                if results.is_empty() {
+                    ui.label("No padding found");
+                    return;
+                }
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("padding_results")
+                            .striped(true)
+                            .num_columns(4)
+                            .show(ui, |ui| {
+                                for region in results.iter() {
+                                    ui.label(region.kind.to_string());
+                                    ui.label(
+                                        egui::RichText::new(format!(
+                                            "0x{:X} - 0x{:X}",
+                                            region.start,
+                                            region.start + region.len - 1
+                                        ))
+                                        .monospace(),
+                                    );
+                                    ui.label(
+                                        egui::RichText::new(format!("len 0x{:X}", region.len))
+                                            .monospace(),
+                                    );
+                                    if ui.button("Go to").clicked() {
+                                        let hv = &mut hex_views[hv];
+                                        hv.set_cur_pos(
+                                            region.start - region.start % hv.bytes_per_row,
+                                        );
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        self.show = open;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regions(
+        data: &[u8],
+        threshold: usize,
+        alignment: usize,
+    ) -> Vec<(usize, usize, PaddingKind)> {
+        find_padding(data, threshold, alignment)
+            .into_iter()
+            .map(|region| (region.start, region.len, region.kind))
+            .collect()
+    }
+
+    #[test]
+    fn find_padding_treats_all_zero_data_as_leading() {
+        assert_eq!(regions(&[0; 8], 1, 1), vec![(0, 8, PaddingKind::Leading)]);
+        assert_eq!(regions(&[], 1, 1), vec![]);
+    }
+
+    #[test]
+    fn find_padding_finds_leading_and_trailing_runs() {
+        let data = [0, 0, 1, 2, 0, 3, 0, 0, 0];
+        assert_eq!(
+            regions(&data, 4, 1),
+            vec![(0, 2, PaddingKind::Leading), (6, 3, PaddingKind::Trailing)]
+        );
+    }
+
+    #[test]
+    fn find_padding_only_counts_aligned_interior_blocks() {
+        // Zeros from 3 to 13, of which 4..12 covers whole blocks of 4
+        let mut data = [1; 16];
+        data[3..14].fill(0);
+        assert_eq!(regions(&data, 1, 1), vec![(3, 11, PaddingKind::Interior)]);
+        assert_eq!(regions(&data, 1, 4), vec![(4, 8, PaddingKind::Interior)]);
+        // An aligned run shorter than a block isn't padding
+        assert_eq!(regions(&data, 1, 16), vec![]);
+    }
+
+    #[test]
+    fn find_padding_skips_runs_under_the_threshold() {
+        let mut data = [1; 16];
+        data[4..8].fill(0);
+        assert_eq!(regions(&data, 4, 1), vec![(4, 4, PaddingKind::Interior)]);
+        assert_eq!(regions(&data, 5, 1), vec![]);
+    }
+}
@@ -0,0 +1,266 @@
+use anyhow::Context;
+use eframe::egui;
+
+use crate::{
+    bin_file::read_file_bytes,
+    hex_view::{hex_view_combo, HexView},
+};
+
+#[derive(Default)]
+pub struct StripDialog {
+    pub show: bool,
+    target: Option<usize>,
+    start: String,
+    end: String,
+    status: String,
+}
+
+impl StripDialog {
+    /// Returns true if a file's data was changed
+    pub fn display(&mut self, ctx: &egui::Context, hex_views: &mut [HexView]) -> bool {
+        let mut open = self.show;
+        let mut changed = false;
+
+        egui::Window::new("Strip range")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                hex_view_combo(ui, "File", &mut self.target, hex_views);
+
+                egui::Grid::new("strip_range_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Start");
+                        ui.text_edit_singleline(&mut self.start);
+                        ui.end_row();
+
+                        ui.label("End (inclusive)");
+                        ui.text_edit_singleline(&mut self.end);
+                        ui.end_row();
+                    });
+
+                let hv = self
+                    .target
+                    .and_then(|id| hex_views.iter_mut().find(|hv| hv.id == id));
+
+                ui.horizontal(|ui| {
+                    let enabled = hv.is_some();
+                    let strip = ui
+                        .add_enabled(enabled, egui::Button::new("Strip"))
+                        .clicked();
+                    let strip_and_save = ui
+                        .add_enabled(enabled, egui::Button::new("Strip and save as..."))
+                        .clicked();
+
+                    if let Some(hv) = hv {
+                        if strip || strip_and_save {
+                            match self.strip(hv) {
+                                Ok(()) => {
+                                    changed = true;
+                                    self.status = "Stripped".to_owned();
+                                }
+                                Err(e) => self.status = e.to_string(),
+                            }
+                        }
+
+                        if changed && strip_and_save {
+                            if let Some(path) = rfd::FileDialog::new()
+                                .set_file_name(hv.file_name())
+                                .save_file()
+                            {
+                                if let Err(e) = hv.file.write_to_path(path) {
+                                    self.status = e.to_string();
+                                }
+                            }
+                        }
+                    }
+                });
+
+                ui.label(&self.status);
+            });
+
+        self.show = open;
+        changed
+    }
+
+    fn strip(&self, hv: &mut HexView) -> Result<(), anyhow::Error> {
+        let start: usize = parse_int::parse(self.start.trim())
+            .map_err(|_| anyhow::Error::msg("Invalid start address"))?;
+        let end: usize = parse_int::parse(self.end.trim())
+            .map_err(|_| anyhow::Error::msg("Invalid end address"))?;
+
+        hv.strip_range(start, end)
+    }
+}
+
+/// A byte that differs between the file on disk and the edited data.
+/// Either side is `None` when the position is past the end of that version.
+#[derive(Clone, Copy, Debug)]
+pub struct PatchChange {
+    pub offset: usize,
+    pub original: Option<u8>,
+    pub new: Option<u8>,
+    pub apply: bool,
+}
+
+fn byte_text(byte: Option<u8>) -> String {
+    match byte {
+        Some(byte) => format!("{:02X}", byte),
+        None => "--".to_owned(),
+    }
+}
+
+/// Lists the in-memory edits of a file so they can be reviewed before writing it to disk
+#[derive(Default)]
+pub struct PatchReview {
+    pub show: bool,
+    target: Option<usize>,
+    changes: Vec<PatchChange>,
+    status: String,
+}
+
+impl PatchReview {
+    pub fn open(&mut self, hv: &HexView) {
+        self.target = Some(hv.id);
+        self.status.clear();
+
+        let original = match read_file_bytes(hv.file.path.clone()) {
+            Ok(data) => data,
+            Err(e) => {
+                self.changes.clear();
+                self.status = e.to_string();
+                self.show = true;
+                return;
+            }
+        };
+
+        let new = &hv.file.data;
+        self.changes = (0..original.len().max(new.len()))
+            .map(|offset| PatchChange {
+                offset,
+                original: original.get(offset).copied(),
+                new: new.get(offset).copied(),
+                apply: true,
+            })
+            .filter(|change| change.original != change.new)
+            .collect();
+
+        if self.changes.is_empty() {
+            self.status = "No changes to save".to_owned();
+        }
+        self.show = true;
+    }
+
+    /// Builds the data to write, using the original byte wherever a change was deselected
+    fn patched_data(&self, data: &[u8]) -> Vec<u8> {
+        let mut changes = self.changes.iter().peekable();
+        let len = data
+            .len()
+            .max(self.changes.last().map_or(0, |c| c.offset + 1));
+
+        (0..len)
+            .filter_map(|offset| match changes.next_if(|c| c.offset == offset) {
+                Some(change) if change.apply => change.new,
+                Some(change) => change.original,
+                None => data.get(offset).copied(),
+            })
+            .collect()
+    }
+
+    /// Returns true if a file's data was changed
+    pub fn display(&mut self, ctx: &egui::Context, hex_views: &mut [HexView]) -> bool {
+        let mut open = self.show;
+        let mut changed = false;
+
+        let Some(hv) = self
+            .target
+            .and_then(|id| hex_views.iter_mut().find(|hv| hv.id == id))
+        else {
+            self.show = false;
+            return false;
+        };
+
+        egui::Window::new(format!("Patch review: {}", hv.file_name()))
+            .id(egui::Id::new("patch_review"))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let applied = self.changes.iter().filter(|c| c.apply).count();
+                ui.label(format!(
+                    "{} of {} changed bytes selected",
+                    applied,
+                    self.changes.len()
+                ));
+
+                ui.horizontal(|ui| {
+                    if ui.button("Select all").clicked() {
+                        self.changes.iter_mut().for_each(|c| c.apply = true);
+                    }
+                    if ui.button("Select none").clicked() {
+                        self.changes.iter_mut().for_each(|c| c.apply = false);
+                    }
+                });
+
+                let text_style = egui::TextStyle::Body;
+                let row_height = ui.text_style_height(&text_style);
+                egui::ScrollArea::vertical().max_height(300.0).show_rows(
+                    ui,
+                    row_height,
+                    self.changes.len(),
+                    |ui, row_range| {
+                        egui::Grid::new("patch_review_grid")
+                            .striped(true)
+                            .num_columns(4)
+                            .show(ui, |ui| {
+                                for change in &mut self.changes[row_range] {
+                                    ui.checkbox(&mut change.apply, "");
+                                    ui.label(
+                                        egui::RichText::new(format!("0x{:X}", change.offset))
+                                            .monospace(),
+                                    );
+                                    ui.label(
+                                        egui::RichText::new(byte_text(change.original)).monospace(),
+                                    );
+                                    ui.label(
+                                        egui::RichText::new(byte_text(change.new)).monospace(),
+                                    );
+                                    ui.end_row();
+                                }
+                            });
+                    },
+                );
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!self.changes.is_empty(), egui::Button::new("Write"))
+                        .clicked()
+                    {
+                        // The view keeps its data unless the write succeeds
+                        let patched = self.patched_data(&hv.file.data);
+                        match std::fs::write(&hv.file.path, &patched).with_context(|| {
+                            format!("Failed to write file {}", hv.file.path.display())
+                        }) {
+                            Ok(()) => {
+                                hv.file.data = patched;
+                                // Deselected changes were reverted, so pick up what was written
+                                if let Err(e) = hv.reload_file() {
+                                    log::error!("Failed to reload file: {}", e);
+                                }
+                                self.changes.clear();
+                                self.status = "Saved".to_owned();
+                                changed = true;
+                            }
+                            Err(e) => self.status = format!("{:#}", e),
+                        }
+                    }
+
+                    if ui.button("Cancel").clicked() {
+                        self.show = false;
+                    }
+                });
+
+                ui.label(&self.status);
+            });
+
+        self.show &= open;
+        changed
+    }
+}
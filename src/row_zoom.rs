@@ -0,0 +1,91 @@
+use eframe::egui;
+
+use crate::{bin_file::Endianness, data_viewer::DataType};
+
+/// One row shown in a large font, with every interpretation of the data starting at each byte
+pub struct RowZoom {
+    pub row_start: usize,
+    /// Frame the zoom was opened on, so the click that opened it doesn't close it
+    opened_frame: u64,
+}
+
+impl RowZoom {
+    pub fn new(ctx: &egui::Context, row_start: usize) -> Self {
+        Self {
+            row_start,
+            opened_frame: ctx.frame_nr(),
+        }
+    }
+
+    /// Shows the panel and returns whether it should stay open
+    #[allow(clippy::too_many_arguments)]
+    pub fn display(
+        &self,
+        ctx: &egui::Context,
+        hv_id: usize,
+        data: &[u8],
+        bytes_per_row: usize,
+        endianness: Endianness,
+        base_addr: usize,
+        font_size: f32,
+    ) -> bool {
+        let row_end = (self.row_start + bytes_per_row).min(data.len());
+        let large = |text: String| egui::RichText::new(text).monospace().size(font_size * 1.5);
+
+        let response = egui::Window::new(format!(
+            "Row 0x{:X}",
+            base_addr.saturating_add(self.row_start)
+        ))
+        .id(egui::Id::new(format!("row_zoom_{}", hv_id)))
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new(format!("row_zoom_grid_{}", hv_id))
+                .striped(true)
+                .show(ui, |ui| {
+                    let headers = ["Offset", "Hex", "Char"].map(str::to_owned);
+                    for header in headers
+                        .into_iter()
+                        .chain(DataType::ALL.map(|data_type| data_type.to_string()))
+                    {
+                        ui.label(egui::RichText::new(header).monospace().strong());
+                    }
+                    ui.end_row();
+
+                    let mut float_buffer = dtoa::Buffer::new();
+                    for pos in self.row_start..row_end {
+                        let byte = data[pos];
+                        let char = match byte {
+                            32..=126 => (byte as char).to_string(),
+                            _ => ".".to_owned(),
+                        };
+                        ui.label(large(format!("0x{:X}", base_addr.saturating_add(pos))));
+                        ui.label(large(format!("{:02X}", byte)));
+                        ui.label(large(char));
+
+                        for data_type in DataType::ALL {
+                            let text = match data.get(pos..pos + data_type.size()) {
+                                Some(chunk) => {
+                                    data_type.format(chunk, endianness, &mut float_buffer)
+                                }
+                                None => "-".to_owned(),
+                            };
+                            ui.label(egui::RichText::new(text).monospace());
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+        // Close when clicking anywhere else
+        let clicked_outside = ctx.frame_nr() != self.opened_frame
+            && ctx.input(|i| {
+                i.pointer.any_click()
+                    && match (i.pointer.interact_pos(), &response) {
+                        (Some(pos), Some(response)) => !response.response.rect.contains(pos),
+                        _ => false,
+                    }
+            });
+        !clicked_outside
+    }
+}
@@ -0,0 +1,136 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use eframe::egui;
+
+use crate::hex_view::{hex_view_combo, HexView};
+
+/// Number of runs listed
+const MAX_RUNS: usize = 20;
+
+/// A sequence of the same byte repeated
+#[derive(Clone, Copy, Debug)]
+pub struct ByteRun {
+    pub start: usize,
+    pub len: usize,
+    pub value: u8,
+}
+
+/// Finds the `count` longest runs of at least two bytes, longest first.
+/// Among runs of the same length, earlier ones come first.
+pub fn find_longest_runs(data: &[u8], count: usize) -> Vec<ByteRun> {
+    // Min-heap of the longest runs so far, so the shortest is the one dropped
+    let mut heap = BinaryHeap::with_capacity(count + 1);
+
+    let mut start = 0;
+    for chunk in data.chunk_by(|a, b| a == b) {
+        if chunk.len() >= 2 {
+            heap.push(Reverse((chunk.len(), Reverse(start), chunk[0])));
+            if heap.len() > count {
+                heap.pop();
+            }
+        }
+        start += chunk.len();
+    }
+
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((len, Reverse(start), value))| ByteRun { start, len, value })
+        .collect()
+}
+
+/// Runs found in a view's data
+struct FoundRuns {
+    view_id: usize,
+    data_generation: usize,
+    runs: Vec<ByteRun>,
+}
+
+/// Lists the longest runs of repeated bytes in a file
+#[derive(Default)]
+pub struct RunSummary {
+    pub show: bool,
+    file: Option<usize>,
+    results: Option<FoundRuns>,
+}
+
+impl RunSummary {
+    pub fn display(&mut self, ctx: &egui::Context, hex_views: &mut [HexView]) {
+        let mut open = self.show;
+
+        egui::Window::new("Byte runs")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                hex_view_combo(ui, "File", &mut self.file, hex_views);
+
+                let hv = self
+                    .file
+                    .and_then(|id| hex_views.iter().position(|hv| hv.id == id));
+
+                // Drop the results once another file is picked or the data changes
+                if self.results.as_ref().is_some_and(|found| {
+                    hv.is_none_or(|i| {
+                        found.view_id != hex_views[i].id
+                            || found.data_generation != hex_views[i].data_generation
+                    })
+                }) {
+                    self.results = None;
+                }
+
+                if ui
+                    .add_enabled(hv.is_some(), egui::Button::new("Find runs"))
+                    .on_hover_text(format!(
+                        "List the {} longest runs of the same byte",
+                        MAX_RUNS
+                    ))
+                    .clicked()
+                {
+                    let hv = &hex_views[hv.unwrap()];
+                    self.results = Some(FoundRuns {
+                        view_id: hv.id,
+                        data_generation: hv.data_generation,
+                        runs: find_longest_runs(&hv.file.data, MAX_RUNS),
+                    });
+                }
+
+                let (Some(hv), Some(found)) = (hv, &self.results) else {
+                    return;
+                };
+                let results = &found.runs;
+                if results.is_empty() {
+                    ui.label("No byte is repeated");
+                    return;
+                }
+
+                egui::Grid::new("byte_runs")
+                    .striped(true)
+                    .num_columns(3)
+                    .show(ui, |ui| {
+                        ui.strong("Byte");
+                        ui.strong("Offset");
+                        ui.strong("Length");
+                        ui.end_row();
+
+                        for run in results.iter() {
+                            ui.label(egui::RichText::new(format!("{:02X}", run.value)).monospace());
+                            let start = run.start;
+                            let res = ui
+                                .add(
+                                    egui::Label::new(
+                                        egui::RichText::new(format!("0x{:X}", start)).monospace(),
+                                    )
+                                    .sense(egui::Sense::click()),
+                                )
+                                .on_hover_text("Go to this run");
+                            if res.clicked() {
+                                let hv = &mut hex_views[hv];
+                                hv.set_cur_pos(start - start % hv.bytes_per_row);
+                            }
+                            ui.label(egui::RichText::new(format!("0x{:X}", run.len)).monospace());
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        self.show = open;
+    }
+}
@@ -0,0 +1,330 @@
+use std::fmt;
+
+use anyhow::Error;
+
+use crate::bin_file::Endianness;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SearchMode {
+    /// Space separated hex bytes, such as `DE AD BE EF`, where `??` matches any byte
+    #[default]
+    Bytes,
+    /// UTF-8 text
+    Text,
+    /// An integer of a given width, matched at offsets aligned to that width
+    Value,
+}
+
+impl SearchMode {
+    pub const ALL: [SearchMode; 3] = [Self::Bytes, Self::Text, Self::Value];
+}
+
+impl fmt::Display for SearchMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Bytes => "Hex bytes",
+            Self::Text => "Text",
+            Self::Value => "By value",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ValueWidth {
+    U8,
+    U16,
+    #[default]
+    U32,
+    U64,
+}
+
+impl ValueWidth {
+    pub const ALL: [ValueWidth; 4] = [Self::U8, Self::U16, Self::U32, Self::U64];
+
+    pub fn size(&self) -> usize {
+        match self {
+            Self::U8 => 1,
+            Self::U16 => 2,
+            Self::U32 => 4,
+            Self::U64 => 8,
+        }
+    }
+}
+
+impl fmt::Display for ValueWidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::U8 => "u8",
+            Self::U16 => "u16",
+            Self::U32 => "u32",
+            Self::U64 => "u64",
+        })
+    }
+}
+
+/// Parses a decimal or hex integer and encodes it as `width` bytes.
+/// Negative values are stored as two's complement.
+pub fn encode_value(
+    text: &str,
+    width: ValueWidth,
+    endianness: Endianness,
+) -> Result<Vec<u8>, Error> {
+    let text = text.trim();
+    let bits = width.size() as u32 * 8;
+
+    let value: u64 = match text.strip_prefix('-') {
+        Some(magnitude) => {
+            let magnitude: u64 = parse_int::parse(magnitude)
+                .map_err(|_| Error::msg(format!("Invalid number \"{}\"", text)))?;
+            if magnitude > 1 << (bits - 1) {
+                return Err(Error::msg(format!("{} doesn't fit in {}", text, width)));
+            }
+            magnitude.wrapping_neg()
+        }
+        None => {
+            let value: u64 = parse_int::parse(text)
+                .map_err(|_| Error::msg(format!("Invalid number \"{}\"", text)))?;
+            if bits < 64 && value >> bits != 0 {
+                return Err(Error::msg(format!("{} doesn't fit in {}", text, width)));
+            }
+            value
+        }
+    };
+
+    let bytes = match endianness {
+        Endianness::Little => value.to_le_bytes()[..width.size()].to_vec(),
+        Endianness::Big => value.to_be_bytes()[8 - width.size()..].to_vec(),
+    };
+    Ok(bytes)
+}
+
+/// Parses a byte pattern such as `DE AD BE EF`. Bytes may also be written without spaces.
+pub fn parse_hex_pattern(text: &str) -> Result<Vec<u8>, Error> {
+    parse_masked_pattern(text)?
+        .into_iter()
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| Error::msg("Wildcards aren't allowed here"))
+}
+
+/// Parses a byte pattern where `??` matches any byte, such as `DE ?? BE EF`.
+/// Wildcards are returned as `None`.
+pub fn parse_masked_pattern(text: &str) -> Result<Vec<Option<u8>>, Error> {
+    let mut bytes = Vec::new();
+    for token in text.split_whitespace() {
+        if !token.len().is_multiple_of(2) {
+            return Err(Error::msg(format!(
+                "\"{}\" is not a whole number of bytes",
+                token
+            )));
+        }
+        for i in (0..token.len()).step_by(2) {
+            let byte = match token.get(i..i + 2) {
+                Some("??") => None,
+                s => Some(
+                    s.and_then(|s| u8::from_str_radix(s, 16).ok())
+                        .ok_or_else(|| Error::msg(format!("Invalid hex byte in \"{}\"", token)))?,
+                ),
+            };
+            bytes.push(byte);
+        }
+    }
+
+    if bytes.is_empty() {
+        return Err(Error::msg("Nothing to search for"));
+    }
+    Ok(bytes)
+}
+
+/// Finds every occurrence of the needle, including overlapping ones, using a
+/// Boyer-Moore-Horspool scan. `None` in the needle matches any byte.
+pub fn find_pattern(data: &[u8], needle: &[Option<u8>]) -> Vec<usize> {
+    let mut matches = Vec::new();
+    if needle.is_empty() || needle.len() > data.len() {
+        return matches;
+    }
+
+    // A wildcard matches every byte, so no shift may move past the last one
+    let last = needle.len() - 1;
+    let max_skip = match needle[..last].iter().rposition(Option::is_none) {
+        Some(i) => last - i,
+        None => needle.len(),
+    };
+    let mut skip = [max_skip; 256];
+    for (i, byte) in needle[..last].iter().enumerate() {
+        if let Some(byte) = byte {
+            skip[*byte as usize] = skip[*byte as usize].min(last - i);
+        }
+    }
+
+    let matches_at = |pos: usize| {
+        needle
+            .iter()
+            .zip(&data[pos..pos + needle.len()])
+            .all(|(pattern, byte)| pattern.is_none_or(|b| b == *byte))
+    };
+
+    let mut pos = 0;
+    while pos + needle.len() <= data.len() {
+        if matches_at(pos) {
+            matches.push(pos);
+        }
+        pos += skip[data[pos + last] as usize];
+    }
+    matches
+}
+
+/// Finds every occurrence of the encoded value at an offset aligned to its size
+pub fn find_value(data: &[u8], needle: &[u8]) -> Vec<usize> {
+    data.chunks_exact(needle.len())
+        .enumerate()
+        .filter(|(_, chunk)| *chunk == needle)
+        .map(|(i, _)| i * needle.len())
+        .collect()
+}
+
+/// The results of a search in one file
+#[derive(Default)]
+pub struct SearchMatches {
+    /// Start of each match, in ascending order
+    pub offsets: Vec<usize>,
+    pub len: usize,
+}
+
+impl SearchMatches {
+    pub fn contains(&self, pos: usize) -> bool {
+        // The last match starting at or before pos reaches the furthest
+        let i = self.offsets.partition_point(|&offset| offset <= pos);
+        i > 0 && pos < self.offsets[i - 1] + self.len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_masked_pattern_mixes_wildcards_and_bytes() {
+        assert_eq!(
+            parse_masked_pattern("DE ?? be EF").unwrap(),
+            vec![Some(0xDE), None, Some(0xBE), Some(0xEF)]
+        );
+        assert_eq!(
+            parse_masked_pattern("12??34").unwrap(),
+            vec![Some(0x12), None, Some(0x34)]
+        );
+    }
+
+    #[test]
+    fn parse_masked_pattern_rejects_bad_tokens() {
+        assert!(parse_masked_pattern("ABC").is_err());
+        assert!(parse_masked_pattern("AB C").is_err());
+        assert!(parse_masked_pattern("GG").is_err());
+        assert!(parse_masked_pattern("?A").is_err());
+        assert!(parse_masked_pattern("ÄB").is_err());
+        assert!(parse_masked_pattern("   ").is_err());
+    }
+
+    #[test]
+    fn find_pattern_finds_overlapping_matches() {
+        let data = [0xAA; 5];
+        assert_eq!(
+            find_pattern(&data, &[Some(0xAA), Some(0xAA)]),
+            vec![0, 1, 2, 3]
+        );
+
+        let data = [1, 2, 1, 2, 1, 2, 1];
+        assert_eq!(
+            find_pattern(&data, &[Some(1), Some(2), Some(1)]),
+            vec![0, 2, 4]
+        );
+    }
+
+    #[test]
+    fn find_pattern_handles_leading_and_trailing_wildcards() {
+        let data = [0x10, 0x20, 0x30, 0x20, 0x40];
+        assert_eq!(find_pattern(&data, &[None, Some(0x20)]), vec![0, 2]);
+        assert_eq!(find_pattern(&data, &[Some(0x20), None]), vec![1, 3]);
+        assert_eq!(find_pattern(&data, &[None, Some(0x30), None]), vec![1]);
+        assert_eq!(find_pattern(&data, &[None, None]), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn find_pattern_with_needle_longer_than_data() {
+        assert!(find_pattern(&[1, 2], &[Some(1), Some(2), Some(3)]).is_empty());
+        assert!(find_pattern(&[], &[None]).is_empty());
+        assert!(find_pattern(&[1, 2], &[]).is_empty());
+    }
+
+    #[test]
+    fn encode_value_accepts_signed_and_unsigned_bounds() {
+        let le = Endianness::Little;
+        assert_eq!(encode_value("255", ValueWidth::U8, le).unwrap(), vec![0xFF]);
+        assert_eq!(
+            encode_value("-128", ValueWidth::U8, le).unwrap(),
+            vec![0x80]
+        );
+        assert_eq!(
+            encode_value("-1", ValueWidth::U16, le).unwrap(),
+            vec![0xFF, 0xFF]
+        );
+        assert_eq!(
+            encode_value("0xFFFFFFFF", ValueWidth::U32, le).unwrap(),
+            vec![0xFF; 4]
+        );
+        assert_eq!(
+            encode_value("-2147483648", ValueWidth::U32, le).unwrap(),
+            vec![0x00, 0x00, 0x00, 0x80]
+        );
+        assert_eq!(
+            encode_value("18446744073709551615", ValueWidth::U64, le).unwrap(),
+            vec![0xFF; 8]
+        );
+        assert_eq!(
+            encode_value("-9223372036854775808", ValueWidth::U64, le).unwrap(),
+            vec![0, 0, 0, 0, 0, 0, 0, 0x80]
+        );
+    }
+
+    #[test]
+    fn encode_value_rejects_out_of_range_and_invalid_numbers() {
+        let le = Endianness::Little;
+        assert!(encode_value("256", ValueWidth::U8, le).is_err());
+        assert!(encode_value("-129", ValueWidth::U8, le).is_err());
+        assert!(encode_value("0x10000", ValueWidth::U16, le).is_err());
+        assert!(encode_value("-32769", ValueWidth::U16, le).is_err());
+        assert!(encode_value("0x100000000", ValueWidth::U32, le).is_err());
+        assert!(encode_value("18446744073709551616", ValueWidth::U64, le).is_err());
+        assert!(encode_value("-9223372036854775809", ValueWidth::U64, le).is_err());
+        assert!(encode_value("", ValueWidth::U32, le).is_err());
+        assert!(encode_value("12ab", ValueWidth::U32, le).is_err());
+        assert!(encode_value("--1", ValueWidth::U32, le).is_err());
+    }
+
+    #[test]
+    fn encode_value_follows_endianness() {
+        assert_eq!(
+            encode_value(" 0x12345678 ", ValueWidth::U32, Endianness::Little).unwrap(),
+            vec![0x78, 0x56, 0x34, 0x12]
+        );
+        assert_eq!(
+            encode_value("0x12345678", ValueWidth::U32, Endianness::Big).unwrap(),
+            vec![0x12, 0x34, 0x56, 0x78]
+        );
+        assert_eq!(
+            encode_value("-2", ValueWidth::U16, Endianness::Big).unwrap(),
+            vec![0xFF, 0xFE]
+        );
+        assert_eq!(
+            encode_value("0x1234", ValueWidth::U64, Endianness::Big).unwrap(),
+            vec![0, 0, 0, 0, 0, 0, 0x12, 0x34]
+        );
+    }
+
+    #[test]
+    fn find_value_only_matches_aligned_offsets() {
+        let data = [0x34, 0x12, 0x34, 0x12, 0x00, 0x34, 0x12, 0x00, 0x34];
+        let needle = encode_value("0x1234", ValueWidth::U16, Endianness::Little).unwrap();
+        assert_eq!(find_value(&data, &needle), vec![0, 2]);
+        assert!(find_value(&data[..1], &needle).is_empty());
+    }
+}
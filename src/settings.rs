@@ -1,6 +1,9 @@
 use std::{
+    collections::BTreeMap,
+    fmt,
     fs::{File, OpenOptions},
     io::Write,
+    ops::RangeInclusive,
     path::PathBuf,
 };
 
@@ -8,12 +11,226 @@ use anyhow::{Context, Error};
 use eframe::epaint::Color32;
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Default, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub const NUM_MEMORY_SLOTS: usize = 8;
+const MAX_RECENT_FILES: usize = 10;
+/// Widest row the settings allow, also enforced on row widths loaded from files
+pub const MAX_BYTES_PER_ROW: usize = 64;
+/// Font sizes the settings allow, in points
+pub const FONT_SIZE_RANGE: RangeInclusive<f32> = 8.0..=24.0;
+/// Entropy window sizes the settings allow
+pub const ENTROPY_WINDOW_RANGE: RangeInclusive<usize> = 16..=4096;
+
+/// Number of bytes a memory slot keeps as a preview of its range
+pub const MEMORY_SLOT_PREVIEW_LEN: usize = 0x20;
+
+/// A saved selection range, as addresses in the view's virtual address space,
+/// and the first bytes it contained when it was saved
+#[derive(Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+pub struct MemorySlot {
+    pub start: usize,
+    pub end: usize,
+    pub bytes: Vec<u8>,
+}
+
+/// Where a view was scrolled to and which of its panels were open
+#[derive(Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+pub struct ViewPreset {
+    pub cur_pos: usize,
+    pub bytes_per_row: usize,
+    pub num_rows: u32,
+    pub show_selection_info: bool,
+    pub show_cursor_info: bool,
+    pub show_data_viewer: bool,
+    pub show_string_viewer: bool,
+    pub show_map_tool: bool,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
 pub struct Settings {
     pub byte_grouping: ByteGrouping,
+    pub bytes_per_row: usize,
+    pub group_rows_every: Option<usize>,
+    /// Show the index of each byte column above the hex grid
+    pub show_column_header: bool,
+    /// Wrap Base64 copied from the selection at 76 characters per line
+    pub base64_wrap: bool,
+    /// How Ctrl+C formats bytes selected in the hex column
+    pub copy_format: CopyFormat,
+    /// Copy runs of non-printable bytes in ASCII selections as a single line break
+    pub copy_normalize_whitespace: bool,
+    /// Use minimal padding between hex cells, useful with small fonts
+    pub compact_mode: bool,
+    /// Size in points of the text in the hex grid
+    pub font_size: f32,
+    /// How null bytes are written in the hex grid
+    pub null_display: NullDisplay,
+    /// Scrolling past either end of the file continues from the other end
+    pub wrap_scroll: bool,
+    /// Name of the key that starts editing the hovered byte
+    pub edit_key: String,
+    /// Width in pixels of the bar next to each view showing diffs across the whole file
+    pub scrollbar_width: usize,
+    /// Number of bytes around each byte that its entropy is computed over
+    pub entropy_window: usize,
+    pub memory_slots: [Option<MemorySlot>; NUM_MEMORY_SLOTS],
+    /// Named layouts for the open views, applied to views in the order they're open
+    pub view_presets: BTreeMap<String, Vec<ViewPreset>>,
+    /// A map file named like an opened file plus one of these suffixes is loaded with it
+    pub map_file_suffixes: Vec<String>,
+    /// Recently opened files, most recent first
+    pub recent_files: Vec<PathBuf>,
     pub theme_settings: ThemeSettings,
 }
 
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            byte_grouping: ByteGrouping::default(),
+            bytes_per_row: 0x10,
+            group_rows_every: None,
+            show_column_header: true,
+            base64_wrap: false,
+            copy_format: CopyFormat::default(),
+            copy_normalize_whitespace: false,
+            compact_mode: false,
+            font_size: 14.0,
+            null_display: NullDisplay::default(),
+            wrap_scroll: false,
+            edit_key: "F2".to_owned(),
+            scrollbar_width: 12,
+            entropy_window: 256,
+            memory_slots: Default::default(),
+            view_presets: BTreeMap::new(),
+            map_file_suffixes: vec![".map".to_owned()],
+            recent_files: Vec::new(),
+            theme_settings: ThemeSettings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Moves the path to the front of the recent files, dropping the oldest past the limit
+    pub fn add_recent_file(&mut self, path: PathBuf) {
+        self.recent_files.retain(|p| *p != path);
+        self.recent_files.insert(0, path);
+        self.recent_files.truncate(MAX_RECENT_FILES);
+    }
+
+    /// Checks that the row width is in range and rows split evenly into byte groups
+    pub fn validate_row_layout(
+        bytes_per_row: usize,
+        byte_grouping: ByteGrouping,
+    ) -> Result<(), String> {
+        if !(1..=MAX_BYTES_PER_ROW).contains(&bytes_per_row) {
+            return Err(format!(
+                "Bytes per row must be between 1 and {}",
+                MAX_BYTES_PER_ROW
+            ));
+        }
+
+        // The detected grouping differs per file, so it can't be checked here
+        if byte_grouping == ByteGrouping::Auto {
+            return Ok(());
+        }
+
+        let group_size: usize = byte_grouping.into();
+        if !bytes_per_row.is_multiple_of(group_size) {
+            return Err(format!(
+                "Bytes per row must be a multiple of the byte grouping ({})",
+                group_size
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum CopyFormat {
+    /// Space separated hex bytes, such as `DE AD BE EF`
+    #[default]
+    HexBytes,
+    /// A C array initializer, such as `{ 0xDE, 0xAD, 0xBE, 0xEF }`
+    CArray,
+    /// A Python bytes literal, such as `b'\xde\xad\xbe\xef'`
+    PythonBytes,
+}
+
+impl CopyFormat {
+    pub const ALL: [CopyFormat; 3] = [Self::HexBytes, Self::CArray, Self::PythonBytes];
+
+    pub fn format(&self, bytes: &[u8]) -> String {
+        match self {
+            Self::HexBytes => bytes
+                .iter()
+                .map(|b| format!("{:02X}", b))
+                .collect::<Vec<String>>()
+                .join(" "),
+            Self::CArray => {
+                let items = bytes
+                    .iter()
+                    .map(|b| format!("0x{:02X}", b))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                format!("{{ {} }}", items)
+            }
+            Self::PythonBytes => {
+                let escaped: String = bytes.iter().map(|b| format!("\\x{:02x}", b)).collect();
+                format!("b'{}'", escaped)
+            }
+        }
+    }
+}
+
+impl fmt::Display for CopyFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::HexBytes => "Hex bytes",
+            Self::CArray => "C array",
+            Self::PythonBytes => "Python bytes",
+        })
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum NullDisplay {
+    /// `00`, like any other byte
+    #[default]
+    Hex,
+    /// `..`
+    Dot,
+    /// A blank cell
+    Empty,
+    /// `··`, the marker text editors use for whitespace
+    Space,
+}
+
+impl NullDisplay {
+    pub const ALL: [NullDisplay; 4] = [Self::Hex, Self::Dot, Self::Empty, Self::Space];
+
+    /// The text shown in the hex grid for a null byte
+    pub fn text(&self) -> &'static str {
+        match self {
+            Self::Hex => "00",
+            Self::Dot => "..",
+            Self::Empty => "  ",
+            Self::Space => "\u{b7}\u{b7}",
+        }
+    }
+}
+
+impl fmt::Display for NullDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Hex => "00",
+            Self::Dot => "Dots",
+            Self::Empty => "Empty",
+            Self::Space => "Whitespace dots",
+        })
+    }
+}
+
 #[derive(Deserialize, Serialize, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub enum ByteGrouping {
     One,
@@ -22,6 +239,7 @@ pub enum ByteGrouping {
     #[default]
     Eight,
     Sixteen,
+    Auto,
 }
 
 impl ByteGrouping {
@@ -32,20 +250,65 @@ impl ByteGrouping {
             ByteGrouping::Four,
             ByteGrouping::Eight,
             ByteGrouping::Sixteen,
+            ByteGrouping::Auto,
         ]
     }
+
+    /// Guesses the natural alignment of the data by checking how often bytes repeat
+    /// at each candidate stride. Pairs of zero bytes are ignored so padding doesn't skew the result.
+    pub fn detect(data: &[u8]) -> ByteGrouping {
+        let sample = &data[..data.len().min(0x10000)];
+
+        let scores: Vec<(ByteGrouping, f32)> = [
+            ByteGrouping::Two,
+            ByteGrouping::Four,
+            ByteGrouping::Eight,
+            ByteGrouping::Sixteen,
+        ]
+        .into_iter()
+        .map(|grouping| {
+            let stride: usize = grouping.into();
+            let pairs = sample
+                .iter()
+                .zip(sample.iter().skip(stride))
+                .filter(|(a, b)| **a != 0 || **b != 0);
+
+            let (total, matches) = pairs.fold((0, 0), |(total, matches), (a, b)| {
+                (total + 1, matches + (a == b) as usize)
+            });
+
+            let score = match total {
+                0 => 0.0,
+                _ => matches as f32 / total as f32,
+            };
+            (grouping, score)
+        })
+        .collect();
+
+        let best = scores.iter().map(|(_, score)| *score).fold(0.0, f32::max);
+        if best == 0.0 {
+            return ByteGrouping::default();
+        }
+
+        // Multiples of the real stride score about as well, so prefer the smallest one close to the best
+        scores
+            .into_iter()
+            .find(|(_, score)| *score >= best * 0.9)
+            .map(|(grouping, _)| grouping)
+            .unwrap_or_default()
+    }
 }
 
-impl ToString for ByteGrouping {
-    fn to_string(&self) -> String {
-        match self {
+impl fmt::Display for ByteGrouping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
             Self::One => "One",
             Self::Two => "Two",
             Self::Four => "Four",
             Self::Eight => "Eight",
             Self::Sixteen => "Sixteen",
-        }
-        .to_string()
+            Self::Auto => "Auto",
+        })
     }
 }
 
@@ -57,6 +320,8 @@ impl From<ByteGrouping> for usize {
             ByteGrouping::Four => 4,
             ByteGrouping::Eight => 8,
             ByteGrouping::Sixteen => 16,
+            // Auto is resolved per file, fall back to the default if it wasn't
+            ByteGrouping::Auto => ByteGrouping::default().into(),
         }
     }
 }
@@ -88,8 +353,12 @@ impl From<Color> for Color32 {
 }
 
 #[derive(Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Clone)]
+#[serde(default)]
 pub struct ThemeSettings {
     pub selection_color: Color,
+    pub hover_highlight_color: Color,
+    pub search_match_color: Color,
+    pub edit_color: Color,
 
     // Offset colors
     pub offset_text_color: Color,
@@ -97,8 +366,22 @@ pub struct ThemeSettings {
 
     // Hex View colors
     pub diff_color: Color,
+    /// Diff colors for each base/file pair when more than two files are open
+    pub diff_palette: Vec<Color>,
+    /// Backgrounds of bytes only in one file in aligned diff mode
+    pub diff_insert_color: Color,
+    pub diff_delete_color: Color,
     pub hex_null_color: Color,
     pub other_hex_color: Color,
+    pub uncovered_color: Color,
+    /// Bytes that differ from the view's template
+    pub template_mismatch_color: Color,
+    /// Entropy coloring blends from the low color at 0 bits to the high color at 8 bits
+    pub entropy_low_color: Color,
+    pub entropy_high_color: Color,
+    /// Density coloring blends from the near color next to a null byte to the far color
+    pub density_near_color: Color,
+    pub density_far_color: Color,
 
     // ASCII View colors
     pub ascii_null_color: Color,
@@ -113,9 +396,30 @@ impl Default for ThemeSettings {
             offset_leading_zero_color: Color32::DARK_GRAY.into(),
 
             selection_color: Color32::DARK_GREEN.into(),
+            hover_highlight_color: Color32::from_rgb(40, 60, 40).into(),
+            search_match_color: Color32::from_rgb(30, 60, 110).into(),
+            edit_color: Color32::from_rgb(110, 60, 20).into(),
             diff_color: Color32::RED.into(),
+            diff_palette: vec![
+                Color32::RED.into(),
+                Color32::from_rgb(0, 160, 255).into(),
+                Color32::from_rgb(255, 200, 0).into(),
+                Color32::from_rgb(200, 80, 255).into(),
+                Color32::from_rgb(0, 220, 120).into(),
+                Color32::from_rgb(255, 120, 0).into(),
+                Color32::from_rgb(255, 100, 180).into(),
+                Color32::from_rgb(0, 220, 220).into(),
+            ],
             hex_null_color: Color32::DARK_GRAY.into(),
             other_hex_color: Color32::GRAY.into(),
+            uncovered_color: Color32::from_rgb(64, 48, 0).into(),
+            diff_insert_color: Color32::from_rgb(20, 90, 40).into(),
+            diff_delete_color: Color32::from_rgb(100, 30, 30).into(),
+            template_mismatch_color: Color32::from_rgb(120, 30, 60).into(),
+            entropy_low_color: Color32::from_rgb(40, 90, 255).into(),
+            entropy_high_color: Color32::from_rgb(255, 40, 40).into(),
+            density_near_color: Color32::from_rgb(50, 50, 50).into(),
+            density_far_color: Color32::WHITE.into(),
 
             ascii_null_color: Color32::DARK_GRAY.into(),
             ascii_color: Color32::LIGHT_GRAY.into(),
@@ -124,6 +428,15 @@ impl Default for ThemeSettings {
     }
 }
 
+impl ThemeSettings {
+    pub fn diff_pair_color(&self, pair: usize) -> Color32 {
+        match self.diff_palette.len() {
+            0 => self.diff_color.clone().into(),
+            len => self.diff_palette[pair % len].clone().into(),
+        }
+    }
+}
+
 pub fn get_settings_path() -> PathBuf {
     let mut path =
         dirs::config_local_dir().expect("Failed to get local configuration dir, report a bug!");
@@ -139,7 +452,25 @@ pub fn read_json_settings() -> Result<Settings, Error> {
     let settings_path = get_settings_path();
     let mut reader = File::open(&settings_path)
         .with_context(|| format!("Failed to open config file at {}", settings_path.display()))?;
-    Ok(serde_json::from_reader(&mut reader)?)
+    let mut settings: Settings = serde_json::from_reader(&mut reader)?;
+
+    // The file may have been edited by hand, and a bad row width breaks every view
+    if let Err(e) = Settings::validate_row_layout(settings.bytes_per_row, settings.byte_grouping) {
+        log::error!("Ignoring the row layout from the settings file: {}", e);
+        let default = Settings::default();
+        settings.bytes_per_row = default.bytes_per_row;
+        settings.byte_grouping = default.byte_grouping;
+    }
+    settings.font_size = settings
+        .font_size
+        .clamp(*FONT_SIZE_RANGE.start(), *FONT_SIZE_RANGE.end());
+    settings.entropy_window = settings
+        .entropy_window
+        .clamp(*ENTROPY_WINDOW_RANGE.start(), *ENTROPY_WINDOW_RANGE.end());
+    for memory_slot in settings.memory_slots.iter_mut().flatten() {
+        memory_slot.bytes.truncate(MEMORY_SLOT_PREVIEW_LEN);
+    }
+    Ok(settings)
 }
 
 pub fn write_json_settings(settings: &Settings) -> Result<(), Error> {
@@ -1,40 +1,117 @@
+use std::{fmt, ops::RangeInclusive};
+
 use eframe::egui;
 use encoding_rs::*;
 
-use crate::bin_file::Endianness;
+/// Finds null-terminated runs of at least `min_len` printable ASCII characters.
+/// Returns the offset of each string within `data` and its text, without the terminator.
+pub fn find_c_strings(data: &[u8], min_len: usize) -> Vec<(usize, String)> {
+    let mut strings = Vec::new();
+    let mut start = 0;
+
+    for (i, &b) in data.iter().enumerate() {
+        match b {
+            32..=126 | b'\t' => continue,
+            0 if i - start >= min_len.max(1) => {
+                strings.push((start, String::from_utf8_lossy(&data[start..i]).into_owned()));
+            }
+            _ => {}
+        }
+        start = i + 1;
+    }
+
+    strings
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum StringEncoding {
+    #[default]
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+    EucJp,
+    ShiftJis,
+}
+
+impl StringEncoding {
+    pub const ALL: [StringEncoding; 6] = [
+        Self::Utf8,
+        Self::Utf16Le,
+        Self::Utf16Be,
+        Self::Latin1,
+        Self::EucJp,
+        Self::ShiftJis,
+    ];
+
+    /// Decodes the bytes, or returns an empty string if they are not valid in this encoding
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        let encoding = match self {
+            // Latin-1 maps every byte to the code point of the same value
+            Self::Latin1 => return bytes.iter().map(|&b| b as char).collect(),
+            Self::Utf8 => UTF_8,
+            Self::Utf16Le => UTF_16LE,
+            Self::Utf16Be => UTF_16BE,
+            Self::EucJp => EUC_JP,
+            Self::ShiftJis => SHIFT_JIS,
+        };
+        encoding
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .unwrap_or_default()
+            .into_owned()
+    }
+}
+
+impl fmt::Display for StringEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Utf8 => "UTF-8",
+            Self::Utf16Le => "UTF-16 LE",
+            Self::Utf16Be => "UTF-16 BE",
+            Self::Latin1 => "Latin-1",
+            Self::EucJp => "EUC-JP",
+            Self::ShiftJis => "Shift JIS",
+        })
+    }
+}
 
 pub struct StringViewer {
     pub show: bool,
-    pub utf8: bool,
-    pub utf16: bool,
-    pub shift_jis: bool,
-    pub euc_jp: bool,
+    pub encoding: StringEncoding,
+    /// List the C strings on screen
+    pub find_strings: bool,
+    pub min_string_len: usize,
 }
 
 impl Default for StringViewer {
     fn default() -> StringViewer {
         StringViewer {
             show: false,
-            utf8: true,
-            utf16: false,
-            shift_jis: false,
-            euc_jp: false,
+            encoding: StringEncoding::default(),
+            find_strings: false,
+            min_string_len: 4,
         }
     }
 }
 
 impl StringViewer {
+    /// Returns the range of a found string that was clicked, to be selected
+    #[allow(clippy::too_many_arguments)]
     pub fn display(
         &mut self,
         ui: &mut egui::Ui,
         hv_id: usize,
         selected_bytes: Vec<u8>,
-        endianness: Endianness,
-    ) {
+        screen_bytes: &[u8],
+        screen_start: usize,
+        base_addr: usize,
+    ) -> Option<RangeInclusive<usize>> {
         if !self.show {
-            return;
+            return None;
         }
 
+        let mut clicked = None;
+
         ui.group(|ui| {
             ui.with_layout(
                 egui::Layout::left_to_right(eframe::emath::Align::Min),
@@ -43,73 +120,63 @@ impl StringViewer {
                         egui::RichText::new("String Viewer").monospace(),
                     ));
 
-                    ui.menu_button("...", |ui| {
-                        ui.checkbox(&mut self.utf8, "UTF-8");
-                        ui.checkbox(&mut self.utf16, "UTF-16");
-                        ui.checkbox(&mut self.euc_jp, "EUC-JP");
-                        ui.checkbox(&mut self.shift_jis, "Shift JIS");
-                    });
+                    egui::ComboBox::from_id_source(format!("string_encoding{}", hv_id))
+                        .selected_text(self.encoding.to_string())
+                        .show_ui(ui, |ui| {
+                            for encoding in StringEncoding::ALL {
+                                ui.selectable_value(
+                                    &mut self.encoding,
+                                    encoding,
+                                    encoding.to_string(),
+                                );
+                            }
+                        });
                 },
             );
 
-            egui::Grid::new(format!("string_grid{}", hv_id))
-                .striped(true)
-                .num_columns(2)
-                .show(ui, |ui| {
-                    if self.utf8 {
-                        ui.add(egui::Label::new(egui::RichText::new("UTF-8").monospace()));
-                        ui.text_edit_singleline(
-                            &mut String::from_utf8(selected_bytes.clone()).unwrap_or_default(),
-                        );
-                        ui.end_row();
-                    }
-
-                    if self.utf16 {
-                        let encoding = match endianness {
-                            Endianness::Little => UTF_16LE,
-                            Endianness::Big => UTF_16BE,
-                        };
-
-                        ui.add(egui::Label::new(egui::RichText::new("UTF-16").monospace()));
-                        ui.text_edit_singleline(
-                            &mut encoding
-                                .decode_without_bom_handling_and_without_replacement(
-                                    &selected_bytes,
-                                )
-                                .unwrap_or_default()
-                                .to_string(),
-                        );
-                        ui.end_row();
-                    }
-
-                    if self.euc_jp {
-                        ui.add(egui::Label::new(egui::RichText::new("EUC-JP").monospace()));
-                        ui.text_edit_singleline(
-                            &mut EUC_JP
-                                .decode_without_bom_handling_and_without_replacement(
-                                    &selected_bytes,
-                                )
-                                .unwrap_or_default()
-                                .to_string(),
-                        );
-                        ui.end_row();
-                    }
-
-                    if self.shift_jis {
-                        ui.add(egui::Label::new(
-                            egui::RichText::new("Shift JIS").monospace(),
-                        ));
-                        ui.text_edit_singleline(
-                            &mut SHIFT_JIS
-                                .decode_without_bom_handling_and_without_replacement(
-                                    &selected_bytes,
-                                )
-                                .unwrap_or_default()
-                                .to_string(),
-                        );
-                        ui.end_row();
-                    }
-                });
+            ui.text_edit_singleline(&mut self.encoding.decode(&selected_bytes));
+
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.find_strings, "Find strings on screen");
+                if self.find_strings {
+                    ui.label("Min length");
+                    ui.add(egui::DragValue::new(&mut self.min_string_len).clamp_range(1..=256));
+                }
+            });
+
+            if self.find_strings {
+                let strings = find_c_strings(screen_bytes, self.min_string_len);
+                if strings.is_empty() {
+                    ui.label("No strings found");
+                }
+
+                egui::ScrollArea::vertical()
+                    .id_source(format!("found_strings{}", hv_id))
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new(format!("found_strings_grid{}", hv_id))
+                            .striped(true)
+                            .num_columns(2)
+                            .show(ui, |ui| {
+                                for (offset, text) in strings {
+                                    let start = screen_start + offset;
+                                    ui.label(
+                                        egui::RichText::new(format!(
+                                            "0x{:X}",
+                                            base_addr.saturating_add(start)
+                                        ))
+                                        .monospace(),
+                                    );
+                                    if ui.selectable_label(false, &text).clicked() {
+                                        clicked = Some(start..=start + text.len() - 1);
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            }
         });
+
+        clicked
     }
 }
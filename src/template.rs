@@ -0,0 +1,104 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Error};
+
+/// One byte of a template. Wildcard nibbles are left out of the mask.
+#[derive(Clone, Copy, Debug)]
+struct TemplateByte {
+    value: u8,
+    mask: u8,
+}
+
+impl TemplateByte {
+    fn matches(&self, byte: u8) -> bool {
+        byte & self.mask == self.value
+    }
+}
+
+/// Parses a template of hex nibbles and `?` wildcards. Line breaks only separate lines and
+/// may fall anywhere, even inside a byte.
+fn parse_template(text: &str) -> Result<Vec<TemplateByte>, Error> {
+    let mut nibbles = Vec::new();
+    for (line_num, line) in text.lines().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            let nibble = match c {
+                '?' => None,
+                _ => Some(c.to_digit(16).ok_or_else(|| {
+                    Error::msg(format!(
+                        "Unexpected {:?} on line {}, column {}",
+                        c,
+                        line_num + 1,
+                        col + 1
+                    ))
+                })? as u8),
+            };
+            nibbles.push(nibble);
+        }
+    }
+
+    if !nibbles.len().is_multiple_of(2) {
+        return Err(Error::msg("The template ends in the middle of a byte"));
+    }
+
+    Ok(nibbles
+        .chunks_exact(2)
+        .map(|pair| {
+            let (high, high_mask) = pair[0].map_or((0, 0), |n| (n << 4, 0xF0));
+            let (low, low_mask) = pair[1].map_or((0, 0), |n| (n, 0x0F));
+            TemplateByte {
+                value: high | low,
+                mask: high_mask | low_mask,
+            }
+        })
+        .collect())
+}
+
+/// A text template the file is compared against, starting at an offset
+pub struct Template {
+    pub path: PathBuf,
+    pub offset: usize,
+    bytes: Vec<TemplateByte>,
+    /// Bytes that don't match the template, including ones past the end of the file
+    pub mismatches: usize,
+}
+
+impl Template {
+    pub fn load(path: &Path, offset: usize, data: &[u8]) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read template {}", path.display()))?;
+        let bytes = parse_template(&text)?;
+
+        let mut template = Self {
+            path: path.to_owned(),
+            offset,
+            bytes,
+            mismatches: 0,
+        };
+        template.update(data);
+        Ok(template)
+    }
+
+    pub fn num_bytes(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Whether the byte at `pos` differs from the template. Bytes outside it never do.
+    pub fn mismatch_at(&self, pos: usize, byte: u8) -> bool {
+        pos.checked_sub(self.offset)
+            .and_then(|i| self.bytes.get(i))
+            .is_some_and(|expected| !expected.matches(byte))
+    }
+
+    /// Recounts the mismatches after the data changed
+    pub fn update(&mut self, data: &[u8]) {
+        self.mismatches = self
+            .bytes
+            .iter()
+            .enumerate()
+            .filter(|(i, expected)| {
+                data.get(self.offset + i)
+                    .is_none_or(|byte| !expected.matches(*byte))
+            })
+            .count();
+    }
+}
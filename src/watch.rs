@@ -0,0 +1,255 @@
+use std::{collections::VecDeque, time::Instant};
+
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bin_file::Endianness,
+    config::Config,
+    hex_view::{hex_view_combo, HexView},
+};
+
+/// Most watches that can be active at once, across all files
+pub const MAX_WATCHES: usize = 16;
+/// Number of bytes a watch can read
+const WATCH_WIDTHS: [usize; 4] = [1, 2, 4, 8];
+/// Number of reload events kept per file
+const MAX_WATCH_EVENTS: usize = 1000;
+
+/// An offset whose value is logged every time the file is reloaded
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Watch {
+    pub offset: usize,
+    /// Number of bytes read at the offset: 1, 2, 4 or 8
+    pub width: usize,
+}
+
+impl Watch {
+    pub fn is_valid(&self) -> bool {
+        WATCH_WIDTHS.contains(&self.width)
+    }
+
+    /// The bytes at the offset, or None when the file is too short
+    pub fn read<'a>(&self, data: &'a [u8]) -> Option<&'a [u8]> {
+        data.get(self.offset..self.offset.checked_add(self.width)?)
+    }
+}
+
+/// The values at a watched offset before and after a reload
+pub struct WatchEvent {
+    pub time: Instant,
+    pub watch: Watch,
+    pub old: Option<Vec<u8>>,
+    pub new: Option<Vec<u8>>,
+}
+
+/// Records one event per watch for a reload from `old` to `new`
+pub fn log_watches(log: &mut VecDeque<WatchEvent>, watches: &[Watch], old: &[u8], new: &[u8]) {
+    let time = Instant::now();
+    for watch in watches {
+        if log.len() == MAX_WATCH_EVENTS {
+            log.pop_front();
+        }
+        log.push_back(WatchEvent {
+            time,
+            watch: watch.clone(),
+            old: watch.read(old).map(<[u8]>::to_vec),
+            new: watch.read(new).map(<[u8]>::to_vec),
+        });
+    }
+}
+
+fn format_value(bytes: Option<&[u8]>, endianness: Endianness) -> String {
+    let Some(bytes) = bytes else {
+        return "(past end)".to_owned();
+    };
+
+    let mut padded = [0; 8];
+    let value = match endianness {
+        Endianness::Little => {
+            padded[..bytes.len()].copy_from_slice(bytes);
+            u64::from_le_bytes(padded)
+        }
+        Endianness::Big => {
+            padded[8 - bytes.len()..].copy_from_slice(bytes);
+            u64::from_be_bytes(padded)
+        }
+    };
+    format!("0x{:0width$X}", value, width = bytes.len() * 2)
+}
+
+/// Adds watches and shows the values logged at each reload
+pub struct WatchPanel {
+    pub show: bool,
+    file: Option<usize>,
+    offset: String,
+    width: usize,
+    error: Option<String>,
+}
+
+impl Default for WatchPanel {
+    fn default() -> Self {
+        Self {
+            show: false,
+            file: None,
+            offset: "0x".to_owned(),
+            width: 4,
+            error: None,
+        }
+    }
+}
+
+impl WatchPanel {
+    /// Saves the view's watches to its file config
+    fn save(hv: &HexView, config: &mut Config) {
+        if let Some(file_config) = config.files.iter_mut().find(|a| a.path == hv.file.path) {
+            file_config.watches = hv.watches.clone();
+            config.changed = true;
+        }
+    }
+
+    pub fn display(&mut self, ctx: &egui::Context, hex_views: &mut [HexView], config: &mut Config) {
+        let mut open = self.show;
+
+        egui::Window::new("Watch")
+            .open(&mut open)
+            .default_height(300.0)
+            .show(ctx, |ui| {
+                let num_watches: usize = hex_views.iter().map(|hv| hv.watches.len()).sum();
+
+                if self.file.is_none() {
+                    self.file = hex_views.first().map(|hv| hv.id);
+                }
+                hex_view_combo(ui, "File", &mut self.file, hex_views);
+
+                ui.horizontal(|ui| {
+                    ui.label("Offset");
+                    ui.add(egui::TextEdit::singleline(&mut self.offset).desired_width(100.0));
+                    ui.label("Width");
+                    for width in WATCH_WIDTHS {
+                        ui.radio_value(&mut self.width, width, width.to_string());
+                    }
+                });
+
+                let hv = self
+                    .file
+                    .and_then(|id| hex_views.iter_mut().find(|hv| hv.id == id));
+                let can_add = hv.is_some() && num_watches < MAX_WATCHES;
+                let res = ui.add_enabled(can_add, egui::Button::new("Add watch"));
+                let res = match num_watches < MAX_WATCHES {
+                    true => res,
+                    false => res.on_disabled_hover_text(format!(
+                        "At most {} offsets can be watched",
+                        MAX_WATCHES
+                    )),
+                };
+                if let (true, Some(hv)) = (res.clicked(), hv) {
+                    match parse_int::parse::<usize>(self.offset.trim()) {
+                        Ok(offset) => {
+                            let watch = Watch {
+                                offset,
+                                width: self.width,
+                            };
+                            if !hv.watches.contains(&watch) {
+                                hv.watches.push(watch);
+                                Self::save(hv, config);
+                            }
+                            self.error = None;
+                        }
+                        Err(_) => {
+                            self.error = Some(format!("Invalid offset \"{}\"", self.offset.trim()))
+                        }
+                    }
+                }
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+
+                ui.separator();
+
+                egui::Grid::new("watch_list")
+                    .striped(true)
+                    .num_columns(4)
+                    .show(ui, |ui| {
+                        for hv in hex_views.iter_mut() {
+                            let mut remove = None;
+                            for (i, watch) in hv.watches.iter().enumerate() {
+                                ui.label(hv.file_name());
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "0x{:X} ({} bytes)",
+                                        watch.offset, watch.width
+                                    ))
+                                    .monospace(),
+                                );
+                                let value =
+                                    format_value(watch.read(&hv.file.data), hv.file.endianness);
+                                ui.label(egui::RichText::new(value).monospace());
+                                if ui
+                                    .button(egui_phosphor::regular::TRASH)
+                                    .on_hover_text("Stop watching this offset")
+                                    .clicked()
+                                {
+                                    remove = Some(i);
+                                }
+                                ui.end_row();
+                            }
+                            if let Some(i) = remove {
+                                hv.watches.remove(i);
+                                Self::save(hv, config);
+                            }
+                        }
+                    });
+
+                ui.separator();
+                ui.label("Reloads");
+
+                egui::ScrollArea::vertical()
+                    .id_source("watch_log_scroll")
+                    .show(ui, |ui| {
+                        egui::Grid::new("watch_log")
+                            .striped(true)
+                            .num_columns(4)
+                            .show(ui, |ui| {
+                                // Newest first
+                                let mut events: Vec<(&HexView, &WatchEvent)> = hex_views
+                                    .iter()
+                                    .flat_map(|hv| hv.watch_log.iter().map(move |e| (hv, e)))
+                                    .collect();
+                                events.sort_by_key(|(_, event)| std::cmp::Reverse(event.time));
+
+                                for (hv, event) in events {
+                                    let since_open = event.time.duration_since(hv.stats.opened_at);
+                                    ui.label(
+                                        egui::RichText::new(format!(
+                                            "+{:.1}s",
+                                            since_open.as_secs_f32()
+                                        ))
+                                        .monospace(),
+                                    )
+                                    .on_hover_text("Time since the file was opened");
+                                    ui.label(hv.file_name());
+                                    ui.label(
+                                        egui::RichText::new(format!("0x{:X}", event.watch.offset))
+                                            .monospace(),
+                                    );
+                                    let old =
+                                        format_value(event.old.as_deref(), hv.file.endianness);
+                                    let new =
+                                        format_value(event.new.as_deref(), hv.file.endianness);
+                                    let text = egui::RichText::new(format!("{} → {}", old, new))
+                                        .monospace();
+                                    match event.old == event.new {
+                                        true => ui.label(text),
+                                        false => ui.label(text.strong()),
+                                    };
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        self.show = open;
+    }
+}
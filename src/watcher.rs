@@ -1,28 +1,60 @@
 use std::{
+    fmt,
     path::PathBuf,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
+    time::Duration,
 };
 
 use notify::Watcher;
 
+/// Either the platform's native watcher or one that polls at a fixed interval.
+/// The path is watched for as long as this is kept around.
+pub enum FileWatcher {
+    Native(notify::RecommendedWatcher),
+    // Native watchers don't see changes made on most network filesystems
+    Poll(notify::PollWatcher),
+}
+
+impl fmt::Debug for FileWatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileWatcher::Native(watcher) => f.debug_tuple("Native").field(watcher).finish(),
+            FileWatcher::Poll(watcher) => f.debug_tuple("Poll").field(watcher).finish(),
+        }
+    }
+}
+
 pub fn create_watcher<P: Into<PathBuf>>(
     path: P,
     modified: Arc<AtomicBool>,
-) -> notify::Result<notify::RecommendedWatcher> {
-    let mut watcher =
-        notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
-            Ok(event) => {
-                if let notify::EventKind::Modify(_) = event.kind {
-                    modified.store(true, Ordering::Relaxed);
-                }
+    poll_interval: Option<Duration>,
+) -> notify::Result<FileWatcher> {
+    let handler = move |res: notify::Result<notify::Event>| match res {
+        Ok(event) => {
+            if let notify::EventKind::Modify(_) = event.kind {
+                modified.store(true, Ordering::Relaxed);
             }
-            Err(e) => println!("watch error: {:?}", e),
-        })?;
-
-    watcher.watch(&path.into(), notify::RecursiveMode::NonRecursive)?;
+        }
+        Err(e) => println!("watch error: {:?}", e),
+    };
+    let path = path.into();
 
-    Ok(watcher)
+    match poll_interval {
+        Some(interval) => {
+            let mut watcher = notify::PollWatcher::new(
+                handler,
+                notify::Config::default().with_poll_interval(interval),
+            )?;
+            watcher.watch(&path, notify::RecursiveMode::NonRecursive)?;
+            Ok(FileWatcher::Poll(watcher))
+        }
+        None => {
+            let mut watcher = notify::recommended_watcher(handler)?;
+            watcher.watch(&path, notify::RecursiveMode::NonRecursive)?;
+            Ok(FileWatcher::Native(watcher))
+        }
+    }
 }
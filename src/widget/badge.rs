@@ -0,0 +1,49 @@
+use eframe::{
+    egui::{Response, Sense, TextStyle, Ui, Widget, WidgetText},
+    epaint::{vec2, Color32},
+};
+
+/// A small colored pill with a short label, like a count or a tag
+#[must_use = "You should put this widget in an ui with `ui.add(widget);`"]
+pub struct Badge {
+    text: String,
+    color: Color32,
+}
+
+impl Badge {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            color: Color32::RED,
+        }
+    }
+
+    /// Set the fill color of the badge.
+    ///
+    pub fn color(mut self, color: Color32) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+impl Widget for Badge {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let Badge { text, color } = self;
+
+        let galley =
+            WidgetText::from(text).into_galley(ui, Some(false), f32::INFINITY, TextStyle::Small);
+
+        let height = galley.size().y + 2.0;
+        let width = (galley.size().x + 8.0).max(height);
+        let (rect, response) = ui.allocate_exact_size(vec2(width, height), Sense::hover());
+
+        if ui.is_rect_visible(rect) {
+            let painter = ui.painter();
+            painter.rect_filled(rect, height / 2.0, color);
+            let text_pos = rect.center() - galley.size() / 2.0;
+            painter.galley(text_pos, galley, Color32::WHITE);
+        }
+
+        response
+    }
+}
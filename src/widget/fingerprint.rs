@@ -0,0 +1,71 @@
+use eframe::{
+    egui::{Response, Sense, Ui, Widget},
+    epaint::{pos2, vec2, Color32, Rect},
+};
+
+pub const FINGERPRINT_SIDE: usize = 16;
+pub const FINGERPRINT_SIZE: usize = FINGERPRINT_SIDE * FINGERPRINT_SIDE;
+
+/// Splits the data into 256 chunks and averages the byte values of each one
+pub fn compute_fingerprint(data: &[u8]) -> [u8; FINGERPRINT_SIZE] {
+    let mut ret = [0; FINGERPRINT_SIZE];
+
+    if data.is_empty() {
+        return ret;
+    }
+
+    let chunk_size = data.len().div_ceil(FINGERPRINT_SIZE);
+    for (pixel, chunk) in ret.iter_mut().zip(data.chunks(chunk_size)) {
+        let sum: usize = chunk.iter().map(|b| *b as usize).sum();
+        *pixel = (sum / chunk.len()) as u8;
+    }
+
+    ret
+}
+
+#[must_use = "You should put this widget in an ui with `ui.add(widget);`"]
+pub struct Fingerprint<'a> {
+    pixels: &'a [u8; FINGERPRINT_SIZE],
+    pixel_size: f32,
+}
+
+impl<'a> Fingerprint<'a> {
+    pub fn new(pixels: &'a [u8; FINGERPRINT_SIZE]) -> Self {
+        Self {
+            pixels,
+            pixel_size: 1.0,
+        }
+    }
+
+    /// Set the size of each pixel.
+    ///
+    pub fn pixel_size(mut self, size: f32) -> Self {
+        self.pixel_size = size;
+        self
+    }
+}
+
+impl Widget for Fingerprint<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let Fingerprint { pixels, pixel_size } = self;
+
+        let side = FINGERPRINT_SIDE as f32 * pixel_size;
+        let (rect, response) = ui.allocate_exact_size(vec2(side, side), Sense::hover());
+
+        if ui.is_rect_visible(rect) {
+            let painter = ui.painter();
+            for (i, value) in pixels.iter().enumerate() {
+                let x = (i % FINGERPRINT_SIDE) as f32 * pixel_size;
+                let y = (i / FINGERPRINT_SIDE) as f32 * pixel_size;
+                let min = pos2(rect.min.x + x, rect.min.y + y);
+                painter.rect_filled(
+                    Rect::from_min_size(min, vec2(pixel_size, pixel_size)),
+                    0.0,
+                    Color32::from_gray(*value),
+                );
+            }
+        }
+
+        response
+    }
+}
@@ -1 +1,5 @@
+pub(crate) mod badge;
+pub(crate) mod fingerprint;
+pub(crate) mod scrollbar_overlay;
 pub(crate) mod spacer;
+pub(crate) mod sparkline;
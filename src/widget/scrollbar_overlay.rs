@@ -0,0 +1,115 @@
+use std::ops::Range;
+
+use eframe::{
+    egui::{Response, Sense, Ui, Widget},
+    epaint::{vec2, Color32, Rect, Stroke},
+};
+
+/// A narrow bar representing the whole file, with marks for where the diffs,
+/// the selection and the visible part of the file are
+#[must_use = "You should put this widget in an ui with `ui.add(widget);`"]
+pub struct ScrollbarOverlay<'a> {
+    len: usize,
+    viewport: Range<usize>,
+    selection: Option<Range<usize>>,
+    /// Whether each pixel row of the bar has a diff in it
+    ticks: &'a [bool],
+    size: (f32, f32),
+    tick_color: Color32,
+    selection_color: Color32,
+}
+
+impl<'a> ScrollbarOverlay<'a> {
+    pub fn new(len: usize, viewport: Range<usize>) -> Self {
+        Self {
+            len,
+            viewport,
+            selection: None,
+            ticks: &[],
+            size: (12.0, 100.0),
+            tick_color: Color32::RED,
+            selection_color: Color32::DARK_GREEN,
+        }
+    }
+
+    /// Set the width and height of the bar.
+    ///
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.size = (width, height);
+        self
+    }
+
+    /// Set the pixel rows to mark as containing diffs, and their color.
+    ///
+    pub fn ticks(mut self, ticks: &'a [bool], color: Color32) -> Self {
+        self.ticks = ticks;
+        self.tick_color = color;
+        self
+    }
+
+    /// Set the selected byte range and its color.
+    ///
+    pub fn selection(mut self, selection: Option<Range<usize>>, color: Color32) -> Self {
+        self.selection = selection;
+        self.selection_color = color;
+        self
+    }
+
+    /// The offset at the point where the bar was clicked or dragged
+    pub fn clicked_offset(response: &Response, len: usize) -> Option<usize> {
+        if !(response.clicked() || response.dragged()) {
+            return None;
+        }
+        let pos = response.interact_pointer_pos()?;
+        let fraction = ((pos.y - response.rect.top()) / response.rect.height()).clamp(0.0, 1.0);
+        Some(((fraction * len as f32) as usize).min(len.saturating_sub(1)))
+    }
+}
+
+impl Widget for ScrollbarOverlay<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let ScrollbarOverlay {
+            len,
+            viewport,
+            selection,
+            ticks,
+            size,
+            tick_color,
+            selection_color,
+        } = self;
+
+        let (rect, response) =
+            ui.allocate_exact_size(vec2(size.0, size.1), Sense::click_and_drag());
+
+        if ui.is_rect_visible(rect) && len > 0 {
+            let painter = ui.painter();
+            let y_at = |offset: usize| rect.top() + offset as f32 / len as f32 * rect.height();
+            let band = |range: &Range<usize>| {
+                Rect::from_x_y_ranges(
+                    rect.x_range(),
+                    y_at(range.start)..=y_at(range.end).max(y_at(range.start) + 1.0),
+                )
+            };
+
+            painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+            painter.rect_filled(band(&viewport), 0.0, ui.visuals().widgets.inactive.bg_fill);
+
+            if let Some(selection) = &selection {
+                painter.rect_filled(band(selection), 0.0, selection_color);
+            }
+
+            for (i, _) in ticks.iter().enumerate().filter(|(_, tick)| **tick) {
+                let y = rect.top() + i as f32 + 0.5;
+                painter.hline(rect.x_range(), y, Stroke::new(1.0, tick_color));
+            }
+
+            painter.rect_stroke(
+                band(&viewport),
+                0.0,
+                Stroke::new(1.0, ui.visuals().widgets.active.fg_stroke.color),
+            );
+        }
+
+        response
+    }
+}
@@ -0,0 +1,66 @@
+use eframe::{
+    egui::{Response, Sense, Ui, Widget},
+    epaint::{pos2, vec2, Color32, Shape, Stroke},
+};
+
+/// A tiny line chart without axes, for showing a trend at a glance
+#[must_use = "You should put this widget in an ui with `ui.add(widget);`"]
+pub struct Sparkline {
+    values: Vec<f32>,
+    width: f32,
+    color: Color32,
+}
+
+impl Sparkline {
+    pub fn new(values: impl IntoIterator<Item = f32>) -> Self {
+        Self {
+            values: values.into_iter().collect(),
+            width: 60.0,
+            color: Color32::LIGHT_BLUE,
+        }
+    }
+
+    /// Set the color of the line.
+    ///
+    pub fn color(mut self, color: Color32) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+impl Widget for Sparkline {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let Sparkline {
+            values,
+            width,
+            color,
+        } = self;
+
+        let height = ui.spacing().interact_size.y;
+        let (rect, response) = ui.allocate_exact_size(vec2(width, height), Sense::hover());
+
+        if ui.is_rect_visible(rect) && !values.is_empty() {
+            let rect = rect.shrink(2.0);
+            let max = values.iter().copied().fold(0.0, f32::max).max(1.0);
+            let step = match values.len() {
+                1 => 0.0,
+                n => rect.width() / (n - 1) as f32,
+            };
+            let points = values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    pos2(
+                        rect.left() + i as f32 * step,
+                        rect.bottom() - value / max * rect.height(),
+                    )
+                })
+                .collect();
+
+            ui.painter()
+                .add(Shape::line(points, Stroke::new(1.0, color)));
+        }
+
+        response
+    }
+}